@@ -1,36 +1,167 @@
 #![allow(dead_code)]
+pub mod provider;
+pub mod rules;
+
 use crate::config::Config;
 use crate::health::IncidentLog;
 use anyhow::Result;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Longest summary we put in a chat message (Telegram captions max out at 1024 chars)
+const SUMMARY_MAX_CHARS: usize = 900;
 
-/// Analyze an incident using OpenClaw's LLM
-pub async fn analyze_incident(cfg: &Config) -> Result<String> {
-    // Read OpenClaw provider config
-    let provider_cfg = cfg.read_openclaw_providers()?;
+/// A completed analysis, saved to disk as a Markdown report
+pub struct AnalysisReport {
+    pub id: String,
+    pub path: PathBuf,
+    pub analysis: String,
+    pub report: String,
+}
 
-    // Extract API configuration
-    let (api_key, base_url, model) = extract_api_config(&provider_cfg)?;
+/// Analyze an incident, write the report next to the backups and return it
+pub async fn run_analysis(
+    cfg: &Config,
+    incident: &IncidentLog,
+    backup_id: Option<&str>,
+    offline: bool,
+) -> Result<AnalysisReport> {
+    let redactor = crate::redact::Redactor::for_config(cfg);
+    let analysis = redactor.redact(&analyze_incident(cfg, incident, offline).await?);
+    let report = redactor.redact(&format_incident_report(&analysis, incident, backup_id));
+
+    let id = backup_id
+        .map(String::from)
+        .unwrap_or_else(|| chrono::Utc::now().format("%Y%m%d-%H%M%S").to_string());
+    let path = incident_report_path(cfg, &id);
+    std::fs::create_dir_all(&cfg.backup.path)?;
+    std::fs::write(&path, &report)?;
+
+    Ok(AnalysisReport {
+        id,
+        path,
+        analysis,
+        report,
+    })
+}
 
+/// Incident record for an analysis requested by a human rather than a failure
+pub fn on_demand_incident(requested_via: &str) -> IncidentLog {
+    IncidentLog {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        cause: format!("On-demand analysis (requested via {})", requested_via),
+        recovery: "n/a".to_string(),
+    }
+}
+
+/// Condense an analysis for a chat message: the root cause section if the
+/// model produced one, otherwise the opening lines, capped in length
+pub fn summarize(analysis: &str) -> String {
+    let lines: Vec<&str> = analysis.lines().collect();
+    let start = lines
+        .iter()
+        .position(|l| l.to_lowercase().contains("root cause"))
+        .unwrap_or(0);
+
+    let mut summary = String::new();
+    for line in &lines[start..] {
+        if line.trim().is_empty() && !summary.trim().is_empty() && summary.lines().count() > 1 {
+            break;
+        }
+        summary.push_str(line);
+        summary.push('\n');
+    }
+
+    let summary = summary.trim();
+    if summary.chars().count() > SUMMARY_MAX_CHARS {
+        let truncated: String = summary.chars().take(SUMMARY_MAX_CHARS).collect();
+        format!("{}…", truncated)
+    } else {
+        summary.to_string()
+    }
+}
+
+/// Analyze an incident. The offline rule-based analyzer always runs; its
+/// findings are given to OpenClaw's LLM as context, or returned on their own
+/// when `offline` is set or no model is reachable.
+pub async fn analyze_incident(
+    cfg: &Config,
+    incident: &IncidentLog,
+    offline: bool,
+) -> Result<String> {
     // Gather evidence
     let evidence = gather_evidence(cfg).await?;
+    let rule_report = rules::analyze(&evidence);
 
-    // Build prompt
-    let prompt = build_analysis_prompt(&evidence);
+    if offline {
+        return Ok(rule_report.to_markdown());
+    }
 
-    // Call LLM
-    let analysis = call_llm(&api_key, &base_url, &model, &prompt).await?;
+    match llm_analysis(cfg, incident, &evidence, &rule_report).await {
+        Ok(analysis) => Ok(analysis),
+        Err(e) => {
+            tracing::warn!("LLM analysis unavailable, using offline analysis: {}", e);
+            Ok(format!(
+                "{}\n_LLM analysis unavailable ({}); this is the offline rule-based analysis._\n",
+                rule_report.to_markdown(),
+                e
+            ))
+        }
+    }
+}
 
-    Ok(analysis)
+/// Quick offline classification of the current failure (gateway log tail and
+/// config validation), without the slower evidence gathering
+pub fn classify_current(cfg: &Config) -> Option<rules::Category> {
+    let evidence = Evidence {
+        gateway_log_tail: read_last_n_lines(&cfg.openclaw.config_path.join("gateway.log"), 100)
+            .unwrap_or_default(),
+        config_issues: config_errors(cfg),
+        config_diff: String::new(),
+        recent_incidents: String::new(),
+        workspace_changes: String::new(),
+    };
+    rules::analyze(&evidence).primary().map(|f| f.category)
+}
+
+/// Ask OpenClaw's LLM for an analysis
+async fn llm_analysis(
+    cfg: &Config,
+    incident: &IncidentLog,
+    evidence: &Evidence,
+    rule_report: &rules::RuleReport,
+) -> Result<String> {
+    // Read OpenClaw's config and pick the provider/model to talk to
+    let openclaw = cfg.read_openclaw_config()?;
+    let llm = provider::LlmProvider::from_openclaw(&openclaw)?;
+
+    // Build prompt, masking secrets before anything leaves the box
+    let prompt = build_analysis_prompt(incident, evidence, rule_report);
+    let prompt = crate::redact::redact(cfg, &prompt);
+
+    // Call LLM
+    llm.complete(&prompt).await
 }
 
 /// Format a complete incident report
-pub fn format_incident_report(analysis: &str, incident: &IncidentLog, backup_id: &str) -> String {
+pub fn format_incident_report(
+    analysis: &str,
+    incident: &IncidentLog,
+    backup_id: Option<&str>,
+) -> String {
+    let recovery = match backup_id {
+        Some(id) => format!(
+            "The agent has been restored from backup `{}`. All workspace files and configuration have been rolled back to the last known good state.",
+            id
+        ),
+        None => "No restore was performed as part of this analysis.".to_string(),
+    };
+
     format!(
         r#"# 🚨 Incident Report
 
 **Timestamp:** {}  
-**Backup Used:** {}
+**Backup Used:** {}  
+**Recovery Status:** {}
 
 ## Incident Summary
 
@@ -42,7 +173,7 @@ pub fn format_incident_report(analysis: &str, incident: &IncidentLog, backup_id:
 
 ## ✅ Recovery
 
-The agent has been restored from backup `{}`. All workspace files and configuration have been rolled back to the last known good state.
+{}
 
 ## 📋 Next Steps
 
@@ -55,18 +186,46 @@ The agent has been restored from backup `{}`. All workspace files and configurat
 *Generated by RescueClaw v{} at {}*
 "#,
         incident.timestamp,
-        backup_id,
+        backup_id.unwrap_or("none"),
+        incident.recovery,
         incident.cause,
         analysis,
-        backup_id,
+        recovery,
         env!("CARGO_PKG_VERSION"),
         chrono::Utc::now().to_rfc3339()
     )
 }
 
+/// Path of the incident report written for a given backup ID
+pub fn incident_report_path(cfg: &Config, id: &str) -> PathBuf {
+    cfg.backup.path.join(format!("incident-report-{}.md", id))
+}
+
+/// List saved incident report IDs, newest first
+pub fn list_incident_reports(cfg: &Config) -> Result<Vec<String>> {
+    if !cfg.backup.path.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut entries: Vec<_> = std::fs::read_dir(&cfg.backup.path)?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let id = name.strip_prefix("incident-report-")?.strip_suffix(".md")?;
+            let modified = e.metadata().and_then(|m| m.modified()).ok()?;
+            Some((modified, id.to_string()))
+        })
+        .collect();
+
+    entries.sort();
+    Ok(entries.into_iter().rev().map(|(_, id)| id).collect())
+}
+
 /// Evidence collected for analysis
 struct Evidence {
     gateway_log_tail: String,
+    /// Validation errors in the live OpenClaw config
+    config_issues: Vec<String>,
     config_diff: String,
     recent_incidents: String,
     workspace_changes: String,
@@ -82,14 +241,25 @@ async fn gather_evidence(cfg: &Config) -> Result<Evidence> {
         "Gateway log not found".to_string()
     };
 
+    // Check the live OpenClaw config
+    let config_issues = config_errors(cfg);
+
     // Read recent incidents
-    let recent_incidents = match crate::health::recent_incidents(cfg, 5) {
-        Ok(incidents) => incidents
+    let recent_incidents = match crate::incidents::IncidentStore::open(cfg).recent(5) {
+        Ok(incidents) if !incidents.is_empty() => incidents
             .iter()
-            .map(|i| format!("{}: {}", i.timestamp, i.cause))
+            .map(|i| {
+                format!(
+                    "{}: {} via {} (recovery: {})",
+                    i.started_at.to_rfc3339(),
+                    i.category,
+                    i.trigger,
+                    i.recovery.as_deref().unwrap_or("open")
+                )
+            })
             .collect::<Vec<_>>()
             .join("\n"),
-        Err(_) => "No incident history".to_string(),
+        _ => "No incident history".to_string(),
     };
 
     // Try to diff config (compare with latest backup)
@@ -103,17 +273,42 @@ async fn gather_evidence(cfg: &Config) -> Result<Evidence> {
 
     Ok(Evidence {
         gateway_log_tail,
+        config_issues,
         config_diff,
         recent_incidents,
         workspace_changes,
     })
 }
 
+/// Error-level validation messages for the live OpenClaw config
+fn config_errors(cfg: &Config) -> Vec<String> {
+    crate::validate::Validator::for_config(cfg)
+        .check_config(&cfg.openclaw.config_path)
+        .into_iter()
+        .filter(|i| i.severity == crate::validate::Severity::Error)
+        .map(|i| i.message)
+        .collect()
+}
+
 /// Build the analysis prompt
-fn build_analysis_prompt(evidence: &Evidence) -> String {
+fn build_analysis_prompt(
+    incident: &IncidentLog,
+    evidence: &Evidence,
+    rule_report: &rules::RuleReport,
+) -> String {
+    let config_issues = if evidence.config_issues.is_empty() {
+        "None".to_string()
+    } else {
+        evidence.config_issues.join("\n")
+    };
+
     format!(
         r#"You are an incident analyst for an AI agent system (OpenClaw).
-The agent became unresponsive and was auto-restored from backup.
+Incident: {} (recovery: {})
+
+An offline rule-based pre-analysis produced the following. Confirm or refute it using the evidence:
+
+{}
 
 Analyze the following evidence and provide:
 1. Most likely root cause
@@ -127,6 +322,11 @@ Be concise and specific. Focus on actionable insights.
 {}
 ```
 
+## Config Validation Errors:
+```
+{}
+```
+
 ## Config Changes:
 ```
 {}
@@ -143,137 +343,71 @@ Be concise and specific. Focus on actionable insights.
 ```
 
 Provide your analysis in clear sections: Root Cause, What Changed, and Recommendations."#,
+        incident.cause,
+        incident.recovery,
+        rule_report.to_markdown(),
         evidence.gateway_log_tail,
+        config_issues,
         evidence.config_diff,
         evidence.recent_incidents,
         evidence.workspace_changes
     )
 }
 
-/// Extract API configuration from OpenClaw provider config
-fn extract_api_config(
-    provider_cfg: &crate::config::OpenClawProviderConfig,
-) -> Result<(String, String, String)> {
-    let providers = provider_cfg
-        .providers
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("No providers in OpenClaw config"))?;
-
-    // Try to find OpenRouter first (cheap, works well)
-    if let Some(openrouter) = providers.get("openrouter") {
-        if let Some(api_key) = openrouter.get("apiKey").and_then(|v| v.as_str()) {
-            return Ok((
-                api_key.to_string(),
-                "https://openrouter.ai/api/v1".to_string(),
-                "anthropic/claude-3.5-haiku".to_string(), // Cheap and good
-            ));
-        }
-    }
-
-    // Try Anthropic
-    if let Some(anthropic) = providers.get("anthropic") {
-        if let Some(api_key) = anthropic.get("apiKey").and_then(|v| v.as_str()) {
-            let model = provider_cfg
-                .default_model
-                .as_deref()
-                .unwrap_or("claude-3-5-haiku-20241022");
-            return Ok((
-                api_key.to_string(),
-                "https://api.anthropic.com/v1".to_string(),
-                model.to_string(),
-            ));
-        }
-    }
-
-    // Fallback: use first available provider
-    if let Some(providers_obj) = providers.as_object() {
-        for (name, provider) in providers_obj {
-            if let Some(api_key) = provider.get("apiKey").and_then(|v| v.as_str()) {
-                let base_url = provider
-                    .get("baseUrl")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("https://api.openai.com/v1");
-                let model = provider_cfg
-                    .default_model
-                    .as_deref()
-                    .unwrap_or("gpt-4o-mini");
-
-                tracing::info!("Using provider '{}' for analysis", name);
-                return Ok((api_key.to_string(), base_url.to_string(), model.to_string()));
-            }
-        }
-    }
-
-    anyhow::bail!("No valid API provider found in OpenClaw config")
-}
-
-/// Call LLM API
-async fn call_llm(api_key: &str, base_url: &str, model: &str, prompt: &str) -> Result<String> {
-    let client = reqwest::Client::new();
-
-    // Build request - try OpenAI-compatible format first
-    let body = serde_json::json!({
-        "model": model,
-        "messages": [
-            {
-                "role": "user",
-                "content": prompt
-            }
-        ],
-        "temperature": 0.7,
-        "max_tokens": 1000
-    });
-
-    let url = format!("{}/chat/completions", base_url);
-
-    let response = client
-        .post(&url)
-        .header("Authorization", format!("Bearer {}", api_key))
-        .header("Content-Type", "application/json")
-        .json(&body)
-        .timeout(std::time::Duration::from_secs(30))
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await?;
-        anyhow::bail!("LLM API error ({}): {}", status, text);
-    }
-
-    let json: serde_json::Value = response.json().await?;
-
-    // Extract response
-    let content = json["choices"][0]["message"]["content"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("Failed to parse LLM response"))?;
-
-    Ok(content.to_string())
-}
-
 /// Read last N lines from a file
-fn read_last_n_lines(path: &Path, n: usize) -> Result<String> {
+pub fn read_last_n_lines(path: &Path, n: usize) -> Result<String> {
     let content = std::fs::read_to_string(path)?;
     let lines: Vec<&str> = content.lines().collect();
     let start = lines.len().saturating_sub(n);
     Ok(lines[start..].join("\n"))
 }
 
-/// Extract config diff by comparing current with backup
+/// Extract config diff by comparing current with the latest backup
 async fn extract_config_diff(cfg: &Config) -> Result<String> {
-    // Get latest backup
-    let snapshots = crate::backup::list_snapshots(cfg)?;
-    if snapshots.is_empty() {
-        return Ok("No backups available for comparison".to_string());
-    }
+    let snapshot = match crate::backup::find_snapshot(cfg, None) {
+        Ok(s) => s,
+        Err(_) => return Ok("No backups available for comparison".to_string()),
+    };
 
-    // For now, just return a placeholder
-    // Full implementation would extract tar.gz, compare JSONs
-    Ok("Config comparison not yet implemented".to_string())
+    let diff = crate::backup::diff_snapshot_config(cfg, &snapshot)?;
+    if diff.is_empty() {
+        Ok(format!("No config changes since backup {}", snapshot.id))
+    } else {
+        Ok(format!("Changes since backup {}:\n{}", snapshot.id, diff))
+    }
 }
 
+/// Most recent journal entries to include as evidence
+const MAX_JOURNAL_LINES: usize = 40;
+
 /// Check for recent workspace file changes
 fn check_recent_workspace_changes(cfg: &Config) -> Result<String> {
+    // Prefer the daemon's change journal; fall back to memory/ mtimes
+    let journaled =
+        crate::journal::changes_since(cfg, chrono::Utc::now() - chrono::Duration::hours(24))?;
+    if !journaled.is_empty() {
+        let mut lines: Vec<String> = journaled
+            .iter()
+            .rev()
+            .take(MAX_JOURNAL_LINES)
+            .map(|c| format!("{} {} {}", c.at.to_rfc3339(), c.kind, c.path))
+            .collect();
+        lines.reverse();
+        if journaled.len() > MAX_JOURNAL_LINES {
+            lines.insert(
+                0,
+                format!(
+                    "({} earlier changes omitted)",
+                    journaled.len() - MAX_JOURNAL_LINES
+                ),
+            );
+        }
+        return Ok(format!(
+            "Changes in the last 24h (+ created, ~ modified, - deleted):\n{}",
+            lines.join("\n")
+        ));
+    }
+
     let memory_dir = cfg.openclaw.workspace.join("memory");
     if !memory_dir.exists() {
         return Ok("Memory directory not found".to_string());
@@ -304,3 +438,35 @@ fn check_recent_workspace_changes(cfg: &Config) -> Result<String> {
         Ok(format!("Recently modified: {}", recent_files.join(", ")))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_prefers_root_cause() {
+        let analysis = "Intro text\n\n## Root Cause\nBad config.apply broke JSON\n\n## Recommendations\nValidate first";
+        let summary = summarize(analysis);
+        assert!(summary.starts_with("## Root Cause"));
+        assert!(summary.contains("Bad config.apply"));
+        assert!(!summary.contains("Validate first"));
+    }
+
+    #[test]
+    fn test_summarize_truncates() {
+        let analysis = "x".repeat(5000);
+        assert!(summarize(&analysis).chars().count() <= SUMMARY_MAX_CHARS + 1);
+    }
+
+    #[test]
+    fn test_report_without_restore() {
+        let incident = IncidentLog {
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            cause: "On-demand analysis".to_string(),
+            recovery: "n/a".to_string(),
+        };
+        let report = format_incident_report("All good", &incident, None);
+        assert!(report.contains("No restore was performed"));
+        assert!(report.contains("**Backup Used:** none"));
+    }
+}
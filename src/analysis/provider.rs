@@ -0,0 +1,402 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use crate::config::{OpenClawFile, ProviderEntry};
+
+/// Anthropic API version sent with every Messages request
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Maximum tokens requested for an analysis
+const MAX_TOKENS: u32 = 1000;
+
+/// Which wire protocol a provider speaks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ApiKind {
+    /// `POST {base}/chat/completions` with a Bearer token (OpenAI, OpenRouter, most proxies)
+    OpenAi,
+    /// `POST {base}/messages` with `x-api-key` and `anthropic-version`
+    Anthropic,
+    /// `POST {base}/api/chat` on an Ollama or other local server, no auth
+    Ollama,
+}
+
+impl ApiKind {
+    /// Pick the protocol for a provider entry: an explicit `api` field wins,
+    /// then the provider name, then the base URL
+    fn detect(name: &str, api: Option<&str>, base_url: Option<&str>) -> Self {
+        let api = api.unwrap_or("");
+        let name = name.to_lowercase();
+        let base_url = base_url.unwrap_or("");
+
+        if api.contains("anthropic") {
+            ApiKind::Anthropic
+        } else if api.contains("ollama") {
+            ApiKind::Ollama
+        } else if !api.is_empty() {
+            ApiKind::OpenAi
+        } else if name == "anthropic" || base_url.contains("api.anthropic.com") {
+            ApiKind::Anthropic
+        } else if name == "ollama" || base_url.contains(":11434") {
+            ApiKind::Ollama
+        } else {
+            ApiKind::OpenAi
+        }
+    }
+
+    fn default_base_url(self) -> &'static str {
+        match self {
+            ApiKind::OpenAi => "https://api.openai.com/v1",
+            ApiKind::Anthropic => "https://api.anthropic.com/v1",
+            ApiKind::Ollama => "http://127.0.0.1:11434",
+        }
+    }
+}
+
+/// An LLM endpoint resolved from the OpenClaw provider config
+#[derive(Debug, Clone)]
+pub struct LlmProvider {
+    pub name: String,
+    pub kind: ApiKind,
+    pub base_url: String,
+    pub api_key: Option<String>,
+    pub model: String,
+    /// Per-request timeout
+    pub timeout: Duration,
+    /// Extra attempts after the first one for network errors, 429 and 5xx
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles on each subsequent retry
+    pub retry_backoff: Duration,
+}
+
+impl LlmProvider {
+    pub fn new(
+        name: impl Into<String>,
+        kind: ApiKind,
+        base_url: impl Into<String>,
+        api_key: Option<String>,
+        model: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key,
+            model: model.into(),
+            timeout: Duration::from_secs(60),
+            max_retries: 2,
+            retry_backoff: Duration::from_secs(2),
+        }
+    }
+
+    /// Choose a provider from OpenClaw's config: the provider of the agent's
+    /// primary model, then OpenRouter, Anthropic and any other provider with
+    /// a resolvable API key, then a keyless local server
+    pub fn from_openclaw(oc: &OpenClawFile) -> Result<Self> {
+        let primary_provider = oc.primary_model.as_ref().and_then(|m| m.provider.clone());
+
+        let configured = oc.provider_names();
+        let mut names: Vec<String> = primary_provider.iter().cloned().collect();
+        names.extend(
+            ["openrouter", "anthropic"]
+                .iter()
+                .filter(|n| configured.iter().any(|c| c == *n))
+                .map(|n| n.to_string()),
+        );
+        names.extend(configured.iter().cloned());
+        let mut seen = std::collections::HashSet::new();
+        names.retain(|n| seen.insert(n.clone()));
+
+        let mut local = None;
+        for name in &names {
+            let entry = oc.providers.get(name);
+            let base_url = entry.and_then(|e| e.base_url.as_deref());
+            let kind = ApiKind::detect(name, entry.and_then(|e| e.api.as_deref()), base_url);
+            let base_url = base_url.unwrap_or(match name.as_str() {
+                "openrouter" => "https://openrouter.ai/api/v1",
+                _ => kind.default_base_url(),
+            });
+            let model = model_for(oc, name, kind, entry);
+            let is_primary = primary_provider.as_deref() == Some(name.as_str());
+
+            match oc.api_key_for(name) {
+                Some(api_key) => {
+                    tracing::info!("Using provider '{}' ({}) for analysis", name, model);
+                    return Ok(Self::new(name, kind, base_url, Some(api_key), model));
+                }
+                None if kind == ApiKind::Ollama && (is_primary || local.is_none()) => {
+                    let provider = Self::new(name, kind, base_url, None, model);
+                    if is_primary {
+                        return Ok(provider);
+                    }
+                    local = Some(provider);
+                }
+                None => {}
+            }
+        }
+
+        let provider = local
+            .ok_or_else(|| anyhow::anyhow!("No valid API provider found in OpenClaw config"))?;
+        tracing::info!("Using local provider '{}' for analysis", provider.name);
+        Ok(provider)
+    }
+
+    /// Send a single-turn prompt and return the model's text reply
+    pub async fn complete(&self, prompt: &str) -> Result<String> {
+        let client = reqwest::Client::builder().timeout(self.timeout).build()?;
+        let mut backoff = self.retry_backoff;
+        let mut attempt = 0;
+
+        loop {
+            match self.send(&client, prompt).await {
+                Ok(text) => return Ok(text),
+                Err(e) if e.retryable && attempt < self.max_retries => {
+                    attempt += 1;
+                    tracing::warn!(
+                        "LLM request to '{}' failed ({}), retry {}/{} in {:?}",
+                        self.name,
+                        e.error,
+                        attempt,
+                        self.max_retries,
+                        backoff
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err(e) => return Err(e.error),
+            }
+        }
+    }
+
+    /// One HTTP round trip
+    async fn send(&self, client: &reqwest::Client, prompt: &str) -> Result<String, AttemptError> {
+        let request = match self.kind {
+            ApiKind::OpenAi => {
+                let mut req = client
+                    .post(format!("{}/chat/completions", self.base_url))
+                    .json(&json!({
+                        "model": self.model,
+                        "messages": [{ "role": "user", "content": prompt }],
+                        "temperature": 0.7,
+                        "max_tokens": MAX_TOKENS,
+                    }));
+                if let Some(key) = &self.api_key {
+                    req = req.bearer_auth(key);
+                }
+                req
+            }
+            ApiKind::Anthropic => client
+                .post(format!("{}/messages", self.base_url))
+                .header("x-api-key", self.api_key.as_deref().unwrap_or_default())
+                .header("anthropic-version", ANTHROPIC_VERSION)
+                .json(&json!({
+                    "model": self.model,
+                    "max_tokens": MAX_TOKENS,
+                    "messages": [{ "role": "user", "content": prompt }],
+                })),
+            ApiKind::Ollama => client
+                .post(format!(
+                    "{}/api/chat",
+                    self.base_url.trim_end_matches("/v1")
+                ))
+                .json(&json!({
+                    "model": self.model,
+                    "messages": [{ "role": "user", "content": prompt }],
+                    "stream": false,
+                })),
+        };
+
+        let response = request.send().await.map_err(AttemptError::retryable)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            let error = anyhow::anyhow!("LLM API error ({}): {}", status, text);
+            return Err(if status.as_u16() == 429 || status.is_server_error() {
+                AttemptError::retryable(error)
+            } else {
+                AttemptError::fatal(error)
+            });
+        }
+
+        let json: Value = response.json().await.map_err(AttemptError::retryable)?;
+        self.extract_text(&json)
+            .ok_or_else(|| AttemptError::fatal(anyhow::anyhow!("Failed to parse LLM response")))
+    }
+
+    /// Pull the reply text out of a provider-specific response body
+    fn extract_text(&self, json: &Value) -> Option<String> {
+        match self.kind {
+            ApiKind::OpenAi => json["choices"][0]["message"]["content"]
+                .as_str()
+                .map(String::from),
+            ApiKind::Anthropic => {
+                let text: Vec<&str> = json["content"]
+                    .as_array()?
+                    .iter()
+                    .filter(|block| block["type"] == "text")
+                    .filter_map(|block| block["text"].as_str())
+                    .collect();
+                (!text.is_empty()).then(|| text.join(""))
+            }
+            ApiKind::Ollama => json["message"]["content"].as_str().map(String::from),
+        }
+    }
+}
+
+/// Outcome of a failed attempt, tagged with whether retrying can help
+struct AttemptError {
+    error: anyhow::Error,
+    retryable: bool,
+}
+
+impl AttemptError {
+    fn retryable(error: impl Into<anyhow::Error>) -> Self {
+        Self {
+            error: error.into(),
+            retryable: true,
+        }
+    }
+
+    fn fatal(error: impl Into<anyhow::Error>) -> Self {
+        Self {
+            error: error.into(),
+            retryable: false,
+        }
+    }
+}
+
+/// Model to ask for from a provider: the agent's primary model if it lives
+/// there, else the provider's first listed model, else a cheap default
+fn model_for(
+    oc: &OpenClawFile,
+    name: &str,
+    kind: ApiKind,
+    entry: Option<&ProviderEntry>,
+) -> String {
+    if let Some(primary) = &oc.primary_model {
+        // Legacy configs name a bare model that applies to whichever provider is used
+        if primary.provider.as_deref() == Some(name) || (primary.provider.is_none() && oc.legacy) {
+            return primary.model.clone();
+        }
+    }
+
+    if let Some(model) = entry
+        .and_then(|e| e.models.as_ref())
+        .and_then(|m| m.first())
+    {
+        return model.clone();
+    }
+
+    match (name, kind) {
+        ("openrouter", _) => "anthropic/claude-3.5-haiku", // Cheap and good
+        (_, ApiKind::Anthropic) => "claude-3-5-haiku-20241022",
+        (_, ApiKind::Ollama) => "llama3.2",
+        (_, ApiKind::OpenAi) => "gpt-4o-mini",
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn openclaw(raw: Value) -> OpenClawFile {
+        OpenClawFile::from_value(raw, None)
+    }
+
+    #[test]
+    fn test_detect_kind() {
+        assert_eq!(ApiKind::detect("anthropic", None, None), ApiKind::Anthropic);
+        assert_eq!(
+            ApiKind::detect("proxy", Some("anthropic-messages"), None),
+            ApiKind::Anthropic
+        );
+        assert_eq!(
+            ApiKind::detect("local", None, Some("http://localhost:11434")),
+            ApiKind::Ollama
+        );
+        assert_eq!(
+            ApiKind::detect("ollama", Some("openai-completions"), None),
+            ApiKind::OpenAi
+        );
+        assert_eq!(ApiKind::detect("groq", None, None), ApiKind::OpenAi);
+    }
+
+    #[test]
+    fn test_primary_model_provider_preferred() {
+        let oc = openclaw(json!({
+            "agents": { "defaults": { "model": { "primary": "anthropic/claude-sonnet-4" } } },
+            "models": { "providers": {
+                "openrouter": { "apiKey": "sk-or" },
+                "anthropic": { "apiKey": "sk-ant" }
+            } }
+        }));
+        let p = LlmProvider::from_openclaw(&oc).unwrap();
+        assert_eq!(p.kind, ApiKind::Anthropic);
+        assert_eq!(p.base_url, "https://api.anthropic.com/v1");
+        assert_eq!(p.model, "claude-sonnet-4");
+    }
+
+    #[test]
+    fn test_auth_profile_key_used_for_anthropic() {
+        let oc = OpenClawFile::from_value(
+            json!({
+                "agents": { "defaults": { "model": { "primary": "anthropic/claude-opus-4" } } },
+                "auth": { "profiles": { "anthropic:default": { "provider": "anthropic", "mode": "api_key" } } }
+            }),
+            Some(&json!({ "profiles": { "anthropic:default": { "key": "sk-ant-store" } } })),
+        );
+        let p = LlmProvider::from_openclaw(&oc).unwrap();
+        assert_eq!(p.kind, ApiKind::Anthropic);
+        assert_eq!(p.api_key.as_deref(), Some("sk-ant-store"));
+        assert_eq!(p.model, "claude-opus-4");
+    }
+
+    #[test]
+    fn test_legacy_default_model_applies() {
+        let oc = openclaw(json!({
+            "defaultModel": "gpt-4",
+            "providers": { "zz-openai": { "apiKey": "sk-test", "baseUrl": "https://api.openai.com/v1" } }
+        }));
+        let p = LlmProvider::from_openclaw(&oc).unwrap();
+        assert_eq!(p.name, "zz-openai");
+        assert_eq!(p.kind, ApiKind::OpenAi);
+        assert_eq!(p.model, "gpt-4");
+    }
+
+    #[test]
+    fn test_keyless_ollama_used_as_last_resort() {
+        let oc = openclaw(json!({
+            "models": { "providers": {
+                "ollama": { "baseUrl": "http://127.0.0.1:11434", "models": [{ "id": "qwen2.5" }] }
+            } }
+        }));
+        let p = LlmProvider::from_openclaw(&oc).unwrap();
+        assert_eq!(p.kind, ApiKind::Ollama);
+        assert_eq!(p.model, "qwen2.5");
+        assert!(p.api_key.is_none());
+
+        let oc = openclaw(json!({
+            "models": { "providers": {
+                "aa-ollama": { "baseUrl": "http://127.0.0.1:11434" },
+                "groq": { "apiKey": "gsk-1", "baseUrl": "https://api.groq.com/openai/v1" }
+            } }
+        }));
+        assert_eq!(LlmProvider::from_openclaw(&oc).unwrap().name, "groq");
+    }
+
+    #[test]
+    fn test_keyless_primary_ollama_wins() {
+        let oc = openclaw(json!({
+            "agents": { "defaults": { "model": { "primary": "ollama/llama3.1" } } },
+            "models": { "providers": {
+                "ollama": { "baseUrl": "http://127.0.0.1:11434" },
+                "groq": { "apiKey": "gsk-1" }
+            } }
+        }));
+        let p = LlmProvider::from_openclaw(&oc).unwrap();
+        assert_eq!(p.name, "ollama");
+        assert_eq!(p.model, "llama3.1");
+    }
+}
@@ -0,0 +1,339 @@
+use regex::RegexSet;
+use serde::Serialize;
+use std::fmt;
+use std::sync::OnceLock;
+
+use super::Evidence;
+
+/// Matching lines quoted per finding
+const MAX_EVIDENCE_LINES: usize = 3;
+
+/// Broad failure classes the offline analyzer can recognize
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Category {
+    ConfigSyntax,
+    PortInUse,
+    ProviderAuth,
+    OutOfMemory,
+    MissingModule,
+    DiskFull,
+}
+
+impl Category {
+    /// Stable kebab-case name, as serialized
+    pub fn slug(&self) -> &'static str {
+        match self {
+            Category::ConfigSyntax => "config-syntax",
+            Category::PortInUse => "port-in-use",
+            Category::ProviderAuth => "provider-auth",
+            Category::OutOfMemory => "out-of-memory",
+            Category::MissingModule => "missing-module",
+            Category::DiskFull => "disk-full",
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Category::ConfigSyntax => "config syntax",
+            Category::PortInUse => "port in use",
+            Category::ProviderAuth => "provider auth",
+            Category::OutOfMemory => "out of memory",
+            Category::MissingModule => "missing module",
+            Category::DiskFull => "disk full",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A known failure signature: any of `patterns` matching a log or evidence line
+struct Signature {
+    id: &'static str,
+    category: Category,
+    title: &'static str,
+    patterns: &'static [&'static str],
+    recommendation: &'static str,
+}
+
+/// Built-in signature library, most specific first
+const SIGNATURES: &[Signature] = &[
+    Signature {
+        id: "config-json-parse",
+        category: Category::ConfigSyntax,
+        title: "OpenClaw config is not valid JSON",
+        patterns: &[
+            r"(?i)(openclaw|clawdbot)\.json.*(syntaxerror|unexpected token|parse|invalid json)",
+            r"(?i)(syntaxerror|unexpected token|json5?:).*(openclaw|clawdbot)\.json",
+            r"(?i)failed to (load|parse|read) config",
+            r"(?i)^invalid json:",
+        ],
+        recommendation: "Restore the config from the last snapshot (`/rescue`) or fix the syntax error at the reported line, then restart the gateway.",
+    },
+    Signature {
+        id: "port-in-use",
+        category: Category::PortInUse,
+        title: "Gateway port already in use",
+        patterns: &[
+            r"EADDRINUSE",
+            r"(?i)address already in use",
+            r"(?i)port \d+ (is )?already in use",
+        ],
+        recommendation: "Another process (often a stale gateway) holds the port. Stop it or change `gateway.port`, then restart the gateway.",
+    },
+    Signature {
+        id: "provider-auth",
+        category: Category::ProviderAuth,
+        title: "Model provider rejected credentials",
+        patterns: &[
+            r"\b(401|403)\b.*(?i)(unauthori[sz]ed|forbidden|api.?key|authentication|permission)",
+            r"(?i)(invalid|incorrect|expired|revoked) (x-)?api[ _-]?key",
+            r"(?i)authentication_error",
+            r"(?i)\bstatus(code)?[=: ]+(401|403)\b",
+        ],
+        recommendation: "Rotate or re-enter the provider API key (auth profiles or `models.providers`). A restore will not help if the key itself was revoked.",
+    },
+    Signature {
+        id: "out-of-memory",
+        category: Category::OutOfMemory,
+        title: "Gateway ran out of memory",
+        patterns: &[
+            r"(?i)javascript heap out of memory",
+            r"(?i)reached heap limit",
+            r"(?i)out of memory: killed? process",
+            r"(?i)oom[- ]?kill",
+            r"(?i)\bENOMEM\b",
+        ],
+        recommendation: "Raise the memory limit (e.g. `NODE_OPTIONS=--max-old-space-size`), trim large sessions, or add swap. Check for a skill leaking memory.",
+    },
+    Signature {
+        id: "missing-module",
+        category: Category::MissingModule,
+        title: "A skill or plugin failed to load a module",
+        patterns: &[
+            r"(?i)cannot find module",
+            r"MODULE_NOT_FOUND",
+            r"ERR_MODULE_NOT_FOUND",
+            r"(?i)modulenotfounderror",
+        ],
+        recommendation: "Reinstall the affected skill (`clawhub install <skill>`) or run its dependency install; roll back to a snapshot taken before the skill change.",
+    },
+    Signature {
+        id: "disk-full",
+        category: Category::DiskFull,
+        title: "Disk is full",
+        patterns: &[r"ENOSPC", r"(?i)no space left on device", r"(?i)disk (is )?full"],
+        recommendation: "Free disk space (old sessions, logs, backups beyond `maxSnapshots`) before restarting; writes will keep failing until then.",
+    },
+];
+
+fn signature_sets() -> &'static [RegexSet] {
+    static SETS: OnceLock<Vec<RegexSet>> = OnceLock::new();
+    SETS.get_or_init(|| {
+        SIGNATURES
+            .iter()
+            .map(|s| RegexSet::new(s.patterns).expect("built-in signature pattern"))
+            .collect()
+    })
+}
+
+/// One matched signature with the lines that triggered it
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub signature: &'static str,
+    pub category: Category,
+    pub title: &'static str,
+    pub recommendation: &'static str,
+    pub match_count: usize,
+    pub evidence: Vec<String>,
+}
+
+/// Result of the offline analyzer
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RuleReport {
+    /// Ordered by number of matching lines, most first
+    pub findings: Vec<Finding>,
+    pub lines_scanned: usize,
+}
+
+impl RuleReport {
+    /// The most likely cause, if any signature matched
+    pub fn primary(&self) -> Option<&Finding> {
+        self.findings.first()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+
+        match self.primary() {
+            None => {
+                out.push_str("## Root Cause (rule-based)\n\n");
+                out.push_str(&format!(
+                    "No known failure signature matched ({} lines scanned).\n",
+                    self.lines_scanned
+                ));
+            }
+            Some(primary) => {
+                out.push_str("## Root Cause (rule-based)\n");
+                out.push_str(&format!(
+                    "**{}** — {} (`{}`, {} matching line{})\n\n",
+                    primary.title,
+                    primary.category,
+                    primary.signature,
+                    primary.match_count,
+                    if primary.match_count == 1 { "" } else { "s" }
+                ));
+
+                out.push_str("## Findings\n\n");
+                for finding in &self.findings {
+                    out.push_str(&format!(
+                        "### {} (`{}`)\n\n",
+                        finding.title, finding.signature
+                    ));
+                    out.push_str("```\n");
+                    for line in &finding.evidence {
+                        out.push_str(line);
+                        out.push('\n');
+                    }
+                    out.push_str("```\n\n");
+                    out.push_str(&format!(
+                        "**Recommendation:** {}\n\n",
+                        finding.recommendation
+                    ));
+                }
+            }
+        }
+
+        out
+    }
+}
+
+/// Classify evidence against the built-in signature library
+pub(super) fn analyze(evidence: &Evidence) -> RuleReport {
+    let lines: Vec<&str> = evidence
+        .gateway_log_tail
+        .lines()
+        .chain(evidence.config_issues.iter().map(String::as_str))
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    classify_lines(&lines)
+}
+
+/// Match lines against every signature
+pub fn classify_lines(lines: &[&str]) -> RuleReport {
+    let sets = signature_sets();
+    let mut findings: Vec<Finding> = Vec::new();
+
+    for (sig, set) in SIGNATURES.iter().zip(sets) {
+        let matched: Vec<&str> = lines.iter().copied().filter(|l| set.is_match(l)).collect();
+        if matched.is_empty() {
+            continue;
+        }
+        findings.push(Finding {
+            signature: sig.id,
+            category: sig.category,
+            title: sig.title,
+            recommendation: sig.recommendation,
+            match_count: matched.len(),
+            // Most recent lines are the most relevant
+            evidence: matched
+                .iter()
+                .rev()
+                .take(MAX_EVIDENCE_LINES)
+                .rev()
+                .map(|l| l.to_string())
+                .collect(),
+        });
+    }
+
+    // Stable sort keeps library order (most specific first) on ties
+    findings.sort_by_key(|f| std::cmp::Reverse(f.match_count));
+
+    RuleReport {
+        findings,
+        lines_scanned: lines.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(log: &str) -> RuleReport {
+        let lines: Vec<&str> = log.lines().collect();
+        classify_lines(&lines)
+    }
+
+    #[test]
+    fn test_signature_library() {
+        let cases = [
+            (
+                "SyntaxError: Unexpected token } in JSON at position 812 (/home/u/.openclaw/openclaw.json)",
+                Category::ConfigSyntax,
+            ),
+            (
+                "Error: listen EADDRINUSE: address already in use 127.0.0.1:7744",
+                Category::PortInUse,
+            ),
+            (
+                "provider anthropic: 401 Unauthorized {\"type\":\"authentication_error\"}",
+                Category::ProviderAuth,
+            ),
+            (
+                "FATAL ERROR: Reached heap limit Allocation failed - JavaScript heap out of memory",
+                Category::OutOfMemory,
+            ),
+            (
+                "Error: Cannot find module 'node-fetch' from skills/weather/index.js",
+                Category::MissingModule,
+            ),
+            (
+                "Error: ENOSPC: no space left on device, write",
+                Category::DiskFull,
+            ),
+        ];
+
+        for (line, category) in cases {
+            let report = classify(line);
+            assert_eq!(
+                report.primary().map(|f| f.category),
+                Some(category),
+                "line: {}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn test_most_frequent_signature_wins() {
+        let report = classify(
+            "Error: Cannot find module 'x'\n\
+             listen EADDRINUSE :7744\n\
+             listen EADDRINUSE :7744\n\
+             gateway starting",
+        );
+        assert_eq!(report.findings.len(), 2);
+        assert_eq!(report.primary().unwrap().signature, "port-in-use");
+        assert_eq!(report.primary().unwrap().match_count, 2);
+        assert_eq!(report.lines_scanned, 4);
+    }
+
+    #[test]
+    fn test_no_match_report() {
+        let report = classify("gateway started\nall good");
+        assert!(report.primary().is_none());
+        assert!(report.to_markdown().contains("No known failure signature"));
+    }
+
+    #[test]
+    fn test_evidence_is_capped() {
+        let log = "ENOSPC\n".repeat(10);
+        let report = classify(&log);
+        let finding = report.primary().unwrap();
+        assert_eq!(finding.match_count, 10);
+        assert_eq!(finding.evidence.len(), MAX_EVIDENCE_LINES);
+        assert!(report.to_markdown().contains("## Root Cause (rule-based)"));
+    }
+}
@@ -1,11 +1,15 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use chrono::Utc;
+use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
 use flate2::Compression;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
 
-use crate::config::Config;
+use crate::config::{BackupMode, Config, LiveConfig};
 
 /// A backup snapshot
 #[derive(Debug)]
@@ -42,6 +46,12 @@ const CONFIG_FILES: &[&str] = &[
 
 /// Take a backup snapshot of the OpenClaw workspace + config
 pub fn take_snapshot(cfg: &Config) -> Result<Snapshot> {
+    take_snapshot_with(cfg, &BTreeMap::new())
+}
+
+/// Take a snapshot, storing `overrides` (archive path → content) in place of
+/// the live config files, e.g. to checkpoint the last valid openclaw.json
+pub fn take_snapshot_with(cfg: &Config, overrides: &BTreeMap<String, Vec<u8>>) -> Result<Snapshot> {
     let now = Utc::now();
     let id = format!("{}", now.format("%Y%m%d-%H%M%S"));
     let filename = format!("backup-{}.tar.gz", id);
@@ -73,7 +83,16 @@ pub fn take_snapshot(cfg: &Config) -> Result<Snapshot> {
     // Add OpenClaw config files
     for entry in CONFIG_FILES {
         let full_path = cfg.openclaw.config_path.join(entry);
-        if full_path.exists() {
+        let name = format!("config/{}", entry);
+        if let Some(data) = overrides.get(&name) {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o600);
+            header.set_mtime(now.timestamp().max(0) as u64);
+            header.set_cksum();
+            tar.append_data(&mut header, &name, &data[..])?;
+            file_count += 1;
+        } else if full_path.exists() {
             if full_path.is_dir() {
                 tar.append_dir_all(format!("config/{}", entry), &full_path)?;
             } else {
@@ -99,6 +118,7 @@ pub fn take_snapshot(cfg: &Config) -> Result<Snapshot> {
         "file_count": file_count,
         "workspace": cfg.openclaw.workspace,
         "version": env!("CARGO_PKG_VERSION"),
+        "content_hash": content_hash(cfg, overrides),
     });
     let manifest_bytes = serde_json::to_vec_pretty(&manifest)?;
     let mut header = tar::Header::new_gnu();
@@ -171,6 +191,265 @@ pub fn list_snapshots(cfg: &Config) -> Result<Vec<Snapshot>> {
     Ok(snapshots)
 }
 
+/// Find a snapshot by ID (latest if omitted)
+pub fn find_snapshot(cfg: &Config, id: Option<&str>) -> Result<Snapshot> {
+    let mut snapshots = list_snapshots(cfg)?;
+
+    if snapshots.is_empty() {
+        anyhow::bail!("No backups available. Run `rescueclaw backup` first.");
+    }
+
+    match id {
+        Some(id) => {
+            let pos = snapshots.iter().position(|s| s.id == id).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Backup '{}' not found. Use `rescueclaw list` to see available backups.",
+                    id
+                )
+            })?;
+            Ok(snapshots.swap_remove(pos))
+        }
+        None => Ok(snapshots.swap_remove(0)),
+    }
+}
+
+/// Read every regular file stored in a snapshot, keyed by archive path
+/// (e.g. `workspace/SOUL.md`, `config/openclaw.json`)
+pub fn read_snapshot_entries(snapshot: &Snapshot) -> Result<BTreeMap<String, Vec<u8>>> {
+    let tar_file = fs::File::open(&snapshot.path)
+        .with_context(|| format!("opening backup: {}", snapshot.path.display()))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(tar_file));
+    let mut files = BTreeMap::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.to_string_lossy().to_string();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        files.insert(path, data);
+    }
+
+    Ok(files)
+}
+
+/// Read the first of `candidates` found in a snapshot, without unpacking
+/// the rest. A file larger than `max_bytes` is refused before it is read.
+fn read_snapshot_entry(
+    snapshot: &Snapshot,
+    candidates: &[String],
+    max_bytes: u64,
+) -> Result<Option<Vec<u8>>> {
+    let tar_file = fs::File::open(&snapshot.path)
+        .with_context(|| format!("opening backup: {}", snapshot.path.display()))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(tar_file));
+    // (rank in `candidates`, content) of the best match so far
+    let mut found: Option<(usize, Vec<u8>)> = None;
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.to_string_lossy().to_string();
+        let Some(rank) = candidates.iter().position(|c| *c == path) else {
+            continue;
+        };
+        if found.as_ref().is_some_and(|(best, _)| *best < rank) {
+            continue;
+        }
+        let size = entry.header().size()?;
+        if size > max_bytes {
+            anyhow::bail!(
+                "'{}' is too large ({}, limit {})",
+                path,
+                human_size(size),
+                human_size(max_bytes)
+            );
+        }
+        let mut data = Vec::with_capacity(size as usize);
+        entry.read_to_end(&mut data)?;
+        if rank == 0 {
+            return Ok(Some(data));
+        }
+        found = Some((rank, data));
+    }
+
+    Ok(found.map(|(_, data)| data))
+}
+
+/// Read a single file out of a snapshot, refusing one over `max_bytes`.
+///
+/// `path` may be an archive path (`workspace/SOUL.md`, `config/openclaw.json`)
+/// or bare, in which case `workspace/` then `config/` are tried.
+pub fn read_snapshot_file(snapshot: &Snapshot, path: &str, max_bytes: u64) -> Result<Vec<u8>> {
+    let path = path.trim_start_matches("./");
+    if Path::new(path)
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        anyhow::bail!("Invalid path '{}': must be relative, without '..'", path);
+    }
+
+    let candidates = if path.starts_with("workspace/")
+        || path.starts_with("config/")
+        || path.starts_with("sessions/")
+    {
+        vec![path.to_string()]
+    } else {
+        vec![format!("workspace/{}", path), format!("config/{}", path)]
+    };
+
+    read_snapshot_entry(snapshot, &candidates, max_bytes)?
+        .ok_or_else(|| anyhow::anyhow!("'{}' not found in backup {}", path, snapshot.id))
+}
+
+/// Unified diff between a snapshot and the live workspace/config files.
+/// Snapshot content is the `a/` side, live content the `b/` side.
+pub fn diff_snapshot(cfg: &Config, snapshot: &Snapshot) -> Result<String> {
+    diff_snapshot_where(cfg, snapshot, |path| {
+        path.starts_with("workspace/") || path.starts_with("config/")
+    })
+}
+
+/// Like [`diff_snapshot`], limited to OpenClaw config files
+pub fn diff_snapshot_config(cfg: &Config, snapshot: &Snapshot) -> Result<String> {
+    diff_snapshot_where(cfg, snapshot, |path| path.starts_with("config/"))
+}
+
+fn diff_snapshot_where(
+    cfg: &Config,
+    snapshot: &Snapshot,
+    include: impl Fn(&str) -> bool,
+) -> Result<String> {
+    let backed_up: BTreeMap<String, Vec<u8>> = read_snapshot_entries(snapshot)?
+        .into_iter()
+        .filter(|(path, _)| include(path))
+        .collect();
+    let mut live = collect_live_files(cfg);
+    live.retain(|path, _| include(path));
+
+    let mut paths: Vec<&String> = backed_up.keys().chain(live.keys()).collect();
+    paths.sort();
+    paths.dedup();
+
+    let mut out = String::new();
+    for path in paths {
+        let old = backed_up
+            .get(path)
+            .map(|d| d.as_slice())
+            .unwrap_or_default();
+        let new = match live.get(path) {
+            Some(p) => fs::read(p).unwrap_or_default(),
+            None => Vec::new(),
+        };
+        if old == new.as_slice() {
+            continue;
+        }
+
+        match (std::str::from_utf8(old), std::str::from_utf8(&new)) {
+            (Ok(old_text), Ok(new_text)) => {
+                let diff = similar::TextDiff::from_lines(old_text, new_text);
+                out.push_str(
+                    &diff
+                        .unified_diff()
+                        .header(&format!("a/{}", path), &format!("b/{}", path))
+                        .to_string(),
+                );
+            }
+            _ => out.push_str(&format!("Binary files a/{} and b/{} differ\n", path, path)),
+        }
+    }
+
+    Ok(out)
+}
+
+/// Archive paths of workspace/config files whose live content differs from
+/// the snapshot (i.e. what a restore would roll back)
+pub fn changed_files(cfg: &Config, snapshot: &Snapshot) -> Result<Vec<String>> {
+    let live = collect_live_files(cfg);
+    Ok(read_snapshot_entries(snapshot)?
+        .into_iter()
+        .filter(|(path, _)| path.starts_with("workspace/") || path.starts_with("config/"))
+        .filter(|(path, data)| {
+            live.get(path)
+                .and_then(|p| fs::read(p).ok())
+                .is_none_or(|current| current != *data)
+        })
+        .map(|(path, _)| path)
+        .collect())
+}
+
+/// SHA-256 over the paths and contents a snapshot would capture, so two
+/// snapshots of the same state can be recognized without comparing archives
+pub fn content_hash(cfg: &Config, overrides: &BTreeMap<String, Vec<u8>>) -> String {
+    let mut files = collect_live_files(cfg);
+    if cfg.backup.include_sessions {
+        collect_files_into(
+            &cfg.openclaw.config_path.join("agents/main/sessions"),
+            "sessions",
+            &mut files,
+        );
+    }
+
+    let mut hasher = Sha256::new();
+    let mut paths: Vec<&String> = files.keys().chain(overrides.keys()).collect();
+    paths.sort();
+    paths.dedup();
+    for path in paths {
+        let data = match overrides.get(path) {
+            Some(data) => data.clone(),
+            None => fs::read(&files[path]).unwrap_or_default(),
+        };
+        hasher.update(path.as_bytes());
+        hasher.update([0]);
+        hasher.update((data.len() as u64).to_le_bytes());
+        hasher.update(&data);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Content hash recorded in a snapshot's manifest (older snapshots have none)
+pub fn snapshot_content_hash(snapshot: &Snapshot) -> Option<String> {
+    let manifest =
+        read_snapshot_entry(snapshot, &["manifest.json".to_string()], u64::MAX).ok()??;
+    let manifest: serde_json::Value = serde_json::from_slice(&manifest).ok()?;
+    manifest["content_hash"].as_str().map(String::from)
+}
+
+/// Live files that a snapshot would capture, keyed by archive path
+fn collect_live_files(cfg: &Config) -> BTreeMap<String, PathBuf> {
+    let mut files = BTreeMap::new();
+    for entry in CORE_FILES {
+        collect_files_into(
+            &cfg.openclaw.workspace.join(entry),
+            &format!("workspace/{}", entry),
+            &mut files,
+        );
+    }
+    for entry in CONFIG_FILES {
+        collect_files_into(
+            &cfg.openclaw.config_path.join(entry),
+            &format!("config/{}", entry),
+            &mut files,
+        );
+    }
+    files
+}
+
+fn collect_files_into(path: &Path, name: &str, files: &mut BTreeMap<String, PathBuf>) {
+    if path.is_file() {
+        files.insert(name.to_string(), path.to_path_buf());
+    } else if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            let child = format!("{}/{}", name, entry.file_name().to_string_lossy());
+            collect_files_into(&entry.path(), &child, files);
+        }
+    }
+}
+
 /// Remove old snapshots beyond max_snapshots
 fn prune_old_snapshots(cfg: &Config) -> Result<()> {
     let snapshots = list_snapshots(cfg)?;
@@ -184,31 +463,193 @@ fn prune_old_snapshots(cfg: &Config) -> Result<()> {
 }
 
 /// Scheduled backup loop
-pub async fn backup_loop(cfg: &Config) -> Result<()> {
-    let interval = parse_duration(&cfg.backup.interval)?;
+pub async fn backup_loop(live: &LiveConfig) -> Result<()> {
+    let mut live = live.clone();
+    let mut scheduler = Scheduler::new(&live.current())?;
     loop {
-        tokio::time::sleep(interval).await;
-        match take_snapshot(cfg) {
-            Ok(snap) => tracing::info!("Scheduled backup: {} ({})", snap.filename, snap.size_human),
-            Err(e) => tracing::error!("Backup failed: {}", e),
+        let cfg = live.current();
+        scheduler.reconfigure(&cfg)?;
+        // A reload restarts the timers; the scheduler keeps the last run and hash
+        tokio::select! {
+            r = run_mode(&cfg, &mut scheduler) => return r,
+            _ = live.changed() => tracing::info!("Backup schedule reloaded"),
         }
     }
 }
 
-fn parse_duration(s: &str) -> Result<tokio::time::Duration> {
-    let s = s.trim();
-    if let Some(h) = s.strip_suffix('h') {
-        Ok(tokio::time::Duration::from_secs(h.parse::<u64>()? * 3600))
-    } else if let Some(m) = s.strip_suffix('m') {
-        Ok(tokio::time::Duration::from_secs(m.parse::<u64>()? * 60))
-    } else if let Some(s_val) = s.strip_suffix('s') {
-        Ok(tokio::time::Duration::from_secs(s_val.parse::<u64>()?))
-    } else {
-        anyhow::bail!("Invalid duration format: {} (use e.g. 6h, 30m, 60s)", s)
+async fn run_mode(cfg: &Config, scheduler: &mut Scheduler) -> Result<()> {
+    if cfg.backup.mode == BackupMode::Schedule {
+        let plan = crate::schedule::BackupPlan::from_config(cfg)?;
+        return schedule_loop(&plan, scheduler).await;
+    }
+    if cfg.backup.mode == BackupMode::Changes {
+        match crate::journal::ChangeWatcher::start(cfg) {
+            Ok(watcher) => return change_loop(cfg, watcher, scheduler).await,
+            Err(e) => tracing::error!(
+                "Change-triggered backups unavailable ({:#}) — backing up every {}",
+                e,
+                cfg.backup.interval
+            ),
+        }
+    }
+
+    let interval = cfg.backup.interval.as_std();
+    loop {
+        tokio::time::sleep_until(scheduler.last_run + interval).await;
+        scheduler.run("Scheduled");
+    }
+}
+
+/// Snapshot on the named cron schedules
+async fn schedule_loop(
+    plan: &crate::schedule::BackupPlan,
+    scheduler: &mut Scheduler,
+) -> Result<()> {
+    loop {
+        let run = plan
+            .next(Utc::now())
+            .context("backup.schedules: no upcoming runs")?;
+        tracing::info!("Next backup: {} at {}", run.schedule, run.at.to_rfc3339());
+        let wait = (run.at - Utc::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+        scheduler.run_with(
+            &format!("Scheduled ({})", run.schedule),
+            run.include_sessions,
+        );
+    }
+}
+
+/// Snapshot once changes have been quiet for `debounce` (or have kept coming
+/// for `maxWait`), no sooner than `minInterval` after the last one and no
+/// later than `maxInterval`
+async fn change_loop(
+    cfg: &Config,
+    mut watcher: crate::journal::ChangeWatcher,
+    scheduler: &mut Scheduler,
+) -> Result<()> {
+    let debounce = cfg.backup.debounce.as_std();
+    let max_wait = cfg.backup.max_wait.as_std();
+    let min_interval = cfg.backup.min_interval.as_std();
+    let max_interval = cfg.backup.max_interval.unwrap_or(cfg.backup.interval);
+    tracing::info!(
+        "Backing up on changes (debounce {}, min {}, max {})",
+        cfg.backup.debounce,
+        cfg.backup.min_interval,
+        max_interval
+    );
+    let max_interval = max_interval.as_std();
+
+    loop {
+        let reason = tokio::select! {
+            batch = watcher.next_batch(debounce, max_wait) => match batch {
+                Some(_) => "Change-triggered",
+                None => anyhow::bail!("Change watcher stopped"),
+            },
+            _ = tokio::time::sleep_until(scheduler.last_run + max_interval) => "Scheduled",
+        };
+        tokio::time::sleep_until(scheduler.last_run + min_interval).await;
+        scheduler.run(reason);
+    }
+}
+
+/// Takes scheduled snapshots, skipping those in quiet hours or identical
+/// to the previous one
+pub struct Scheduler {
+    cfg: Config,
+    quiet: crate::schedule::QuietHours,
+    /// Content hash of the newest snapshot, and which snapshot that was
+    last_hash: Option<String>,
+    last_seen: Option<SnapshotStamp>,
+    /// When a scheduled snapshot was last attempted (or the scheduler started)
+    pub last_run: tokio::time::Instant,
+}
+
+impl Scheduler {
+    pub fn new(cfg: &Config) -> Result<Self> {
+        Ok(Self {
+            cfg: cfg.clone(),
+            quiet: crate::schedule::QuietHours::backup(cfg)?,
+            last_hash: None,
+            last_seen: None,
+            last_run: tokio::time::Instant::now(),
+        })
+    }
+
+    /// Apply a reloaded config, keeping the last run and content hash
+    pub fn reconfigure(&mut self, cfg: &Config) -> Result<()> {
+        self.quiet = crate::schedule::QuietHours::backup(cfg)?;
+        self.cfg = cfg.clone();
+        Ok(())
+    }
+
+    /// Snapshot unless in quiet hours or nothing changed; returns the
+    /// snapshot taken, if any
+    pub fn run(&mut self, reason: &str) -> Option<Snapshot> {
+        self.run_with(reason, None)
     }
+
+    /// Like [`Scheduler::run`], optionally overriding `includeSessions`
+    pub fn run_with(&mut self, reason: &str, include_sessions: Option<bool>) -> Option<Snapshot> {
+        let snapshot = self.attempt(reason, include_sessions);
+        self.last_run = tokio::time::Instant::now();
+        snapshot
+    }
+
+    fn attempt(&mut self, reason: &str, include_sessions: Option<bool>) -> Option<Snapshot> {
+        if let Some(window) = self.quiet.active(Utc::now()) {
+            tracing::info!("{} backup skipped: quiet hours {}", reason, window);
+            return None;
+        }
+
+        let mut cfg = self.cfg.clone();
+        if let Some(include) = include_sessions {
+            cfg.backup.include_sessions = include;
+        }
+        let hash = content_hash(&cfg, &BTreeMap::new());
+        if cfg.backup.skip_unchanged() && self.newest_hash().as_ref() == Some(&hash) {
+            tracing::info!(
+                "{} backup skipped: no changes since the last snapshot",
+                reason
+            );
+            return None;
+        }
+        match take_snapshot(&cfg) {
+            Ok(snap) => {
+                tracing::info!("{} backup: {} ({})", reason, snap.filename, snap.size_human);
+                self.last_hash = Some(hash);
+                self.last_seen = Some(stamp(&snap));
+                Some(snap)
+            }
+            Err(e) => {
+                tracing::error!("Backup failed: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Hash of the newest snapshot, read again when it isn't the one last
+    /// seen (a manual, guard, checkpoint or journal snapshot came since)
+    fn newest_hash(&mut self) -> Option<String> {
+        let newest = find_snapshot(&self.cfg, None).ok();
+        let seen = newest.as_ref().map(stamp);
+        if seen != self.last_seen {
+            self.last_hash = newest.as_ref().and_then(snapshot_content_hash);
+            self.last_seen = seen;
+        }
+        self.last_hash.clone()
+    }
+}
+
+/// Identifies a snapshot file: snapshots taken in the same second share an
+/// ID, so the file's modification time tells them apart
+type SnapshotStamp = (String, Option<std::time::SystemTime>);
+
+fn stamp(snapshot: &Snapshot) -> SnapshotStamp {
+    let modified = fs::metadata(&snapshot.path).and_then(|m| m.modified()).ok();
+    (snapshot.id.clone(), modified)
 }
 
-fn human_size(bytes: u64) -> String {
+pub fn human_size(bytes: u64) -> String {
     if bytes < 1024 {
         format!("{}B", bytes)
     } else if bytes < 1024 * 1024 {
@@ -0,0 +1,379 @@
+use std::fmt;
+
+use super::{BackupMode, Config, GatewayDriver, GuardPolicy};
+use crate::validate::Severity;
+
+/// A problem with rescueclaw's own config, named by its JSON path
+#[derive(Debug, Clone)]
+pub struct ConfigProblem {
+    /// e.g. `backup.maxSnapshots`, `backup.schedules[1].cron`
+    pub path: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Collects problems while walking the config
+#[derive(Default)]
+struct Problems(Vec<ConfigProblem>);
+
+impl Problems {
+    fn error(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.push(path.into(), Severity::Error, message.into());
+    }
+
+    fn warning(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.push(path.into(), Severity::Warning, message.into());
+    }
+
+    fn push(&mut self, path: String, severity: Severity, message: String) {
+        self.0.push(ConfigProblem {
+            path,
+            severity,
+            message,
+        });
+    }
+}
+
+impl Config {
+    /// Check values that parse but can't work: zero intervals, empty paths,
+    /// bad cron and quiet-hour specs, unknown time zones, invalid patterns.
+    /// Reports every problem rather than stopping at the first.
+    pub fn check(&self) -> Vec<ConfigProblem> {
+        let mut p = Problems::default();
+        self.check_backup(&mut p);
+        self.check_health(&mut p);
+        self.check_telegram(&mut p);
+        self.check_openclaw(&mut p);
+        self.check_rest(&mut p);
+        p.0
+    }
+
+    /// Whether any problem is an error (warnings don't stop the daemon)
+    pub fn has_errors(problems: &[ConfigProblem]) -> bool {
+        problems.iter().any(|p| p.severity == Severity::Error)
+    }
+
+    fn check_backup(&self, p: &mut Problems) {
+        let b = &self.backup;
+        if b.interval.as_std().is_zero() {
+            p.error("backup.interval", "must be longer than 0s");
+        }
+        if b.max_snapshots == 0 {
+            p.error("backup.maxSnapshots", "must be at least 1");
+        }
+        if b.path.as_os_str().is_empty() {
+            p.error("backup.path", "is empty");
+        }
+        if b.mode == BackupMode::Changes {
+            if b.debounce.as_std().is_zero() {
+                p.error("backup.debounce", "must be longer than 0s in changes mode");
+            }
+            let max = b.max_interval.unwrap_or(b.interval);
+            if b.min_interval > max {
+                p.error(
+                    "backup.minInterval",
+                    format!("{} is longer than maxInterval ({})", b.min_interval, max),
+                );
+            }
+        }
+        if b.mode == BackupMode::Schedule && b.schedules.is_empty() {
+            p.error(
+                "backup.schedules",
+                "mode is 'schedule' but none are defined",
+            );
+        }
+
+        let mut names = std::collections::HashSet::new();
+        for (i, s) in b.schedules.iter().enumerate() {
+            if s.name.trim().is_empty() {
+                p.error(format!("backup.schedules[{}].name", i), "is empty");
+            } else if !names.insert(s.name.as_str()) {
+                p.error(
+                    format!("backup.schedules[{}].name", i),
+                    format!("duplicate schedule name '{}'", s.name),
+                );
+            }
+            if let Err(e) = crate::schedule::parse_cron(&s.cron) {
+                p.error(format!("backup.schedules[{}].cron", i), e.to_string());
+            }
+        }
+        check_quiet_hours(p, "backup.quietHours", &b.quiet_hours);
+    }
+
+    fn check_health(&self, p: &mut Problems) {
+        let h = &self.health;
+        if h.check_interval.as_std().is_zero() {
+            p.error("health.checkInterval", "must be longer than 0s");
+        }
+        if h.unhealthy_threshold == 0 {
+            p.error("health.unhealthyThreshold", "must be at least 1");
+        }
+        check_quiet_hours(p, "health.quietHours", &h.quiet_hours);
+        for (i, step) in h.escalation.iter().enumerate() {
+            if let Err(e) = step.parse::<crate::health::escalation::Step>() {
+                p.error(format!("health.escalation[{}]", i), e);
+            }
+        }
+    }
+
+    fn check_telegram(&self, p: &mut Problems) {
+        let t = &self.telegram;
+        if t.token.is_empty() {
+            p.warning("telegram.token", "not set — Telegram control is disabled");
+            return;
+        }
+        let well_formed = t.token.split_once(':').is_some_and(|(id, secret)| {
+            !id.is_empty()
+                && id.chars().all(|c| c.is_ascii_digit())
+                && !secret.is_empty()
+                && secret
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        });
+        if !well_formed {
+            p.error(
+                "telegram.token",
+                "doesn't look like a bot token (expected <bot id>:<secret> from @BotFather)",
+            );
+        }
+        for (i, admin) in t.admins.iter().enumerate() {
+            if !t.allowed_users.contains(admin) {
+                p.error(
+                    format!("telegram.admins[{}]", i),
+                    format!("{} is not in allowedUsers", admin),
+                );
+            }
+        }
+        if t.allowed_users.is_empty() {
+            p.warning(
+                "telegram.allowedUsers",
+                "empty — anyone who finds the bot can control it",
+            );
+        }
+    }
+
+    fn check_openclaw(&self, p: &mut Problems) {
+        let o = &self.openclaw;
+        if o.workspace.as_os_str().is_empty() {
+            p.error("openclaw.workspace", "is empty");
+        } else if !o.workspace.is_dir() {
+            p.warning(
+                "openclaw.workspace",
+                format!("{} is not a directory", o.workspace.display()),
+            );
+        }
+        if o.config_path.as_os_str().is_empty() {
+            p.error("openclaw.configPath", "is empty");
+        } else if !o.config_path.is_dir() {
+            p.warning(
+                "openclaw.configPath",
+                format!("{} is not a directory", o.config_path.display()),
+            );
+        }
+
+        let g = &o.gateway;
+        match g.driver {
+            GatewayDriver::Systemd if g.unit.trim().is_empty() => {
+                p.error("openclaw.gateway.unit", "is empty (systemd driver)")
+            }
+            GatewayDriver::Docker if g.container.trim().is_empty() => {
+                p.error("openclaw.gateway.container", "is empty (docker driver)")
+            }
+            GatewayDriver::Docker if !is_container_name(&g.container) => p.error(
+                "openclaw.gateway.container",
+                format!(
+                    "'{}' is not a container name or ID (letters, digits, '_', '.', '-')",
+                    g.container
+                ),
+            ),
+            GatewayDriver::Custom
+                if g.start_command
+                    .as_deref()
+                    .is_none_or(|c| c.trim().is_empty()) =>
+            {
+                p.error(
+                    "openclaw.gateway.startCommand",
+                    "is required by the custom driver",
+                )
+            }
+            _ => {}
+        }
+        if g.driver == GatewayDriver::Docker
+            && !g.container.trim().is_empty()
+            && !g.docker_socket.exists()
+        {
+            p.warning(
+                "openclaw.gateway.dockerSocket",
+                format!(
+                    "{} does not exist (mount it into this container?)",
+                    g.docker_socket.display()
+                ),
+            );
+        }
+        if g.host.as_deref().is_some_and(|h| h.trim().is_empty()) {
+            p.error("openclaw.gateway.host", "is empty");
+        }
+        if reqwest::header::HeaderName::from_bytes(g.auth_header.as_bytes()).is_err() {
+            p.error(
+                "openclaw.gateway.authHeader",
+                format!("'{}' is not a valid header name", g.auth_header),
+            );
+        }
+        if let Some(ca) = &g.tls.ca_cert {
+            if !ca.is_file() {
+                p.warning(
+                    "openclaw.gateway.tls.caCert",
+                    format!("{} does not exist", ca.display()),
+                );
+            }
+        }
+    }
+
+    fn check_rest(&self, p: &mut Problems) {
+        if let Some(name) = &self.timezone {
+            if name.parse::<chrono_tz::Tz>().is_err() {
+                p.error("timezone", format!("unknown time zone '{}'", name));
+            }
+        }
+
+        for (i, pattern) in self.redaction.custom_patterns.iter().enumerate() {
+            if let Err(e) = regex::Regex::new(pattern) {
+                p.error(format!("redaction.customPatterns[{}]", i), e.to_string());
+            }
+        }
+
+        if let Err(e) = crate::validate::Validator::new(&self.validation) {
+            p.error("validation", format!("{:#}", e));
+        }
+
+        if self.guard.enabled
+            && self.guard.policy == GuardPolicy::Checkpoint
+            && self.guard.rollback_window.as_std().is_zero()
+        {
+            p.error(
+                "guard.rollbackWindow",
+                "must be longer than 0s with the checkpoint policy",
+            );
+        }
+
+        if self.journal.retention.as_std().is_zero() {
+            p.error("journal.retention", "must be longer than 0s");
+        }
+        for (i, file) in self.journal.protected_files.iter().enumerate() {
+            if !file.starts_with("workspace/") && !file.starts_with("config/") {
+                p.error(
+                    format!("journal.protectedFiles[{}]", i),
+                    format!("'{}' must start with workspace/ or config/", file),
+                );
+            }
+        }
+    }
+}
+
+fn check_quiet_hours(p: &mut Problems, key: &str, specs: &[String]) {
+    for (i, spec) in specs.iter().enumerate() {
+        if let Err(e) = spec.parse::<crate::schedule::TimeWindow>() {
+            p.error(format!("{}[{}]", key, i), format!("{:#}", e));
+        }
+    }
+}
+
+/// Docker's name charset, `[a-zA-Z0-9][a-zA-Z0-9_.-]*`, which IDs also fit.
+/// The name goes into API paths and the gateway URL unescaped.
+fn is_container_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphanumeric())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BackupSchedule;
+
+    fn paths(cfg: &Config) -> Vec<String> {
+        cfg.check()
+            .into_iter()
+            .filter(|p| p.severity == Severity::Error)
+            .map(|p| p.path)
+            .collect()
+    }
+
+    #[test]
+    fn test_sane_config_has_no_errors() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut cfg = Config::default();
+        cfg.openclaw.workspace = dir.path().to_path_buf();
+        cfg.openclaw.config_path = dir.path().to_path_buf();
+        assert!(paths(&cfg).is_empty(), "{:?}", cfg.check());
+    }
+
+    #[test]
+    fn test_reports_every_problem_by_path() {
+        let mut cfg = Config::default();
+        cfg.backup.max_snapshots = 0;
+        cfg.backup.interval = "0s".parse().unwrap();
+        cfg.backup.mode = BackupMode::Schedule;
+        cfg.backup.schedules = vec![
+            BackupSchedule {
+                name: "nightly".into(),
+                cron: "0 3 * * *".into(),
+                include_sessions: None,
+            },
+            BackupSchedule {
+                name: "nightly".into(),
+                cron: "every day".into(),
+                include_sessions: None,
+            },
+        ];
+        cfg.health.quiet_hours = vec!["22:00-06:00".into(), "25:00-26:00".into()];
+        cfg.health.escalation = vec!["restart 30s".into(), "reboot".into()];
+        cfg.telegram.token = "not-a-token".into();
+        cfg.timezone = Some("Mars/Olympus".into());
+        cfg.redaction.custom_patterns = vec!["(".into()];
+        cfg.openclaw.gateway.driver = GatewayDriver::Custom;
+        cfg.openclaw.gateway.auth_header = "X Token".into();
+
+        assert_eq!(
+            paths(&cfg),
+            vec![
+                "backup.interval",
+                "backup.maxSnapshots",
+                "backup.schedules[1].name",
+                "backup.schedules[1].cron",
+                "health.quietHours[1]",
+                "health.escalation[1]",
+                "telegram.token",
+                "openclaw.workspace",
+                "openclaw.gateway.startCommand",
+                "openclaw.gateway.authHeader",
+                "timezone",
+                "redaction.customPatterns[0]",
+            ]
+        );
+        assert!(Config::has_errors(&cfg.check()));
+    }
+
+    #[test]
+    fn test_container_names() {
+        for ok in ["openclaw", "claw_1.prod-2", "4f2a9c0d1e3b"] {
+            assert!(is_container_name(ok), "{}", ok);
+        }
+        for bad in [
+            "",
+            "-claw",
+            "claw/../images",
+            "claw?all=1",
+            "claw:1",
+            "claw gw",
+        ] {
+            assert!(!is_container_name(bad), "{}", bad);
+        }
+    }
+}
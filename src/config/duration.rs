@@ -0,0 +1,255 @@
+use chrono::{DateTime, Utc};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// A duration in config: `500ms`, `90s`, `5m`, `6h`, `1d`, `2w`, or compound
+/// forms like `1h30m` / `1d 12h`. A bare JSON number is read as seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Duration(std::time::Duration);
+
+/// Units from largest to smallest, as (suffix, milliseconds)
+const UNITS: &[(&str, u64)] = &[
+    ("w", 7 * 24 * 3600 * 1000),
+    ("d", 24 * 3600 * 1000),
+    ("h", 3600 * 1000),
+    ("m", 60 * 1000),
+    ("s", 1000),
+    ("ms", 1),
+];
+
+impl Duration {
+    pub const fn from_secs(secs: u64) -> Self {
+        Self(std::time::Duration::from_secs(secs))
+    }
+
+    pub fn as_secs(&self) -> u64 {
+        self.0.as_secs()
+    }
+
+    pub fn as_std(&self) -> std::time::Duration {
+        self.0
+    }
+
+    pub fn as_chrono(&self) -> chrono::Duration {
+        chrono::Duration::from_std(self.0).unwrap_or(chrono::Duration::MAX)
+    }
+
+    /// `t` minus this duration, or the earliest representable time if that
+    /// is out of range
+    pub fn before(&self, t: DateTime<Utc>) -> DateTime<Utc> {
+        t.checked_sub_signed(self.as_chrono())
+            .unwrap_or(DateTime::<Utc>::MIN_UTC)
+    }
+
+    /// `t` plus this duration, or the latest representable time if that is
+    /// out of range
+    pub fn after(&self, t: DateTime<Utc>) -> DateTime<Utc> {
+        t.checked_add_signed(self.as_chrono())
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+}
+
+impl From<Duration> for std::time::Duration {
+    fn from(d: Duration) -> Self {
+        d.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseDurationError {
+    input: String,
+    reason: String,
+}
+
+impl fmt::Display for ParseDurationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid duration '{}': {} (use e.g. 500ms, 90s, 5m, 1h30m, 1d, 2w)",
+            self.input, self.reason
+        )
+    }
+}
+
+impl std::error::Error for ParseDurationError {}
+
+impl FromStr for Duration {
+    type Err = ParseDurationError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let err = |reason: String| ParseDurationError {
+            input: input.to_string(),
+            reason,
+        };
+        let s: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+        if s.is_empty() {
+            return Err(err("empty".to_string()));
+        }
+
+        let mut total: u64 = 0;
+        let mut rest = s.as_str();
+        while !rest.is_empty() {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            if digits == 0 {
+                return Err(err(format!("expected a number at '{}'", rest)));
+            }
+            let (number, tail) = rest.split_at(digits);
+            let unit_len = tail
+                .find(|c: char| c.is_ascii_digit())
+                .unwrap_or(tail.len());
+            let (unit, tail) = tail.split_at(unit_len);
+            if unit.is_empty() {
+                return Err(err(format!("missing unit after '{}'", number)));
+            }
+            let ms = UNITS
+                .iter()
+                .find(|(u, _)| *u == unit)
+                .map(|(_, ms)| *ms)
+                .ok_or_else(|| err(format!("unknown unit '{}'", unit)))?;
+            total = number
+                .parse::<u64>()
+                .ok()
+                .and_then(|n| n.checked_mul(ms))
+                .and_then(|n| total.checked_add(n))
+                .ok_or_else(|| err("too large".to_string()))?;
+            rest = tail;
+        }
+        Ok(Self(std::time::Duration::from_millis(total)))
+    }
+}
+
+/// Shortest compound form, e.g. `1h30m`, `1d`, `500ms`
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut ms = self.0.as_millis() as u64;
+        if ms == 0 {
+            return write!(f, "0s");
+        }
+        for (unit, size) in UNITS {
+            if ms >= *size {
+                write!(f, "{}{}", ms / size, unit)?;
+                ms %= size;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Duration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Duration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = Duration;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "a duration like \"90s\", \"1h30m\" or \"1d\", or seconds"
+                )
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Duration, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Duration, E> {
+                Ok(Duration::from_secs(v))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Duration, E> {
+                u64::try_from(v)
+                    .map(Duration::from_secs)
+                    .map_err(|_| E::custom("duration cannot be negative"))
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(s: &str) -> u64 {
+        s.parse::<Duration>().unwrap().as_secs()
+    }
+
+    #[test]
+    fn test_parse_units_and_compound() {
+        assert_eq!(secs("5m"), 300);
+        assert_eq!(secs("30s"), 30);
+        assert_eq!(secs("2h"), 7200);
+        assert_eq!(secs("1d"), 86400);
+        assert_eq!(secs("2w"), 14 * 86400);
+        assert_eq!(secs("1h30m"), 5400);
+        assert_eq!(secs("1d 12h"), 129600);
+        assert_eq!(
+            "500ms".parse::<Duration>().unwrap().as_std(),
+            std::time::Duration::from_millis(500)
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for bad in [
+            "bad",
+            "",
+            "5x",
+            "30",
+            "h",
+            "1h30",
+            "-5m",
+            "99999999999999999999w",
+        ] {
+            assert!(bad.parse::<Duration>().is_err(), "{}", bad);
+        }
+        let err = "5x".parse::<Duration>().unwrap_err().to_string();
+        assert!(err.contains("unknown unit 'x'"));
+    }
+
+    #[test]
+    fn test_display_roundtrip() {
+        for (input, shown) in [
+            ("90s", "1m30s"),
+            ("1h30m", "1h30m"),
+            ("36h", "1d12h"),
+            ("500ms", "500ms"),
+            ("0s", "0s"),
+        ] {
+            let d: Duration = input.parse().unwrap();
+            assert_eq!(d.to_string(), shown);
+            assert_eq!(shown.parse::<Duration>().unwrap(), d);
+        }
+    }
+
+    #[test]
+    fn test_before_and_after_clamp_huge_durations() {
+        let now = Utc::now();
+        let d: Duration = "1h".parse().unwrap();
+        assert_eq!(d.before(now), now - chrono::Duration::hours(1));
+        let d: Duration = "100000000d".parse().unwrap();
+        assert_eq!(d.before(now), DateTime::<Utc>::MIN_UTC);
+        assert_eq!(d.after(now), DateTime::<Utc>::MAX_UTC);
+    }
+
+    #[test]
+    fn test_serde() {
+        let d: Duration = serde_json::from_str("\"1h30m\"").unwrap();
+        assert_eq!(d.as_secs(), 5400);
+        let d: Duration = serde_json::from_str("600").unwrap();
+        assert_eq!(d.as_secs(), 600);
+        assert_eq!(serde_json::to_string(&d).unwrap(), "\"10m\"");
+        assert!(serde_json::from_str::<Duration>("\"soon\"").is_err());
+    }
+}
@@ -0,0 +1,599 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use super::{Config, ConfigProblem};
+use crate::validate::Severity;
+
+/// Prefix for per-field overrides, e.g. `RESCUECLAW_TELEGRAM_TOKEN`
+pub const ENV_PREFIX: &str = "RESCUECLAW_";
+
+/// Alternative to `--config`
+pub const CONFIG_ENV: &str = "RESCUECLAW_CONFIG";
+
+/// Recognised config file names, per directory
+pub(super) const FILE_NAMES: &[&str] = &[
+    "rescueclaw.json",
+    "rescueclaw.toml",
+    "rescueclaw.yaml",
+    "rescueclaw.yml",
+];
+
+/// Where a config value came from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Origin {
+    Default,
+    File(PathBuf),
+    Env(String),
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Origin::Default => write!(f, "default"),
+            Origin::File(path) => write!(f, "{}", path.display()),
+            Origin::Env(var) => write!(f, "${}", var),
+        }
+    }
+}
+
+/// The effective config and where each value came from
+#[derive(Debug, Clone)]
+pub struct LoadedConfig {
+    pub config: Config,
+    /// Files merged, lowest precedence first
+    pub files: Vec<PathBuf>,
+    /// Origin of every value not left at its default, by JSON path
+    origins: BTreeMap<String, Origin>,
+    /// `RESCUECLAW_*` variables that match no config key
+    unknown_env: Vec<String>,
+}
+
+impl Config {
+    /// Load the effective config: defaults, then the system, user and local
+    /// files (or only `explicit` / `$RESCUECLAW_CONFIG`), then `RESCUECLAW_*`
+    /// environment overrides. Later layers win, key by key.
+    pub fn load_from(explicit: Option<&Path>) -> Result<LoadedConfig> {
+        let explicit = explicit
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os(CONFIG_ENV).map(PathBuf::from));
+        let files = match explicit {
+            Some(path) => {
+                if !path.is_file() {
+                    anyhow::bail!("Config file {} not found", path.display());
+                }
+                vec![path]
+            }
+            None => discover()?,
+        };
+        layered(&files, std::env::vars())
+    }
+}
+
+/// Directories searched for a config file, lowest precedence first
+pub(super) fn search_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::from("/etc/rescueclaw")];
+    if let Some(home) = dirs::home_dir() {
+        dirs.push(home.join(".config/rescueclaw"));
+    }
+    dirs.push(PathBuf::from("."));
+    dirs
+}
+
+/// The config file in each search directory; two formats side by side is
+/// ambiguous and an error
+fn discover() -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for dir in search_dirs() {
+        let found: Vec<PathBuf> = FILE_NAMES
+            .iter()
+            .map(|name| dir.join(name))
+            .filter(|p| p.is_file())
+            .collect();
+        if found.len() > 1 {
+            anyhow::bail!(
+                "Several config files in {}: {} — keep one",
+                dir.display(),
+                found
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        files.extend(found);
+    }
+    Ok(files)
+}
+
+/// Read a config file as JSON, TOML or YAML by its extension
+pub(super) fn read_file(path: &Path) -> Result<Value> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("reading config from {}", path.display()))?;
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let value: Value = match ext {
+        "json" => serde_json::from_str(&content).map_err(anyhow::Error::from),
+        "toml" => toml::from_str(&content).map_err(anyhow::Error::from),
+        "yaml" | "yml" => serde_yaml::from_str(&content).map_err(anyhow::Error::from),
+        _ => anyhow::bail!(
+            "Unsupported config format '{}' (use .json, .toml, .yaml or .yml)",
+            path.display()
+        ),
+    }
+    .with_context(|| format!("parsing config from {}", path.display()))?;
+    if !value.is_object() && !value.is_null() {
+        anyhow::bail!("{}: expected a table of settings", path.display());
+    }
+    Ok(value)
+}
+
+fn layered(
+    files: &[PathBuf],
+    env: impl IntoIterator<Item = (String, String)>,
+) -> Result<LoadedConfig> {
+    let layers = files
+        .iter()
+        .map(|path| Ok((read_file(path)?, Origin::File(path.clone()))))
+        .collect::<Result<Vec<_>>>()?;
+    let mut loaded = layered_values(layers, env)?;
+    loaded.files = files.to_vec();
+    Ok(loaded)
+}
+
+/// Defaults, then each layer in order, then environment overrides
+pub(super) fn layered_values(
+    layers: Vec<(Value, Origin)>,
+    env: impl IntoIterator<Item = (String, String)>,
+) -> Result<LoadedConfig> {
+    let mut merged = serde_json::to_value(Config::default())?;
+    let mut origins = BTreeMap::new();
+
+    for (layer, origin) in layers {
+        if !layer.is_null() {
+            merge(&mut merged, layer, "", &origin, &mut origins);
+        }
+    }
+
+    // Env names are derived from the keys that exist after merging files
+    let by_name: BTreeMap<String, String> = leaves(&merged)
+        .into_iter()
+        .map(|(path, _)| (env_name(&path), path))
+        .collect();
+    let mut unknown_env = Vec::new();
+    let mut overrides: Vec<(String, String)> = env
+        .into_iter()
+        .filter(|(k, _)| k.starts_with(ENV_PREFIX) && k != CONFIG_ENV)
+        .collect();
+    overrides.sort();
+    // Unset optional keys have no type to go by: their values are read as
+    // JSON scalars, and fall back to strings if the key turns out to want one
+    let mut guessed = BTreeMap::new();
+    for (name, raw) in overrides {
+        let Some(path) = by_name.get(&name) else {
+            unknown_env.push(name);
+            continue;
+        };
+        let slot = pointer_mut(&mut merged, path).expect("leaf path exists");
+        if slot.is_null() {
+            guessed.insert(path.clone(), raw.clone());
+        }
+        *slot = env_value(&raw, slot).map_err(|e| anyhow::anyhow!("{}: {}", name, e))?;
+        origins.insert(path.clone(), Origin::Env(name));
+    }
+
+    let config: Config = loop {
+        match serde_path_to_error::deserialize(merged.clone()) {
+            Ok(config) => break config,
+            Err(e) => {
+                let path = e.path().to_string();
+                if let Some(raw) = guessed.remove(&path) {
+                    if let Some(slot) = pointer_mut(&mut merged, &path) {
+                        if !slot.is_string() {
+                            *slot = Value::String(raw);
+                            continue;
+                        }
+                    }
+                }
+                let origin = origin_of(&origins, &path);
+                anyhow::bail!("{}: {} (from {})", path, e.into_inner(), origin);
+            }
+        }
+    };
+
+    Ok(LoadedConfig {
+        config,
+        files: vec![],
+        origins,
+        unknown_env,
+    })
+}
+
+/// Merge `layer` into `base` key by key; arrays and scalars replace
+pub(super) fn merge(
+    base: &mut Value,
+    layer: Value,
+    path: &str,
+    origin: &Origin,
+    origins: &mut BTreeMap<String, Origin>,
+) {
+    match (base, layer) {
+        (Value::Object(base), Value::Object(layer)) => {
+            for (key, value) in layer {
+                let child = join(path, &key);
+                match base.get_mut(&key) {
+                    Some(slot) => merge(slot, value, &child, origin, origins),
+                    None => {
+                        mark(&value, &child, origin, origins);
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (slot, value) => {
+            origins.retain(|k, _| !is_under(k, path));
+            mark(&value, path, origin, origins);
+            *slot = value;
+        }
+    }
+}
+
+fn mark(value: &Value, path: &str, origin: &Origin, origins: &mut BTreeMap<String, Origin>) {
+    for (leaf, _) in leaves_at(value, path) {
+        origins.insert(leaf, origin.clone());
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn is_under(key: &str, path: &str) -> bool {
+    key == path
+        || key
+            .strip_prefix(path)
+            .is_some_and(|rest| rest.starts_with('.') || rest.starts_with('['))
+}
+
+/// Settable values by dotted path: scalars, arrays and empty objects
+pub(super) fn leaves(value: &Value) -> Vec<(String, &Value)> {
+    leaves_at(value, "")
+}
+
+fn leaves_at<'a>(value: &'a Value, path: &str) -> Vec<(String, &'a Value)> {
+    match value {
+        Value::Object(map) if !map.is_empty() => map
+            .iter()
+            .flat_map(|(k, v)| leaves_at(v, &join(path, k)))
+            .collect(),
+        _ => vec![(path.to_string(), value)],
+    }
+}
+
+fn pointer_mut<'a>(value: &'a mut Value, path: &str) -> Option<&'a mut Value> {
+    path.split('.')
+        .try_fold(value, |v, key| v.as_object_mut()?.get_mut(key))
+}
+
+/// `backup.maxSnapshots` → `RESCUECLAW_BACKUP_MAX_SNAPSHOTS`
+fn env_name(path: &str) -> String {
+    let mut name = ENV_PREFIX.to_string();
+    for (i, segment) in path.split('.').enumerate() {
+        if i > 0 {
+            name.push('_');
+        }
+        for (j, c) in segment.chars().enumerate() {
+            if c.is_ascii_uppercase() && j > 0 {
+                name.push('_');
+            }
+            name.push(if c == '-' {
+                '_'
+            } else {
+                c.to_ascii_uppercase()
+            });
+        }
+    }
+    name
+}
+
+/// Parse an env value as the type already at that key
+fn env_value(raw: &str, current: &Value) -> Result<Value, String> {
+    match current {
+        Value::Bool(_) => match raw.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Ok(Value::Bool(true)),
+            "false" | "0" | "no" | "off" => Ok(Value::Bool(false)),
+            _ => Err(format!("expected true or false, got '{}'", raw)),
+        },
+        Value::Number(_) => serde_json::from_str::<serde_json::Number>(raw.trim())
+            .map(Value::Number)
+            .map_err(|_| format!("expected a number, got '{}'", raw)),
+        Value::Array(items) => {
+            if raw.trim_start().starts_with('[') {
+                return serde_json::from_str(raw).map_err(|e| format!("invalid JSON array: {}", e));
+            }
+            // Comma-separated; keep strings as strings when the list holds strings
+            let strings = items.first().is_some_and(Value::is_string);
+            Ok(Value::Array(
+                raw.split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(|s| match serde_json::from_str::<Value>(s) {
+                        Ok(v @ (Value::Number(_) | Value::Bool(_))) if !strings => v,
+                        _ => Value::String(s.to_string()),
+                    })
+                    .collect(),
+            ))
+        }
+        Value::Object(_) => {
+            serde_json::from_str(raw).map_err(|e| format!("invalid JSON object: {}", e))
+        }
+        // Unset optional key: a guess, retried as a string if it doesn't fit
+        Value::Null => Ok(match serde_json::from_str::<Value>(raw.trim()) {
+            Ok(v @ (Value::Bool(_) | Value::Number(_))) => v,
+            _ => env_value(raw, &Value::Bool(false))
+                .unwrap_or_else(|_| Value::String(raw.to_string())),
+        }),
+        Value::String(_) => Ok(Value::String(raw.to_string())),
+    }
+}
+
+/// Origin of the value at `path`, or of the nearest enclosing value
+fn origin_of<'a>(origins: &'a BTreeMap<String, Origin>, path: &str) -> &'a Origin {
+    let mut path = path;
+    loop {
+        if let Some(origin) = origins.get(path) {
+            return origin;
+        }
+        match path.rfind(['.', '[']) {
+            Some(i) => path = &path[..i],
+            None => return &Origin::Default,
+        }
+    }
+}
+
+impl LoadedConfig {
+    /// Where the value at a JSON path (e.g. `backup.schedules[1].cron`) came from
+    pub fn origin(&self, path: &str) -> &Origin {
+        origin_of(&self.origins, path)
+    }
+
+    /// `Config::check` plus unknown `RESCUECLAW_*` variables, each naming
+    /// the file or variable the bad value came from
+    pub fn problems(&self) -> Vec<ConfigProblem> {
+        let mut problems = self.config.check();
+        for p in &mut problems {
+            let origin = self.origin(&p.path);
+            if *origin != Origin::Default {
+                p.message = format!("{} (from {})", p.message, origin);
+            }
+        }
+        for name in &self.unknown_env {
+            problems.push(ConfigProblem {
+                path: format!("${}", name),
+                severity: Severity::Warning,
+                message: "matches no config key — ignored".to_string(),
+            });
+        }
+        problems
+    }
+
+    /// What the config was read from, for display
+    pub fn sources(&self) -> String {
+        let mut sources: Vec<String> = self.files.iter().map(|p| p.display().to_string()).collect();
+        if self.origins.values().any(|o| matches!(o, Origin::Env(_))) {
+            sources.push("environment".to_string());
+        }
+        if sources.is_empty() {
+            "built-in defaults".to_string()
+        } else {
+            sources.join(" + ")
+        }
+    }
+
+    /// Problems for the daemon: fails when there's nothing to run from or
+    /// any error, otherwise returns the warnings
+    pub fn require_valid(&self) -> Result<Vec<ConfigProblem>> {
+        if self.files.is_empty() && self.origins.is_empty() {
+            anyhow::bail!(
+                "No config file found (looked for rescueclaw.json/.toml/.yaml in {}) — run `rescueclaw setup` first",
+                search_dirs()
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        let problems = self.problems();
+        if Config::has_errors(&problems) {
+            let list: Vec<String> = problems
+                .iter()
+                .filter(|p| p.severity == Severity::Error)
+                .map(|p| format!("  {}", p))
+                .collect();
+            anyhow::bail!(
+                "Invalid config ({}):\n{}\nFix these and run `rescueclaw config check`",
+                self.sources(),
+                list.join("\n")
+            );
+        }
+        Ok(problems)
+    }
+
+    /// Every effective value (secrets masked) with where it came from
+    pub fn show(&self) -> Result<String> {
+        let value = serde_json::to_value(self.config.masked())?;
+        let rows: Vec<(String, String, &Origin)> = leaves(&value)
+            .into_iter()
+            .map(|(path, v)| {
+                let origin = self.origin(&path);
+                (path, v.to_string(), origin)
+            })
+            .collect();
+        let width = rows.iter().map(|(p, _, _)| p.len()).max().unwrap_or(0);
+        let mut out = String::new();
+        for (path, value, origin) in rows {
+            out.push_str(&format!(
+                "{:<width$} = {:<24} # {}\n",
+                path,
+                value,
+                origin,
+                width = width
+            ));
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_layers_merge_key_by_key_with_provenance() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let system = write(
+            dir.path(),
+            "system.toml",
+            "[backup]\ninterval = \"12h\"\nmaxSnapshots = 20\n\n[telegram]\nallowedUsers = [1]\n",
+        );
+        let user = write(
+            dir.path(),
+            "user.yaml",
+            "backup:\n  maxSnapshots: 5\nhealth:\n  quietHours: ['02:00-03:00']\n",
+        );
+        let local = write(dir.path(), "local.json", r#"{"timezone": "Europe/Berlin"}"#);
+
+        let loaded = layered(
+            &[system.clone(), user.clone(), local.clone()],
+            env(&[
+                ("RESCUECLAW_TELEGRAM_TOKEN", "123:secret"),
+                ("RESCUECLAW_TELEGRAM_ALLOWED_USERS", "7,8"),
+                ("RESCUECLAW_BACKUP_SKIP_UNCHANGED", "no"),
+                ("RESCUECLAW_JOURNAL_IGNORE", ".git,target"),
+                ("RESCUECLAW_BAKCUP_PATH", "/typo"),
+                ("HOME", "/root"),
+            ]),
+        )
+        .unwrap();
+        let cfg = &loaded.config;
+
+        assert_eq!(cfg.backup.interval.to_string(), "12h");
+        assert_eq!(cfg.backup.max_snapshots, 5);
+        assert_eq!(cfg.health.quiet_hours, vec!["02:00-03:00"]);
+        assert_eq!(cfg.timezone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(cfg.telegram.token, "123:secret");
+        assert_eq!(cfg.telegram.allowed_users, vec![7, 8]);
+        assert_eq!(cfg.backup.skip_unchanged, Some(false));
+        assert_eq!(cfg.journal.ignore, vec![".git", "target"]);
+        assert_eq!(cfg.health.check_interval.to_string(), "5m");
+
+        assert_eq!(*loaded.origin("backup.interval"), Origin::File(system));
+        assert_eq!(
+            *loaded.origin("backup.maxSnapshots"),
+            Origin::File(user.clone())
+        );
+        assert_eq!(*loaded.origin("health.quietHours[0]"), Origin::File(user));
+        assert_eq!(*loaded.origin("timezone"), Origin::File(local));
+        assert_eq!(
+            *loaded.origin("telegram.token"),
+            Origin::Env("RESCUECLAW_TELEGRAM_TOKEN".into())
+        );
+        assert_eq!(*loaded.origin("backup.path"), Origin::Default);
+
+        let shown = loaded.show().unwrap();
+        assert!(shown.contains("\"123:********\""), "{}", shown);
+        assert!(!shown.contains("secret"));
+        assert!(loaded
+            .problems()
+            .iter()
+            .any(|p| p.path == "$RESCUECLAW_BAKCUP_PATH"));
+    }
+
+    #[test]
+    fn test_errors_name_the_key_and_its_origin() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = write(
+            dir.path(),
+            "rescueclaw.json",
+            r#"{"health": {"checkInterval": "5x"}}"#,
+        );
+        let err = layered(std::slice::from_ref(&file), env(&[]))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.starts_with("health.checkInterval: invalid duration '5x'"),
+            "{}",
+            err
+        );
+        assert!(err.contains(&file.display().to_string()));
+
+        let file = write(
+            dir.path(),
+            "rescueclaw.json",
+            r#"{"health": {"checkInterval": "1h30m"}, "guard": {"rollbackWindow": 90}}"#,
+        );
+        let cfg = layered(&[file], env(&[])).unwrap().config;
+        assert_eq!(cfg.health.check_interval.as_secs(), 5400);
+        assert_eq!(cfg.guard.rollback_window.as_secs(), 90);
+
+        let err = layered(&[], env(&[("RESCUECLAW_BACKUP_MAX_SNAPSHOTS", "lots")]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("RESCUECLAW_BACKUP_MAX_SNAPSHOTS: expected a number"));
+    }
+
+    #[test]
+    fn test_env_sets_unset_optional_keys() {
+        let cfg = layered(
+            &[],
+            env(&[
+                ("RESCUECLAW_OPENCLAW_CONTAINER_MODE", "true"),
+                ("RESCUECLAW_OPENCLAW_GATEWAY_PORT", "8080"),
+                ("RESCUECLAW_OPENCLAW_GATEWAY_TOKEN", "12345"),
+                ("RESCUECLAW_TIMEZONE", "UTC"),
+            ]),
+        )
+        .unwrap()
+        .config;
+        assert_eq!(cfg.openclaw.container_mode, Some(true));
+        assert_eq!(cfg.openclaw.gateway.port, Some(8080));
+        // A number-looking value for a string key stays a string
+        assert_eq!(cfg.openclaw.gateway.token.as_deref(), Some("12345"));
+        assert_eq!(cfg.timezone.as_deref(), Some("UTC"));
+
+        let err = layered(&[], env(&[("RESCUECLAW_OPENCLAW_GATEWAY_PORT", "big")]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("openclaw.gateway.port:"), "{}", err);
+    }
+
+    #[test]
+    fn test_env_names() {
+        assert_eq!(
+            env_name("backup.maxSnapshots"),
+            "RESCUECLAW_BACKUP_MAX_SNAPSHOTS"
+        );
+        assert_eq!(env_name("timezone"), "RESCUECLAW_TIMEZONE");
+        assert_eq!(
+            env_name("validation.rules.config-parse"),
+            "RESCUECLAW_VALIDATION_RULES_CONFIG_PARSE"
+        );
+    }
+}
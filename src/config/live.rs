@@ -0,0 +1,240 @@
+use anyhow::Result;
+use notify::{RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::watch;
+
+use super::layers::{leaves, search_dirs, FILE_NAMES};
+use super::Config;
+
+/// Quiet period after a config file event before reloading (editors write in bursts)
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Settings only read when the daemon starts (the bot connection)
+const RESTART_KEYS: &[&str] = &["telegram.token"];
+
+/// The daemon's config, replaced in place on reload. Loops read `current()`
+/// each cycle, so a reload applies without restarting them.
+#[derive(Clone)]
+pub struct LiveConfig(watch::Receiver<Arc<Config>>);
+
+impl LiveConfig {
+    pub fn new(cfg: Config) -> (watch::Sender<Arc<Config>>, Self) {
+        let (tx, rx) = watch::channel(Arc::new(cfg));
+        (tx, Self(rx))
+    }
+
+    pub fn current(&self) -> Arc<Config> {
+        self.0.borrow().clone()
+    }
+
+    /// Wait for the next reload
+    pub async fn changed(&mut self) -> Arc<Config> {
+        if self.0.changed().await.is_err() {
+            // Nobody reloads any more; the current config is final
+            std::future::pending::<()>().await;
+        }
+        self.0.borrow_and_update().clone()
+    }
+
+    /// Wait for a reload that changes what `key` picks out of the config
+    pub async fn changed_in<K: PartialEq>(&mut self, key: impl Fn(&Config) -> K) -> Arc<Config> {
+        let before = key(&self.current());
+        loop {
+            let cfg = self.changed().await;
+            if key(&cfg) != before {
+                return cfg;
+            }
+        }
+    }
+}
+
+/// Settings that differ between two configs, as `key: old → new` with
+/// secrets masked. Secrets are compared unmasked, so a new token counts.
+pub fn diff(old: &Config, new: &Config) -> Vec<String> {
+    let values = |cfg: &Config| -> Option<(serde_json::Value, serde_json::Value)> {
+        Some((
+            serde_json::to_value(cfg).ok()?,
+            serde_json::to_value(cfg.masked()).ok()?,
+        ))
+    };
+    let (Some((old_raw, old_shown)), Some((new_raw, new_shown))) = (values(old), values(new))
+    else {
+        return vec![];
+    };
+    type Leaves<'a> = std::collections::BTreeMap<String, &'a serde_json::Value>;
+    let leaf_map = |v| -> Leaves { leaves(v).into_iter().collect() };
+    let (old, new) = (leaf_map(&old_raw), leaf_map(&new_raw));
+    let (old_shown, new_shown) = (leaf_map(&old_shown), leaf_map(&new_shown));
+
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter_map(|key| {
+            if old.get(key) == new.get(key) {
+                return None;
+            }
+            let (before, after) = (old_shown.get(key), new_shown.get(key));
+            let show =
+                |v: Option<&&serde_json::Value>| v.map_or("-".to_string(), |v| v.to_string());
+            let restart = RESTART_KEYS.contains(&key.as_str());
+            Some(format!(
+                "{}: {} → {}{}",
+                key,
+                show(before),
+                show(after),
+                if restart {
+                    " (applies after a restart)"
+                } else {
+                    ""
+                }
+            ))
+        })
+        .collect()
+}
+
+/// Load and check the config again; `None` when nothing changed
+fn reload(explicit: Option<&Path>, current: &Config) -> Result<Option<(Config, Vec<String>)>> {
+    let loaded = Config::load_from(explicit)?;
+    loaded.require_valid()?;
+    let changes = diff(current, &loaded.config);
+    Ok((!changes.is_empty()).then_some((loaded.config, changes)))
+}
+
+/// Reload the config on SIGHUP or when a config file changes. An invalid
+/// config is logged and the running one kept.
+pub async fn reload_loop(explicit: Option<PathBuf>, tx: watch::Sender<Arc<Config>>) -> Result<()> {
+    let mut hangup = signal(SignalKind::hangup())?;
+
+    // Watch directories, not files: editors often save by renaming
+    let dirs: Vec<PathBuf> = match &explicit {
+        Some(path) => vec![path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+            .to_path_buf()],
+        None => search_dirs().into_iter().filter(|d| d.is_dir()).collect(),
+    };
+    let names: Vec<String> = match &explicit {
+        Some(path) => path
+            .file_name()
+            .map(|n| vec![n.to_string_lossy().into_owned()])
+            .unwrap_or_default(),
+        None => FILE_NAMES.iter().map(|n| n.to_string()).collect(),
+    };
+
+    let (events_tx, mut events) = tokio::sync::mpsc::unbounded_channel();
+    let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            let _ = events_tx.send(event);
+        }
+    })
+    .and_then(|mut w| {
+        for dir in &dirs {
+            w.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+        Ok(w)
+    });
+    let _watcher = match watcher {
+        Ok(w) => Some(w),
+        Err(e) => {
+            tracing::warn!("Cannot watch config files ({}) — reload with SIGHUP", e);
+            None
+        }
+    };
+
+    loop {
+        let trigger = tokio::select! {
+            _ = hangup.recv() => "SIGHUP",
+            Some(event) = events.recv() => {
+                let ours = event.paths.iter().any(|p| {
+                    p.file_name()
+                        .is_some_and(|n| names.iter().any(|name| n == name.as_str()))
+                });
+                if !ours || event.kind.is_access() {
+                    continue;
+                }
+                // Drain the rest of the burst
+                while let Ok(Some(_)) = tokio::time::timeout(DEBOUNCE, events.recv()).await {}
+                "file change"
+            }
+        };
+
+        let current = tx.borrow().clone();
+        match reload(explicit.as_deref(), &current) {
+            Ok(Some((cfg, changes))) => {
+                tracing::info!(
+                    "Config reloaded ({}): {} setting(s) changed",
+                    trigger,
+                    changes.len()
+                );
+                for change in &changes {
+                    tracing::info!("  {}", change);
+                }
+                tx.send_replace(Arc::new(cfg));
+            }
+            Ok(None) => tracing::info!("Config reloaded ({}): no changes", trigger),
+            Err(e) => tracing::error!(
+                "Config reload rejected ({}): {:#} — keeping the running config",
+                trigger,
+                e
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_live_config_swaps_and_diffs() {
+        let old = Config::default();
+        let mut new = old.clone();
+        new.backup.interval = "12h".parse().unwrap();
+        new.telegram.token = "123:secret".into();
+        new.telegram.allowed_users = vec![42];
+
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                "backup.interval: \"6h\" → \"12h\"",
+                "telegram.allowedUsers: [] → [42]",
+                "telegram.token: \"\" → \"123:********\" (applies after a restart)",
+            ]
+        );
+        assert!(diff(&old, &old).is_empty());
+
+        // Only the masked part of a secret changes
+        let mut rotated = new.clone();
+        rotated.telegram.token = "123:other".into();
+        rotated.openclaw.gateway.token = Some("gw-secret".into());
+        let changes = diff(&new, &rotated);
+        assert_eq!(changes.len(), 2, "{:?}", changes);
+        assert!(changes.iter().all(|c| !c.contains("secret")));
+
+        let (tx, live) = LiveConfig::new(old);
+        let mut watcher = live.clone();
+        tx.send_replace(Arc::new(new));
+        assert_eq!(watcher.changed().await.backup.interval.to_string(), "12h");
+        assert_eq!(live.current().telegram.allowed_users, vec![42]);
+
+        // Watchers restart only for the keys they were set up from
+        let mut paths = live.clone();
+        let mut next = Config::clone(&live.current());
+        next.backup.interval = "1d".parse().unwrap();
+        tx.send_replace(Arc::new(next.clone()));
+        next.openclaw.config_path = "/srv/openclaw".into();
+        let sender = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            tx.send_replace(Arc::new(next));
+        });
+        let cfg = paths.changed_in(|c| c.openclaw.config_path.clone()).await;
+        assert_eq!(cfg.openclaw.config_path, PathBuf::from("/srv/openclaw"));
+        sender.await.unwrap();
+    }
+}
@@ -1,6 +1,20 @@
-use anyhow::{Context, Result};
+pub mod check;
+pub mod duration;
+pub mod layers;
+pub mod live;
+pub mod openclaw;
+pub mod setup;
+pub mod users;
+
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub use check::ConfigProblem;
+pub use duration::Duration;
+pub use live::LiveConfig;
+pub use openclaw::{OpenClawFile, ProviderEntry};
 
 /// Main configuration — rescueclaw's own settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,28 +23,111 @@ pub struct Config {
     pub health: HealthConfig,
     pub telegram: TelegramConfig,
     pub openclaw: OpenClawConfig,
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    #[serde(default)]
+    pub validation: ValidationConfig,
+    #[serde(default)]
+    pub guard: GuardConfig,
+    #[serde(default)]
+    pub journal: JournalConfig,
+    /// IANA time zone for schedules and quiet hours (system local if unset)
+    #[serde(default)]
+    pub timezone: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackupConfig {
-    pub interval: String,
+    pub interval: Duration,
     #[serde(rename = "maxSnapshots")]
     pub max_snapshots: usize,
     pub path: PathBuf,
     #[serde(rename = "includeSessions")]
     pub include_sessions: bool,
+    /// When scheduled backups run: every `interval`, or after changes settle
+    #[serde(default)]
+    pub mode: BackupMode,
+    /// Quiet period after the last change before a snapshot (changes mode)
+    #[serde(default = "default_debounce")]
+    pub debounce: Duration,
+    /// Shortest gap between change-triggered snapshots
+    #[serde(rename = "minInterval", default = "default_min_interval")]
+    pub min_interval: Duration,
+    /// Longest gap between snapshots in changes mode (defaults to `interval`)
+    #[serde(rename = "maxInterval", default)]
+    pub max_interval: Option<Duration>,
+    /// Longest a stream of changes can hold off a change-triggered snapshot
+    #[serde(rename = "maxWait", default = "default_max_wait")]
+    pub max_wait: Duration,
+    /// Skip a scheduled snapshot when the content matches the previous one
+    /// (defaults to on, except in interval mode)
+    #[serde(rename = "skipUnchanged", default)]
+    pub skip_unchanged: Option<bool>,
+    /// Named cron schedules (schedule mode)
+    #[serde(default)]
+    pub schedules: Vec<BackupSchedule>,
+    /// Windows with no scheduled backups, e.g. `02:00-03:00`, `sat,sun 00:00-06:00`
+    #[serde(rename = "quietHours", default)]
+    pub quiet_hours: Vec<String>,
+}
+
+/// A named cron schedule for backups
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSchedule {
+    pub name: String,
+    /// Cron expression: `min hour day month weekday`, optionally with seconds first
+    pub cron: String,
+    /// Override `includeSessions` for this schedule (e.g. a nightly full backup)
+    #[serde(rename = "includeSessions", default)]
+    pub include_sessions: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupMode {
+    /// Every `interval`
+    #[default]
+    Interval,
+    /// After filesystem changes, debounced and bounded by min/max interval
+    Changes,
+    /// On the cron `schedules`
+    Schedule,
+}
+
+fn default_debounce() -> Duration {
+    Duration::from_secs(2 * 60)
+}
+
+fn default_min_interval() -> Duration {
+    Duration::from_secs(10 * 60)
+}
+
+fn default_max_wait() -> Duration {
+    Duration::from_secs(15 * 60)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthConfig {
     #[serde(rename = "checkInterval")]
-    pub check_interval: String,
+    pub check_interval: Duration,
     #[serde(rename = "unhealthyThreshold")]
     pub unhealthy_threshold: u32,
     #[serde(rename = "autoRestore")]
     pub auto_restore: bool,
+    /// Least time between auto-restores in separate incidents
     #[serde(rename = "autoRestoreCooldown")]
-    pub auto_restore_cooldown: Option<String>,
+    pub auto_restore_cooldown: Option<Duration>,
+    /// Run LLM incident analysis after an auto-restore
+    #[serde(rename = "analyzeOnRestore", default)]
+    pub analyze_on_restore: bool,
+    /// Maintenance windows during which auto-restore is suppressed
+    #[serde(rename = "quietHours", default)]
+    pub quiet_hours: Vec<String>,
+    /// What to try, in order, once the agent is unhealthy: `restart [wait]`,
+    /// `restore [wait]` (next older known-good snapshot), `alert [wait]`.
+    /// Restart and restore steps are skipped unless `autoRestore` is on.
+    #[serde(default = "default_escalation")]
+    pub escalation: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +135,9 @@ pub struct TelegramConfig {
     pub token: String,
     #[serde(rename = "allowedUsers")]
     pub allowed_users: Vec<i64>,
+    /// Users who may hand out `/pair` codes (every allowed user if empty)
+    #[serde(default)]
+    pub admins: Vec<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,71 +145,334 @@ pub struct OpenClawConfig {
     pub workspace: PathBuf,
     #[serde(rename = "configPath")]
     pub config_path: PathBuf,
+    /// How the gateway is stopped and started around a restore
+    #[serde(default)]
+    pub gateway: GatewayConfig,
+    /// RescueClaw runs in its own container next to the agent's: the gateway
+    /// is reached by container name and restarted through the Docker socket,
+    /// and restored files keep the owners they were backed up with. Detected
+    /// from /.dockerenv or /run/.containerenv when unset.
+    #[serde(rename = "containerMode", default)]
+    pub container_mode: Option<bool>,
+}
+
+impl BackupConfig {
+    pub fn skip_unchanged(&self) -> bool {
+        self.skip_unchanged
+            .unwrap_or(self.mode != BackupMode::Interval)
+    }
+}
+
+impl OpenClawConfig {
+    pub fn container_mode(&self) -> bool {
+        self.container_mode.unwrap_or_else(in_container)
+    }
+}
+
+/// Whether this process runs inside a Docker or Podman container
+pub fn in_container() -> bool {
+    Path::new("/.dockerenv").exists() || Path::new("/run/.containerenv").exists()
 }
 
-/// Model/provider config read from OpenClaw's own config
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OpenClawProviderConfig {
-    pub default_model: Option<String>,
-    pub providers: Option<serde_json::Value>,
+pub struct GatewayConfig {
+    /// What runs the gateway
+    #[serde(default)]
+    pub driver: GatewayDriver,
+    /// How long the gateway gets to exit after being asked before it's killed
+    #[serde(rename = "gracePeriod", default = "default_grace_period")]
+    pub grace_period: Duration,
+    /// CLI that starts it (cli driver; `openclaw`, else `clawdbot`, if unset)
+    #[serde(default)]
+    pub cli: Option<String>,
+    /// Unit name (systemd driver)
+    #[serde(default = "default_gateway_unit")]
+    pub unit: String,
+    /// A `systemctl --user` unit rather than a system one (systemd driver)
+    #[serde(rename = "userUnit", default = "default_true")]
+    pub user_unit: bool,
+    /// Container name or ID (docker driver)
+    #[serde(default = "default_gateway_container")]
+    pub container: String,
+    /// Shell commands (custom driver). Stop and kill default to SIGTERM and
+    /// SIGKILL to the process listening on the gateway port.
+    #[serde(rename = "startCommand", default)]
+    pub start_command: Option<String>,
+    #[serde(rename = "stopCommand", default)]
+    pub stop_command: Option<String>,
+    #[serde(rename = "killCommand", default)]
+    pub kill_command: Option<String>,
+    /// Host the gateway listens on: 127.0.0.1, or in container mode with the
+    /// docker driver the container's name, if unset
+    #[serde(default)]
+    pub host: Option<String>,
+    /// Gateway port, if not the one in openclaw.json
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// `http` or `https`; https when openclaw.json enables `gateway.tls`, if unset
+    #[serde(default)]
+    pub scheme: Option<GatewayScheme>,
+    /// Health endpoint path
+    #[serde(rename = "statusPath", default = "default_status_path")]
+    pub status_path: String,
+    /// Header carrying `token`; `Authorization` sends `Bearer <token>`
+    #[serde(rename = "authHeader", default = "default_auth_header")]
+    pub auth_header: String,
+    /// Gateway API token; `gateway.auth.token` from openclaw.json, else
+    /// `$OPENCLAW_GATEWAY_TOKEN`, if unset
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default)]
+    pub tls: GatewayTlsConfig,
+    /// Docker Engine API socket (docker driver)
+    #[serde(rename = "dockerSocket", default = "default_docker_socket")]
+    pub docker_socket: PathBuf,
 }
 
-impl Config {
-    /// Standard config file locations (checked in order)
-    fn config_paths() -> Vec<PathBuf> {
-        let mut paths = vec![
-            PathBuf::from("rescueclaw.json"),
-            PathBuf::from("/etc/rescueclaw/rescueclaw.json"),
-        ];
-        if let Some(home) = dirs::home_dir() {
-            paths.insert(1, home.join(".config/rescueclaw/rescueclaw.json"));
+impl Default for GatewayConfig {
+    fn default() -> Self {
+        GatewayConfig {
+            driver: GatewayDriver::default(),
+            grace_period: default_grace_period(),
+            cli: None,
+            unit: default_gateway_unit(),
+            user_unit: true,
+            container: default_gateway_container(),
+            start_command: None,
+            stop_command: None,
+            kill_command: None,
+            host: None,
+            port: None,
+            scheme: None,
+            status_path: default_status_path(),
+            auth_header: default_auth_header(),
+            token: None,
+            tls: GatewayTlsConfig::default(),
+            docker_socket: default_docker_socket(),
         }
-        paths
     }
+}
 
-    /// Load config from first available location
-    pub fn load() -> Result<Self> {
-        for path in Self::config_paths() {
-            if path.exists() {
-                let content = std::fs::read_to_string(&path)
-                    .with_context(|| format!("reading config from {}", path.display()))?;
-                let config: Config = serde_json::from_str(&content)
-                    .with_context(|| format!("parsing config from {}", path.display()))?;
-                return Ok(config);
-            }
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GatewayScheme {
+    Http,
+    Https,
+}
+
+/// How the gateway's certificate is checked over https
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GatewayTlsConfig {
+    /// PEM CA (or self-signed certificate) to trust; from openclaw.json's
+    /// `gateway.tls.caPath` / `certPath` if unset
+    #[serde(rename = "caCert", default)]
+    pub ca_cert: Option<PathBuf>,
+    /// Skip certificate verification altogether
+    #[serde(default)]
+    pub insecure: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GatewayDriver {
+    /// `openclaw gateway start`; stopped by signalling the process on the port
+    #[default]
+    Cli,
+    /// A systemd unit
+    Systemd,
+    /// A Docker container
+    Docker,
+    /// `startCommand` / `stopCommand` / `killCommand`
+    Custom,
+}
+
+/// Secret masking for everything sent to LLMs and chat
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    /// Extra regexes to mask, on top of the built-in API key/token rules
+    #[serde(rename = "customPatterns", default)]
+    pub custom_patterns: Vec<String>,
+    #[serde(rename = "redactEmails", default = "default_true")]
+    pub redact_emails: bool,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        RedactionConfig {
+            custom_patterns: vec![],
+            redact_emails: true,
         }
-        // Return default config if no file found (setup wizard will create one)
-        Ok(Config::default())
     }
+}
 
-    /// Read OpenClaw's provider/model config to reuse API keys and model settings
-    pub fn read_openclaw_providers(&self) -> Result<OpenClawProviderConfig> {
-        let oc_config_path = self.openclaw.config_path.join("openclaw.json");
-        // Also try legacy path
-        let legacy_path = self.openclaw.config_path.join("clawdbot.json");
+/// Tuning for OpenClaw config/workspace validation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ValidationConfig {
+    /// Per-rule overrides by rule ID: `off`, `warning` or `error`
+    #[serde(default)]
+    pub rules: BTreeMap<String, RuleLevel>,
+    /// JSON file with user-defined rules
+    #[serde(rename = "rulesFile", default)]
+    pub rules_file: Option<PathBuf>,
+}
 
-        let config_file = if oc_config_path.exists() {
-            oc_config_path
-        } else if legacy_path.exists() {
-            legacy_path
-        } else {
-            anyhow::bail!(
-                "OpenClaw config not found at {} or {}",
-                oc_config_path.display(),
-                legacy_path.display()
-            );
-        };
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    Off,
+    Warning,
+    Error,
+}
+
+/// Watches openclaw.json and validates every edit as it lands
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// What to do when an edit fails validation
+    #[serde(default)]
+    pub policy: GuardPolicy,
+    /// How long a checkpoint stays armed for rollback (checkpoint policy)
+    #[serde(rename = "rollbackWindow", default = "default_rollback_window")]
+    pub rollback_window: Duration,
+}
 
-        let content = std::fs::read_to_string(&config_file)?;
-        let raw: serde_json::Value = serde_json::from_str(&content)?;
+impl Default for GuardConfig {
+    fn default() -> Self {
+        GuardConfig {
+            enabled: true,
+            policy: GuardPolicy::default(),
+            rollback_window: default_rollback_window(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GuardPolicy {
+    /// Notify only
+    #[default]
+    Alert,
+    /// Put the last valid config back
+    Revert,
+    /// Snapshot the last valid config and arm a health-check rollback
+    Checkpoint,
+}
+
+/// Filesystem change journal for the workspace and config dirs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// How long to keep changes
+    #[serde(default = "default_retention")]
+    pub retention: Duration,
+    /// Path components to ignore anywhere in the tree; `*.log` matches by suffix
+    #[serde(default = "default_journal_ignore")]
+    pub ignore: Vec<String>,
+    /// Files (`workspace/...` or `config/...`) worth a snapshot when they change
+    #[serde(rename = "protectedFiles", default = "default_protected_files")]
+    pub protected_files: Vec<String>,
+    /// Take a snapshot as soon as a protected file changes
+    #[serde(rename = "snapshotOnProtectedChange", default)]
+    pub snapshot_on_protected_change: bool,
+}
+
+impl Default for JournalConfig {
+    fn default() -> Self {
+        JournalConfig {
+            enabled: true,
+            retention: default_retention(),
+            ignore: default_journal_ignore(),
+            protected_files: default_protected_files(),
+            snapshot_on_protected_change: false,
+        }
+    }
+}
+
+fn default_retention() -> Duration {
+    Duration::from_secs(7 * 24 * 3600)
+}
+
+fn default_journal_ignore() -> Vec<String> {
+    // Logs and session transcripts change constantly while the agent runs
+    [".git", "node_modules", "logs", "sessions", "*.log"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_protected_files() -> Vec<String> {
+    vec![
+        "workspace/SOUL.md".to_string(),
+        "workspace/AGENTS.md".to_string(),
+    ]
+}
+
+fn default_rollback_window() -> Duration {
+    Duration::from_secs(10 * 60)
+}
+
+fn default_escalation() -> Vec<String> {
+    [
+        "restart 1m",
+        "restart 5m",
+        "restore 5m",
+        "restore 5m",
+        "alert",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+fn default_grace_period() -> Duration {
+    Duration::from_secs(10)
+}
+
+fn default_gateway_unit() -> String {
+    "openclaw-gateway".to_string()
+}
+
+fn default_gateway_container() -> String {
+    "openclaw".to_string()
+}
+
+fn default_status_path() -> String {
+    "/api/status".to_string()
+}
+
+fn default_auth_header() -> String {
+    "Authorization".to_string()
+}
+
+fn default_docker_socket() -> PathBuf {
+    PathBuf::from("/var/run/docker.sock")
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Config {
+    /// Copy safe to print: the bot token keeps only its public bot ID, the
+    /// gateway token is hidden
+    pub fn masked(&self) -> Self {
+        let mut cfg = self.clone();
+        if !cfg.telegram.token.is_empty() {
+            cfg.telegram.token = match cfg.telegram.token.split_once(':') {
+                Some((id, _)) => format!("{}:********", id),
+                None => "********".to_string(),
+            };
+        }
+        if let Some(token) = &mut cfg.openclaw.gateway.token {
+            *token = "********".to_string();
+        }
+        cfg
+    }
 
-        Ok(OpenClawProviderConfig {
-            default_model: raw
-                .get("defaultModel")
-                .and_then(|v| v.as_str())
-                .map(String::from),
-            providers: raw.get("providers").cloned(),
-        })
+    /// Read OpenClaw's own config to reuse API keys, model settings and gateway port
+    pub fn read_openclaw_config(&self) -> Result<OpenClawFile> {
+        OpenClawFile::load(&self.openclaw.config_path)
     }
 }
 
@@ -117,63 +480,106 @@ impl Default for Config {
     fn default() -> Self {
         Config {
             backup: BackupConfig {
-                interval: "6h".to_string(),
+                interval: Duration::from_secs(6 * 3600),
                 max_snapshots: 10,
                 path: PathBuf::from("/var/rescueclaw/backups"),
                 include_sessions: false,
+                mode: BackupMode::default(),
+                debounce: default_debounce(),
+                min_interval: default_min_interval(),
+                max_interval: None,
+                max_wait: default_max_wait(),
+                skip_unchanged: None,
+                schedules: vec![],
+                quiet_hours: vec![],
             },
             health: HealthConfig {
-                check_interval: "5m".to_string(),
+                check_interval: Duration::from_secs(5 * 60),
                 unhealthy_threshold: 3,
                 auto_restore: false,
-                auto_restore_cooldown: Some("1h".to_string()),
+                auto_restore_cooldown: Some(Duration::from_secs(3600)),
+                analyze_on_restore: false,
+                quiet_hours: vec![],
+                escalation: default_escalation(),
             },
             telegram: TelegramConfig {
                 token: String::new(),
                 allowed_users: vec![],
+                admins: vec![],
             },
             openclaw: OpenClawConfig {
                 workspace: PathBuf::from(""),
                 config_path: dirs::home_dir().unwrap_or_default().join(".openclaw"),
+                gateway: GatewayConfig::default(),
+                container_mode: None,
             },
+            redaction: RedactionConfig::default(),
+            validation: ValidationConfig::default(),
+            guard: GuardConfig::default(),
+            journal: JournalConfig::default(),
+            timezone: None,
         }
     }
 }
 
-/// Interactive setup wizard
-pub async fn setup_wizard() -> Result<()> {
+/// Interactive setup wizard; writes `target` (or the user config file)
+pub async fn setup_wizard(opts: &setup::SetupOptions, target: Option<&Path>) -> Result<()> {
     use std::io::{self, Write};
 
     println!("🛟 RescueClaw Setup");
     println!("━━━━━━━━━━━━━━━━━━\n");
 
+    // Fail on an unsupported file type before asking anything
+    if let Some(path) = target {
+        setup::render(path, &serde_json::json!({}))?;
+    }
+
     // Step 1: Detect OpenClaw
     println!("Step 1/6: Detect OpenClaw");
-    let workspace = detect_openclaw_workspace()?;
+    let workspace = match &opts.workspace {
+        Some(path) => path.clone(),
+        None => detect_openclaw_workspace()?,
+    };
     println!("  ✓ Workspace: {}", workspace.display());
 
-    let config_path = detect_openclaw_config()?;
+    let config_path = match &opts.config_path {
+        Some(path) => path.clone(),
+        None => detect_openclaw_config()?,
+    };
     println!("  ✓ Config:    {}", config_path.display());
 
-    // Check if gateway is running
-    let gateway_running = check_gateway_running().await;
-    if gateway_running {
-        println!("  ✓ Gateway:   Running");
-    } else {
-        println!("  ⚠ Gateway:   Not responding on :7744");
+    // In a container the gateway lives in a sibling container, restarted
+    // through the Docker socket
+    let mut gateway = GatewayConfig::default();
+    if in_container() {
+        gateway.driver = GatewayDriver::Docker;
+        println!(
+            "  ✓ Container: yes (gateway driver: docker, container {})",
+            gateway.container
+        );
     }
-
-    // Validate OpenClaw config
     let oc_config = OpenClawConfig {
         workspace: workspace.clone(),
         config_path: config_path.clone(),
+        gateway,
+        container_mode: None,
     };
     let temp_cfg = Config {
         openclaw: oc_config.clone(),
         ..Default::default()
     };
 
-    match temp_cfg.read_openclaw_providers() {
+    // Check if gateway is running
+    let endpoint = crate::gateway::endpoint(&temp_cfg);
+    if endpoint.probe(std::time::Duration::from_secs(5)).await {
+        println!("  ✓ Gateway:   Running");
+    } else {
+        println!("  ⚠ Gateway:   Not responding at {}", endpoint.url);
+    }
+
+    // Validate OpenClaw config
+
+    match temp_cfg.read_openclaw_config() {
         Ok(_) => println!("  ✓ Config:    Valid"),
         Err(e) => println!("  ⚠ Config:    {}", e),
     }
@@ -198,6 +604,10 @@ pub async fn setup_wizard() -> Result<()> {
             continue;
         }
 
+        if opts.skip_telegram_check {
+            break input;
+        }
+
         // Test token
         print!("  Testing token...");
         io::stdout().flush()?;
@@ -213,30 +623,39 @@ pub async fn setup_wizard() -> Result<()> {
         }
     };
 
-    println!("\n  Now send /start to your bot in Telegram.");
-    println!("  Then get your user ID from @userinfobot (send any message to it).\n");
+    println!();
+    let mut allowed_users = if opts.skip_telegram_check {
+        vec![]
+    } else {
+        users::discover(&token, std::time::Duration::from_secs(120)).await
+    };
 
-    let user_id: i64 = loop {
-        let input = prompt("Your Telegram user ID: ", "")?;
-        match input.parse() {
-            Ok(id) => break id,
-            Err(_) => {
-                println!("  ❌ Must be a number");
-                continue;
+    if allowed_users.is_empty() {
+        println!("  Get your user ID from @userinfobot (send any message to it).\n");
+        let user_id: i64 = loop {
+            let input = prompt("Your Telegram user ID: ", "")?;
+            match input.parse() {
+                Ok(id) => break id,
+                Err(_) => {
+                    println!("  ❌ Must be a number");
+                    continue;
+                }
             }
-        }
-    };
+        };
+        allowed_users.push(user_id);
+    }
+    println!("  ✓ Allowed users: {:?}", allowed_users);
+    println!("  Add more later with `rescueclaw users add` or /pair in Telegram");
 
     println!();
 
     // Step 3: Backup Settings
     println!("Step 3/6: Backup Settings");
-    let backup_interval = loop {
-        let input = prompt("Backup interval [6h]: ", "6h")?;
-        if validate_interval(&input) {
-            break input;
+    let backup_interval: Duration = loop {
+        match prompt("Backup interval [6h]: ", "6h")?.parse() {
+            Ok(d) => break d,
+            Err(e) => println!("  ❌ {}", e),
         }
-        println!("  ❌ Invalid interval format. Use a number with suffix: 30s, 5m, 6h");
     };
     let max_snapshots: usize = prompt("Max snapshots to keep [10]: ", "10")?
         .parse()
@@ -274,17 +693,21 @@ pub async fn setup_wizard() -> Result<()> {
 
     // Step 4: Health Check Settings
     println!("Step 4/6: Health Check Settings");
-    let check_interval = loop {
-        let input = prompt("Health check interval [5m]: ", "5m")?;
-        if validate_interval(&input) {
-            break input;
+    let check_interval: Duration = loop {
+        match prompt("Health check interval [5m]: ", "5m")?.parse() {
+            Ok(d) => break d,
+            Err(e) => println!("  ❌ {}", e),
         }
-        println!("  ❌ Invalid interval format. Use a number with suffix: 30s, 5m, 6h");
     };
     let unhealthy_threshold: u32 = prompt("Failures before auto-restore [3]: ", "3")?
         .parse()
         .unwrap_or(3);
     let auto_restore = prompt_yn("Enable auto-restore? [y]: ", true)?;
+    let analyze_on_restore = auto_restore
+        && prompt_yn(
+            "Analyze incidents with your agent's model after auto-restore? [y]: ",
+            true,
+        )?;
 
     println!();
 
@@ -296,28 +719,46 @@ pub async fn setup_wizard() -> Result<()> {
             max_snapshots,
             path: backup_path,
             include_sessions,
+            mode: BackupMode::default(),
+            debounce: default_debounce(),
+            min_interval: default_min_interval(),
+            max_interval: None,
+            max_wait: default_max_wait(),
+            skip_unchanged: None,
+            schedules: vec![],
+            quiet_hours: vec![],
         },
         health: HealthConfig {
             check_interval,
             unhealthy_threshold,
             auto_restore,
-            auto_restore_cooldown: Some("1h".to_string()),
+            auto_restore_cooldown: Some(Duration::from_secs(3600)),
+            analyze_on_restore,
+            quiet_hours: vec![],
+            escalation: default_escalation(),
         },
         telegram: TelegramConfig {
             token,
-            allowed_users: vec![user_id],
+            allowed_users,
+            admins: vec![],
         },
         openclaw: oc_config,
+        redaction: RedactionConfig::default(),
+        validation: ValidationConfig::default(),
+        guard: GuardConfig::default(),
+        journal: JournalConfig::default(),
+        timezone: None,
     };
 
-    let config_dir = dirs::home_dir()
-        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-        .join(".config/rescueclaw");
-    std::fs::create_dir_all(&config_dir)?;
-
-    let config_file = config_dir.join("rescueclaw.json");
-    let json = serde_json::to_string_pretty(&config)?;
-    std::fs::write(&config_file, json)?;
+    let config_file = match target {
+        Some(path) => path.to_path_buf(),
+        None => setup::default_config_file()?,
+    };
+    if let Some(dir) = config_file.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let rendered = setup::render(&config_file, &serde_json::to_value(&config)?)?;
+    std::fs::write(&config_file, rendered)?;
 
     println!("  ✓ Config written to {}", config_file.display());
     println!();
@@ -333,11 +774,37 @@ pub async fn setup_wizard() -> Result<()> {
     }
 
     println!();
-    if prompt_yn("Install systemd service? [y]: ", true)? {
-        install_systemd_service(&config)?;
-    } else {
-        println!("  Skipped. Run 'sudo rescueclaw install' later to install the service.");
-    }
+    let manager = match opts
+        .service_manager
+        .map_or_else(crate::service::Manager::detect, Ok)
+    {
+        // The container's entrypoint runs the daemon
+        _ if config.openclaw.container_mode() && opts.service_manager.is_none() => None,
+        Ok(manager) => Some(manager),
+        Err(e) => {
+            println!("  ⚠ {}", e);
+            None
+        }
+    };
+    let installed = match manager {
+        Some(manager)
+            if prompt_yn(
+                &format!("Install the watchdog service ({:?})? [y]: ", manager),
+                true,
+            )? =>
+        {
+            crate::service::install(manager, &crate::service::ServiceSpec::new(&config, target))?;
+            true
+        }
+        _ if config.openclaw.container_mode() => {
+            println!("  Skipped in a container: make `rescueclaw start` its command.");
+            false
+        }
+        _ => {
+            println!("  Skipped. Run `rescueclaw service install` later to install the service.");
+            false
+        }
+    };
 
     println!();
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -354,7 +821,9 @@ pub async fn setup_wizard() -> Result<()> {
         );
     }
     println!();
-    println!("Start the daemon:  sudo systemctl start rescueclaw");
+    if !installed {
+        println!("Start the daemon:  rescueclaw service install (or rescueclaw start)");
+    }
     println!("View status:       rescueclaw status");
     println!("List backups:      rescueclaw list");
     println!();
@@ -362,20 +831,6 @@ pub async fn setup_wizard() -> Result<()> {
     Ok(())
 }
 
-/// Validate interval format (e.g., "30s", "5m", "6h")
-fn validate_interval(s: &str) -> bool {
-    let s = s.trim();
-    if let Some(n) = s
-        .strip_suffix('s')
-        .or_else(|| s.strip_suffix('m'))
-        .or_else(|| s.strip_suffix('h'))
-    {
-        n.parse::<u64>().is_ok()
-    } else {
-        false
-    }
-}
-
 /// Helper: prompt for input with default
 fn prompt(question: &str, default: &str) -> Result<String> {
     use std::io::{self, Write};
@@ -394,7 +849,7 @@ fn prompt(question: &str, default: &str) -> Result<String> {
 }
 
 /// Helper: prompt for yes/no
-fn prompt_yn(question: &str, default: bool) -> Result<bool> {
+pub(crate) fn prompt_yn(question: &str, default: bool) -> Result<bool> {
     let input = prompt(question, if default { "y" } else { "n" })?;
     Ok(matches!(input.to_lowercase().as_str(), "y" | "yes"))
 }
@@ -417,13 +872,6 @@ async fn validate_telegram_token(token: &str) -> Result<String> {
     Ok(bot_name)
 }
 
-/// Check if OpenClaw gateway is running
-async fn check_gateway_running() -> bool {
-    reqwest::get("http://127.0.0.1:7744/api/status")
-        .await
-        .is_ok()
-}
-
 /// Detect OpenClaw config directory
 fn detect_openclaw_config() -> Result<PathBuf> {
     let candidates = vec![
@@ -437,7 +885,9 @@ fn detect_openclaw_config() -> Result<PathBuf> {
         }
     }
 
-    anyhow::bail!("Could not find OpenClaw config directory (~/.openclaw or ~/.clawdbot)")
+    anyhow::bail!(
+        "Could not find OpenClaw config directory (~/.openclaw or ~/.clawdbot). Please specify it with `rescueclaw setup --config-path <dir>`"
+    )
 }
 
 /// Try to find the OpenClaw workspace
@@ -455,128 +905,11 @@ fn detect_openclaw_workspace() -> Result<PathBuf> {
         }
     }
 
-    anyhow::bail!("Could not auto-detect OpenClaw workspace. Please specify with --workspace")
-}
-
-/// Generate systemd service file content
-fn generate_service_file(_cfg: &Config) -> String {
-    let binary_path = std::env::current_exe()
-        .ok()
-        .and_then(|p| p.to_str().map(String::from))
-        .unwrap_or_else(|| "/usr/local/bin/rescueclaw".to_string());
-
-    // Detect current user — run as them, not root
-    let user = std::env::var("USER").unwrap_or_else(|_| "root".to_string());
-
-    format!(
-        r#"[Unit]
-Description=RescueClaw - AI Agent Watchdog
-After=network-online.target
-Wants=network-online.target
-
-[Service]
-Type=simple
-User={}
-ExecStart={} start
-Restart=on-failure
-RestartSec=15
-Environment=RUST_LOG=info
-Environment=HOME=/home/{}
-
-# Crash loop protection: max 5 restarts per 5 minutes
-StartLimitBurst=5
-StartLimitIntervalSec=300
-
-[Install]
-WantedBy=multi-user.target
-"#,
-        user, binary_path, user
+    anyhow::bail!(
+        "Could not auto-detect OpenClaw workspace. Please specify it with `rescueclaw setup --workspace <dir>`"
     )
 }
 
-/// Install systemd service
-pub fn install_systemd_service(cfg: &Config) -> Result<()> {
-    use std::io::Write;
-    use std::process::{Command, Stdio};
-
-    println!("  Installing systemd service...");
-
-    let service_content = generate_service_file(cfg);
-
-    // Write service file using sudo tee
-    let mut child = Command::new("sudo")
-        .args(["tee", "/etc/systemd/system/rescueclaw.service"])
-        .stdin(Stdio::piped())
-        .stdout(Stdio::null())
-        .spawn()?;
-
-    if let Some(mut stdin) = child.stdin.take() {
-        stdin.write_all(service_content.as_bytes())?;
-    }
-
-    let status = child.wait()?;
-    if !status.success() {
-        anyhow::bail!("Failed to write service file");
-    }
-
-    // Reload systemd
-    Command::new("sudo")
-        .args(["systemctl", "daemon-reload"])
-        .status()?;
-
-    // Enable service
-    Command::new("sudo")
-        .args(["systemctl", "enable", "rescueclaw"])
-        .status()?;
-
-    // Start service
-    let start_status = Command::new("sudo")
-        .args(["systemctl", "start", "rescueclaw"])
-        .status()?;
-
-    if start_status.success() {
-        println!("  ✓ Service installed and started");
-        println!("  View logs: sudo journalctl -u rescueclaw -f");
-    } else {
-        println!("  ⚠ Service installed but failed to start");
-        println!("  Check: sudo systemctl status rescueclaw");
-    }
-
-    Ok(())
-}
-
-/// Uninstall the watchdog service
-pub fn uninstall() -> Result<()> {
-    use std::process::Command;
-
-    println!("🛟 Uninstalling RescueClaw...");
-
-    // Stop service
-    let _ = Command::new("sudo")
-        .args(["systemctl", "stop", "rescueclaw"])
-        .status();
-
-    // Disable service
-    let _ = Command::new("sudo")
-        .args(["systemctl", "disable", "rescueclaw"])
-        .status();
-
-    // Remove service file
-    let _ = Command::new("sudo")
-        .args(["rm", "/etc/systemd/system/rescueclaw.service"])
-        .status();
-
-    // Reload systemd
-    let _ = Command::new("sudo")
-        .args(["systemctl", "daemon-reload"])
-        .status();
-
-    println!("  ✓ Service uninstalled");
-    println!("  Backups preserved at /var/rescueclaw/backups/");
-    println!("  Config preserved at ~/.config/rescueclaw/");
-    Ok(())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -584,9 +917,9 @@ mod tests {
     #[test]
     fn test_default_config_is_valid() {
         let cfg = Config::default();
-        assert_eq!(cfg.backup.interval, "6h");
+        assert_eq!(cfg.backup.interval.to_string(), "6h");
         assert_eq!(cfg.backup.max_snapshots, 10);
-        assert_eq!(cfg.health.check_interval, "5m");
+        assert_eq!(cfg.health.check_interval.to_string(), "5m");
         assert_eq!(cfg.health.unhealthy_threshold, 3);
     }
 
@@ -598,4 +931,15 @@ mod tests {
         assert_eq!(parsed.backup.interval, cfg.backup.interval);
         assert_eq!(parsed.backup.max_snapshots, cfg.backup.max_snapshots);
     }
+
+    #[test]
+    fn test_masked_hides_token_secret() {
+        let mut cfg = Config::default();
+        cfg.telegram.token = "123456:AAHsecretsecret".into();
+        cfg.openclaw.gateway.token = Some("gw-secret".into());
+        let shown = serde_json::to_string(&cfg.masked()).unwrap();
+        assert!(shown.contains("123456:********"));
+        assert!(!shown.contains("AAHsecretsecret"));
+        assert!(!shown.contains("gw-secret"));
+    }
 }
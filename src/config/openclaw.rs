@@ -0,0 +1,407 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Config file names, modern first
+const CONFIG_FILE_NAMES: &[&str] = &["openclaw.json", "clawdbot.json"];
+
+/// Where OpenClaw keeps auth profile secrets (relative to the config dir)
+const AUTH_STORE_PATH: &str = "agents/main/agent/auth-profiles.json";
+
+/// OpenClaw's own config (`openclaw.json`, or legacy `clawdbot.json`),
+/// normalized so callers don't care which layout is on disk.
+///
+/// Modern layout:
+/// `agents.defaults.model.primary`, `models.providers`, `auth.profiles`, `gateway`.
+/// Legacy layout: top-level `defaultModel` and `providers`.
+#[derive(Debug, Clone)]
+pub struct OpenClawFile {
+    pub raw: Value,
+    /// True when the file uses the legacy top-level `defaultModel`/`providers` shape
+    pub legacy: bool,
+    pub primary_model: Option<ModelRef>,
+    pub providers: BTreeMap<String, ProviderEntry>,
+    pub auth_profiles: BTreeMap<String, AuthProfile>,
+    pub gateway_port: Option<u64>,
+    /// `gateway.tls.enabled`
+    pub gateway_tls: bool,
+    /// CA the gateway's certificate chains to: `gateway.tls.caPath`, else
+    /// its (self-signed) `gateway.tls.certPath`
+    pub gateway_ca: Option<PathBuf>,
+    /// `gateway.auth.token`
+    pub gateway_token: Option<String>,
+    env: BTreeMap<String, String>,
+}
+
+/// A `provider/model` reference, e.g. `anthropic/claude-sonnet-4-5` or
+/// `openrouter/anthropic/claude-3.5-haiku` (only the first segment is the provider)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelRef {
+    pub provider: Option<String>,
+    pub model: String,
+}
+
+/// One entry under `models.providers` (or legacy `providers`)
+#[derive(Debug, Clone)]
+pub struct ProviderEntry {
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+    /// Wire protocol hint, e.g. `openai-completions`, `anthropic-messages`
+    pub api: Option<String>,
+    /// `None` when the provider has no `models` key at all
+    pub models: Option<Vec<String>>,
+}
+
+/// One entry under `auth.profiles` (e.g. `anthropic:default`), merged with
+/// its secret from the agent's auth store when present
+#[derive(Debug, Clone)]
+pub struct AuthProfile {
+    pub provider: String,
+    pub mode: Option<String>,
+    pub key: Option<String>,
+}
+
+impl ModelRef {
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if s.is_empty() {
+            return None;
+        }
+        Some(match s.split_once('/') {
+            Some((provider, model)) if !provider.is_empty() && !model.is_empty() => ModelRef {
+                provider: Some(provider.to_string()),
+                model: model.to_string(),
+            },
+            _ => ModelRef {
+                provider: None,
+                model: s.to_string(),
+            },
+        })
+    }
+}
+
+impl std::fmt::Display for ModelRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.provider {
+            Some(p) => write!(f, "{}/{}", p, self.model),
+            None => write!(f, "{}", self.model),
+        }
+    }
+}
+
+impl OpenClawFile {
+    /// Locate the config file: `path` may be the config dir or the file itself
+    pub fn find(path: &Path) -> Option<PathBuf> {
+        if path.is_file() {
+            return Some(path.to_path_buf());
+        }
+        CONFIG_FILE_NAMES
+            .iter()
+            .map(|name| path.join(name))
+            .find(|p| p.exists())
+    }
+
+    /// Load and normalize the config found at `path` (dir or file)
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = Self::find(path).ok_or_else(|| {
+            anyhow::anyhow!(
+                "OpenClaw config not found at {} or {}",
+                path.join(CONFIG_FILE_NAMES[0]).display(),
+                path.join(CONFIG_FILE_NAMES[1]).display()
+            )
+        })?;
+        let content = std::fs::read_to_string(&file)
+            .with_context(|| format!("reading {}", file.display()))?;
+        Self::parse(&file, &content).with_context(|| format!("parsing {}", file.display()))
+    }
+
+    /// Parse config file contents, picking up the auth store next to `file`
+    pub fn parse(file: &Path, content: &str) -> serde_json::Result<Self> {
+        let raw: Value = serde_json::from_str(content)?;
+        let auth_store = file
+            .parent()
+            .map(|dir| dir.join(AUTH_STORE_PATH))
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|c| serde_json::from_str::<Value>(&c).ok());
+        Ok(Self::from_value(raw, auth_store.as_ref()))
+    }
+
+    /// Normalize an already-parsed config. `auth_store` is the contents of
+    /// `auth-profiles.json`, if any.
+    pub fn from_value(raw: Value, auth_store: Option<&Value>) -> Self {
+        let modern =
+            raw.get("agents").is_some() || raw.get("models").is_some() || raw.get("auth").is_some();
+        let legacy =
+            !modern && (raw.get("defaultModel").is_some() || raw.get("providers").is_some());
+
+        let primary_model = [
+            "/agents/defaults/model/primary",
+            "/agents/defaults/model",
+            "/defaultModel",
+        ]
+        .iter()
+        .find_map(|p| raw.pointer(p).and_then(|v| v.as_str()))
+        .and_then(ModelRef::parse);
+
+        let providers = raw
+            .pointer("/models/providers")
+            .or_else(|| raw.get("providers"))
+            .and_then(|v| v.as_object())
+            .map(|obj| {
+                obj.iter()
+                    .map(|(name, p)| (name.clone(), ProviderEntry::from_value(p)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let stored = auth_store
+            .and_then(|s| s.get("profiles"))
+            .and_then(|v| v.as_object());
+        let mut auth_profiles: BTreeMap<String, AuthProfile> = raw
+            .pointer("/auth/profiles")
+            .and_then(|v| v.as_object())
+            .map(|obj| {
+                obj.iter()
+                    .map(|(id, p)| (id.clone(), AuthProfile::from_value(id, p)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        // Secrets live in the auth store; profiles may exist only there too
+        for (id, p) in stored.into_iter().flatten() {
+            let from_store = AuthProfile::from_value(id, p);
+            let profile = auth_profiles
+                .entry(id.clone())
+                .or_insert(from_store.clone());
+            if profile.key.is_none() {
+                profile.key = from_store.key;
+            }
+        }
+
+        let gateway_port = raw.pointer("/gateway/port").and_then(|v| v.as_u64());
+        let gateway_tls = raw
+            .pointer("/gateway/tls/enabled")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let gateway_ca = ["/gateway/tls/caPath", "/gateway/tls/certPath"]
+            .iter()
+            .find_map(|p| raw.pointer(p).and_then(|v| v.as_str()))
+            .map(PathBuf::from);
+        let gateway_token = raw
+            .pointer("/gateway/auth/token")
+            .and_then(|v| v.as_str())
+            .filter(|t| !t.is_empty())
+            .map(String::from);
+
+        let mut env = BTreeMap::new();
+        if let Some(obj) = raw.get("env").and_then(|v| v.as_object()) {
+            let vars = obj.get("vars").and_then(|v| v.as_object());
+            for (k, v) in obj.iter().chain(vars.into_iter().flatten()) {
+                if let Some(s) = v.as_str() {
+                    env.insert(k.clone(), s.to_string());
+                }
+            }
+        }
+
+        Self {
+            raw,
+            legacy,
+            primary_model,
+            providers,
+            auth_profiles,
+            gateway_port,
+            gateway_tls,
+            gateway_ca,
+            gateway_token,
+            env,
+        }
+    }
+
+    /// Every provider name the config mentions, via `providers` or auth profiles
+    pub fn provider_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .providers
+            .keys()
+            .cloned()
+            .chain(self.auth_profiles.values().map(|p| p.provider.clone()))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Token for the gateway's own API: `gateway.auth.token`, then
+    /// `OPENCLAW_GATEWAY_TOKEN` from `env` in the config or the process
+    pub fn gateway_token(&self) -> Option<String> {
+        const VAR: &str = "OPENCLAW_GATEWAY_TOKEN";
+        self.gateway_token
+            .clone()
+            .or_else(|| self.env.get(VAR).cloned())
+            .or_else(|| std::env::var(VAR).ok())
+            .filter(|t| !t.is_empty())
+    }
+
+    /// Resolve an API key for a provider: inline `apiKey`, then an auth
+    /// profile for that provider, then `env` in the config, then the
+    /// process environment (`<PROVIDER>_API_KEY`)
+    pub fn api_key_for(&self, provider: &str) -> Option<String> {
+        if let Some(key) = self.providers.get(provider).and_then(|p| p.api_key.clone()) {
+            return Some(key);
+        }
+
+        // Prefer the `<provider>:default` profile when there are several.
+        // OAuth profiles hold session tokens, not API keys, so skip them.
+        let default_id = format!("{}:default", provider);
+        let usable =
+            |p: &&AuthProfile| p.provider == provider && p.mode.as_deref() != Some("oauth");
+        let profile_key = self
+            .auth_profiles
+            .get(&default_id)
+            .filter(usable)
+            .and_then(|p| p.key.clone())
+            .or_else(|| {
+                self.auth_profiles
+                    .values()
+                    .filter(usable)
+                    .find_map(|p| p.key.clone())
+            });
+        if profile_key.is_some() {
+            return profile_key;
+        }
+
+        let var = format!("{}_API_KEY", provider.to_uppercase().replace('-', "_"));
+        self.env
+            .get(&var)
+            .cloned()
+            .or_else(|| std::env::var(&var).ok())
+            .filter(|k| !k.is_empty())
+    }
+}
+
+impl ProviderEntry {
+    fn from_value(v: &Value) -> Self {
+        let str_field = |name: &str| v.get(name).and_then(|x| x.as_str()).map(String::from);
+        Self {
+            base_url: str_field("baseUrl"),
+            api_key: str_field("apiKey"),
+            api: str_field("api"),
+            models: v.get("models").and_then(|m| m.as_array()).map(|arr| {
+                arr.iter()
+                    .filter_map(|m| m.get("id").and_then(|x| x.as_str()).or_else(|| m.as_str()))
+                    .map(String::from)
+                    .collect()
+            }),
+        }
+    }
+}
+
+impl AuthProfile {
+    fn from_value(id: &str, v: &Value) -> Self {
+        let str_field = |name: &str| v.get(name).and_then(|x| x.as_str()).map(String::from);
+        Self {
+            // Profile IDs are `<provider>:<name>`
+            provider: str_field("provider")
+                .unwrap_or_else(|| id.split(':').next().unwrap_or(id).to_string()),
+            mode: str_field("mode").or_else(|| str_field("type")),
+            key: str_field("key")
+                .or_else(|| str_field("apiKey"))
+                .filter(|k| !k.is_empty()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(raw: Value, store: Option<Value>) -> OpenClawFile {
+        OpenClawFile::from_value(raw, store.as_ref())
+    }
+
+    #[test]
+    fn test_model_ref_parse() {
+        assert_eq!(
+            ModelRef::parse("anthropic/claude-sonnet-4-5").unwrap(),
+            ModelRef {
+                provider: Some("anthropic".to_string()),
+                model: "claude-sonnet-4-5".to_string()
+            }
+        );
+        let nested = ModelRef::parse("openrouter/anthropic/claude-3.5-haiku").unwrap();
+        assert_eq!(nested.provider.as_deref(), Some("openrouter"));
+        assert_eq!(nested.model, "anthropic/claude-3.5-haiku");
+        assert_eq!(ModelRef::parse("gpt-4").unwrap().provider, None);
+        assert!(ModelRef::parse("  ").is_none());
+    }
+
+    #[test]
+    fn test_modern_layout() {
+        let file = parse(
+            json!({
+                "agents": { "defaults": { "model": { "primary": "anthropic/claude-sonnet-4-5" } } },
+                "models": { "providers": {
+                    "openrouter": { "baseUrl": "https://openrouter.ai/api/v1", "apiKey": "sk-or-1", "models": [{ "id": "x" }] }
+                } },
+                "auth": { "profiles": { "anthropic:default": { "provider": "anthropic", "mode": "api_key" } } },
+                "gateway": { "port": 18789 }
+            }),
+            Some(
+                json!({ "profiles": { "anthropic:default": { "type": "api_key", "provider": "anthropic", "key": "sk-ant-1" } } }),
+            ),
+        );
+
+        assert!(!file.legacy);
+        assert_eq!(
+            file.primary_model.as_ref().unwrap().to_string(),
+            "anthropic/claude-sonnet-4-5"
+        );
+        assert_eq!(file.gateway_port, Some(18789));
+        assert_eq!(file.api_key_for("openrouter").as_deref(), Some("sk-or-1"));
+        assert_eq!(file.api_key_for("anthropic").as_deref(), Some("sk-ant-1"));
+        assert_eq!(file.provider_names(), vec!["anthropic", "openrouter"]);
+
+        let oauth = parse(
+            json!({ "auth": { "profiles": { "anthropic:me": { "provider": "anthropic", "mode": "oauth", "key": "tok" } } } }),
+            None,
+        );
+        assert_ne!(oauth.api_key_for("anthropic").as_deref(), Some("tok"));
+        assert_eq!(
+            file.providers["openrouter"].models.as_deref(),
+            Some(&["x".to_string()][..])
+        );
+    }
+
+    #[test]
+    fn test_legacy_layout() {
+        let file = parse(
+            json!({
+                "defaultModel": "gpt-4",
+                "providers": { "openai": { "apiKey": "sk-test", "baseUrl": "https://api.openai.com/v1" } },
+                "gateway": { "port": 7744 }
+            }),
+            None,
+        );
+
+        assert!(file.legacy);
+        assert_eq!(file.primary_model.as_ref().unwrap().model, "gpt-4");
+        assert_eq!(file.api_key_for("openai").as_deref(), Some("sk-test"));
+        assert_eq!(file.gateway_port, Some(7744));
+    }
+
+    #[test]
+    fn test_model_as_plain_string_and_env_keys() {
+        let file = parse(
+            json!({
+                "agents": { "defaults": { "model": "openai/gpt-4o" } },
+                "env": { "vars": { "OPENAI_API_KEY": "sk-env" } }
+            }),
+            None,
+        );
+        assert_eq!(
+            file.primary_model.as_ref().unwrap().to_string(),
+            "openai/gpt-4o"
+        );
+        assert_eq!(file.api_key_for("openai").as_deref(), Some("sk-env"));
+    }
+}
@@ -0,0 +1,361 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::layers::{layered_values, merge, read_file, Origin};
+use super::{detect_openclaw_config, detect_openclaw_workspace, validate_telegram_token};
+use super::{Config, Duration};
+
+/// Answers for `rescueclaw setup`. Everything but the OpenClaw paths and
+/// the Telegram check needs `--non-interactive`; the wizard asks instead.
+#[derive(Debug, Default, clap::Args)]
+pub struct SetupOptions {
+    /// Never prompt: take answers from flags, --answers, the environment and
+    /// the existing config, and print a JSON summary
+    #[arg(long)]
+    pub non_interactive: bool,
+    /// Answers file: a partial config in .json, .toml or .yaml
+    #[arg(long, value_name = "FILE", requires = "non_interactive")]
+    pub answers: Option<PathBuf>,
+    /// OpenClaw workspace (auto-detected if omitted)
+    #[arg(long)]
+    pub workspace: Option<PathBuf>,
+    /// OpenClaw config directory (auto-detected if omitted)
+    #[arg(long)]
+    pub config_path: Option<PathBuf>,
+    /// Telegram bot token; $RESCUECLAW_TELEGRAM_TOKEN works too and is
+    /// not written to the config file
+    #[arg(long, requires = "non_interactive")]
+    pub token: Option<String>,
+    /// Telegram user ID allowed to control the bot (repeatable)
+    #[arg(long = "user", value_name = "ID", requires = "non_interactive")]
+    pub users: Vec<i64>,
+    /// Don't ask Telegram whether the bot token works
+    #[arg(long)]
+    pub skip_telegram_check: bool,
+    /// Init system for the watchdog service (detected if omitted;
+    /// systemd-user for a unit that needs no root)
+    #[arg(long, value_enum, value_name = "MANAGER")]
+    pub service_manager: Option<crate::service::Manager>,
+    #[arg(long, requires = "non_interactive")]
+    pub backup_path: Option<PathBuf>,
+    #[arg(long, requires = "non_interactive")]
+    pub backup_interval: Option<Duration>,
+    #[arg(long, requires = "non_interactive")]
+    pub max_snapshots: Option<usize>,
+    #[arg(long, num_args = 0..=1, default_missing_value = "true", requires = "non_interactive")]
+    pub include_sessions: Option<bool>,
+    #[arg(long, requires = "non_interactive")]
+    pub check_interval: Option<Duration>,
+    #[arg(long, requires = "non_interactive")]
+    pub unhealthy_threshold: Option<u32>,
+    #[arg(long, num_args = 0..=1, default_missing_value = "true", requires = "non_interactive")]
+    pub auto_restore: Option<bool>,
+    /// Don't take a first backup
+    #[arg(long, requires = "non_interactive")]
+    pub no_backup: bool,
+}
+
+impl SetupOptions {
+    /// The flags as a partial config
+    fn to_layer(&self) -> Value {
+        let mut layer = Value::Object(Default::default());
+        let mut put = |path: &str, value: Option<Value>| {
+            if let Some(value) = value {
+                set(&mut layer, path, value);
+            }
+        };
+        // Absolute, since the daemon won't run from this directory
+        let path = |p: &Option<PathBuf>| {
+            p.as_ref().map(|p| {
+                let p = std::path::absolute(p).unwrap_or_else(|_| p.clone());
+                Value::from(p.display().to_string())
+            })
+        };
+        let text = |d: &Option<Duration>| d.map(|d| Value::from(d.to_string()));
+
+        put("openclaw.workspace", path(&self.workspace));
+        put("openclaw.configPath", path(&self.config_path));
+        put("telegram.token", self.token.clone().map(Value::from));
+        put(
+            "telegram.allowedUsers",
+            (!self.users.is_empty()).then(|| Value::from(self.users.clone())),
+        );
+        put("backup.path", path(&self.backup_path));
+        put("backup.interval", text(&self.backup_interval));
+        put("backup.maxSnapshots", self.max_snapshots.map(Value::from));
+        put(
+            "backup.includeSessions",
+            self.include_sessions.map(Value::from),
+        );
+        put("health.checkInterval", text(&self.check_interval));
+        put(
+            "health.unhealthyThreshold",
+            self.unhealthy_threshold.map(Value::from),
+        );
+        put("health.autoRestore", self.auto_restore.map(Value::from));
+        layer
+    }
+}
+
+/// What a non-interactive setup did, printed as JSON
+#[derive(Debug, Serialize)]
+pub struct SetupSummary {
+    #[serde(rename = "configFile")]
+    pub config_file: PathBuf,
+    /// False when a rerun left the file as it was
+    #[serde(rename = "configChanged")]
+    pub config_changed: bool,
+    pub workspace: PathBuf,
+    #[serde(rename = "configPath")]
+    pub config_path: PathBuf,
+    /// Bot username, when the token was checked with Telegram
+    #[serde(rename = "telegramBot")]
+    pub telegram_bot: Option<String>,
+    /// Latest snapshot after setup
+    pub snapshot: Option<String>,
+    /// False when the latest snapshot already matched the files
+    #[serde(rename = "snapshotTaken")]
+    pub snapshot_taken: bool,
+    pub warnings: Vec<String>,
+}
+
+/// Where setup writes the config unless `--config` says otherwise
+pub fn default_config_file() -> Result<PathBuf> {
+    Ok(dirs::home_dir()
+        .context("Could not determine home directory")?
+        .join(".config/rescueclaw/rescueclaw.json"))
+}
+
+/// Setup without prompts. Answers layer as: existing config file, answers
+/// file, flags, then auto-detection for the OpenClaw paths. The result is
+/// checked with `RESCUECLAW_*` overrides applied, but those stay out of the
+/// file. Reruns with the same answers change nothing.
+pub async fn non_interactive(opts: &SetupOptions, target: Option<&Path>) -> Result<SetupSummary> {
+    let target = match target {
+        Some(path) => path.to_path_buf(),
+        None => default_config_file()?,
+    };
+    let env: Vec<(String, String)> = std::env::vars().collect();
+
+    let mut doc = if target.is_file() {
+        read_file(&target)?
+    } else {
+        Value::Null
+    };
+    if doc.is_null() {
+        doc = Value::Object(Default::default());
+    }
+    let mut scratch = BTreeMap::new();
+    if let Some(answers) = &opts.answers {
+        let layer = read_file(answers)?;
+        if !layer.is_null() {
+            merge(&mut doc, layer, "", &Origin::Default, &mut scratch);
+        }
+    }
+    merge(
+        &mut doc,
+        opts.to_layer(),
+        "",
+        &Origin::Default,
+        &mut scratch,
+    );
+
+    // Detect whatever neither the answers nor the environment set
+    let origin = Origin::File(target.clone());
+    let loaded = layered_values(vec![(doc.clone(), origin.clone())], env.clone())?;
+    if *loaded.origin("openclaw.workspace") == Origin::Default {
+        let workspace = detect_openclaw_workspace()?;
+        eprintln!("  ✓ Detected workspace {}", workspace.display());
+        set(
+            &mut doc,
+            "openclaw.workspace",
+            workspace.display().to_string().into(),
+        );
+    }
+    if *loaded.origin("openclaw.configPath") == Origin::Default {
+        if let Ok(path) = detect_openclaw_config() {
+            eprintln!("  ✓ Detected OpenClaw config {}", path.display());
+            set(
+                &mut doc,
+                "openclaw.configPath",
+                path.display().to_string().into(),
+            );
+        }
+    }
+
+    let loaded = layered_values(vec![(doc.clone(), origin)], env)?;
+    let problems = loaded.problems();
+    if Config::has_errors(&problems) {
+        let list: Vec<String> = problems
+            .iter()
+            .filter(|p| p.severity == crate::validate::Severity::Error)
+            .map(|p| format!("  {}", p))
+            .collect();
+        anyhow::bail!("Setup answers are invalid:\n{}", list.join("\n"));
+    }
+    let warnings = problems.iter().map(|p| p.to_string()).collect();
+    let cfg = loaded.config;
+
+    let telegram_bot = if cfg.telegram.token.is_empty() || opts.skip_telegram_check {
+        None
+    } else {
+        let bot = validate_telegram_token(&cfg.telegram.token).await.context(
+            "telegram.token: Telegram rejected the token (offline? use --skip-telegram-check)",
+        )?;
+        eprintln!("  ✓ Telegram bot @{}", bot);
+        Some(bot)
+    };
+
+    std::fs::create_dir_all(&cfg.backup.path)
+        .with_context(|| format!("creating backup.path {}", cfg.backup.path.display()))?;
+
+    let content = render(&target, &doc)?;
+    let config_changed = std::fs::read_to_string(&target).ok().as_deref() != Some(content.as_str());
+    if config_changed {
+        if let Some(dir) = target.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&target, &content)
+            .with_context(|| format!("writing {}", target.display()))?;
+        eprintln!("  ✓ Config written to {}", target.display());
+    } else {
+        eprintln!("  ✓ Config unchanged: {}", target.display());
+    }
+
+    let (snapshot, snapshot_taken) = if opts.no_backup {
+        (None, false)
+    } else {
+        first_backup(&cfg)?
+    };
+
+    Ok(SetupSummary {
+        config_file: target,
+        config_changed,
+        workspace: cfg.openclaw.workspace,
+        config_path: cfg.openclaw.config_path,
+        telegram_bot,
+        snapshot,
+        snapshot_taken,
+        warnings,
+    })
+}
+
+/// Snapshot unless the latest one already matches the files
+fn first_backup(cfg: &Config) -> Result<(Option<String>, bool)> {
+    if let Ok(latest) = crate::backup::find_snapshot(cfg, None) {
+        let current = crate::backup::content_hash(cfg, &BTreeMap::new());
+        if crate::backup::snapshot_content_hash(&latest).as_ref() == Some(&current) {
+            eprintln!("  ✓ Latest backup {} is up to date", latest.id);
+            return Ok((Some(latest.id), false));
+        }
+    }
+    let snap = crate::backup::take_snapshot(cfg)?;
+    eprintln!("  ✓ First backup {}", snap.id);
+    Ok((Some(snap.id), true))
+}
+
+/// Set a dotted path, creating objects along the way
+pub(super) fn set(doc: &mut Value, path: &str, value: Value) {
+    let mut node = doc;
+    for key in path.split('.') {
+        if !node.is_object() {
+            *node = Value::Object(Default::default());
+        }
+        node = node
+            .as_object_mut()
+            .expect("just made an object")
+            .entry(key)
+            .or_insert(Value::Null);
+    }
+    *node = value;
+}
+
+/// Serialize in the target's format (by extension)
+pub(super) fn render(target: &Path, doc: &Value) -> Result<String> {
+    let ext = target.extension().and_then(|e| e.to_str()).unwrap_or("");
+    Ok(match ext {
+        "json" => serde_json::to_string_pretty(doc)? + "\n",
+        "toml" => toml::to_string_pretty(&without_nulls(doc))?,
+        "yaml" | "yml" => serde_yaml::to_string(doc)?,
+        _ => anyhow::bail!(
+            "Unsupported config format '{}' (use .json, .toml, .yaml or .yml)",
+            target.display()
+        ),
+    })
+}
+
+/// TOML has no null; an unset optional is simply left out
+fn without_nulls(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k.clone(), without_nulls(v)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(without_nulls).collect()),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_non_interactive_setup_is_idempotent() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let workspace = dir.path().join("workspace");
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::fs::write(workspace.join("SOUL.md"), "# Soul").unwrap();
+        let answers = dir.path().join("answers.yaml");
+        std::fs::write(&answers, "backup:\n  maxSnapshots: 3\n").unwrap();
+        let target = dir.path().join("etc/rescueclaw.toml");
+
+        let opts = SetupOptions {
+            non_interactive: true,
+            answers: Some(answers),
+            workspace: Some(workspace.clone()),
+            config_path: Some(dir.path().to_path_buf()),
+            users: vec![42],
+            backup_path: Some(dir.path().join("backups")),
+            backup_interval: Some("12h".parse().unwrap()),
+            ..Default::default()
+        };
+
+        let first = non_interactive(&opts, Some(&target)).await.unwrap();
+        assert!(first.config_changed);
+        assert!(first.snapshot_taken);
+        assert_eq!(first.workspace, workspace);
+
+        let written = std::fs::read_to_string(&target).unwrap();
+        assert!(written.contains("interval = \"12h\""), "{}", written);
+        assert!(written.contains("maxSnapshots = 3"));
+        assert!(written.contains("allowedUsers = [42]"));
+        // Only what was answered; defaults stay implicit
+        assert!(!written.contains("checkInterval"));
+
+        let second = non_interactive(&opts, Some(&target)).await.unwrap();
+        assert!(!second.config_changed);
+        assert!(!second.snapshot_taken);
+        assert_eq!(second.snapshot, first.snapshot);
+    }
+
+    #[test]
+    fn test_full_config_renders_loadable_in_every_format() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut cfg = Config::default();
+        cfg.telegram.allowed_users = vec![42];
+        let doc = serde_json::to_value(&cfg).unwrap();
+        for name in ["rescueclaw.json", "rescueclaw.toml", "rescueclaw.yml"] {
+            let file = dir.path().join(name);
+            std::fs::write(&file, render(&file, &doc).unwrap()).unwrap();
+            let loaded = Config::load_from(Some(&file)).unwrap().config;
+            assert_eq!(loaded.telegram.allowed_users, vec![42], "{}", name);
+        }
+        assert!(render(&dir.path().join("rescueclaw.ini"), &doc).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use super::layers::{read_file, LoadedConfig, Origin};
+use super::setup::{default_config_file, render, set};
+use super::{prompt_yn, Config};
+
+/// The file `telegram.allowedUsers` is kept in: the one that set it, else
+/// the highest-precedence config file, else the user config file
+fn users_file(loaded: &LoadedConfig, explicit: Option<&Path>) -> Result<PathBuf> {
+    match loaded.origin("telegram.allowedUsers") {
+        Origin::Env(var) => {
+            anyhow::bail!("telegram.allowedUsers is set by ${} — change it there", var)
+        }
+        Origin::File(path) => Ok(path.clone()),
+        Origin::Default => match explicit.or(loaded.files.last().map(PathBuf::as_path)) {
+            Some(path) => Ok(path.to_path_buf()),
+            None => default_config_file(),
+        },
+    }
+}
+
+/// Allowed users after the change, and the file written (`None` if unchanged)
+pub struct UsersUpdate {
+    pub users: Vec<i64>,
+    pub file: Option<PathBuf>,
+}
+
+/// Add users to `telegram.allowedUsers` in the config file
+pub fn add_users(explicit: Option<&Path>, ids: &[i64]) -> Result<UsersUpdate> {
+    update(explicit, |users, _| {
+        for id in ids {
+            if !users.contains(id) {
+                users.push(*id);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Remove users (and their admin rights); refuses to empty the list, since
+/// an empty list lets anyone control the bot. Removing the last admin makes
+/// every remaining user an admin, so that takes `force`.
+pub fn remove_users(explicit: Option<&Path>, ids: &[i64], force: bool) -> Result<UsersUpdate> {
+    update(explicit, |users, admins| {
+        users.retain(|u| !ids.contains(u));
+        let had_admins = !admins.is_empty();
+        admins.retain(|u| !ids.contains(u));
+        if users.is_empty() {
+            anyhow::bail!(
+                "Refusing to remove the last user: an empty list lets anyone control the bot"
+            );
+        }
+        if had_admins && admins.is_empty() && !force {
+            anyhow::bail!(
+                "Refusing to remove the last admin: with no admins every allowed user is one \
+                 (--force to do it anyway)"
+            );
+        }
+        Ok(())
+    })
+}
+
+fn update(
+    explicit: Option<&Path>,
+    change: impl FnOnce(&mut Vec<i64>, &mut Vec<i64>) -> Result<()>,
+) -> Result<UsersUpdate> {
+    let loaded = Config::load_from(explicit)?;
+    let file = users_file(&loaded, explicit)?;
+    let telegram = &loaded.config.telegram;
+    let (mut users, mut admins) = (telegram.allowed_users.clone(), telegram.admins.clone());
+    change(&mut users, &mut admins)?;
+    if users == telegram.allowed_users && admins == telegram.admins {
+        return Ok(UsersUpdate { users, file: None });
+    }
+
+    let mut doc = if file.is_file() {
+        read_file(&file)?
+    } else {
+        Value::Null
+    };
+    set(&mut doc, "telegram.allowedUsers", users.clone().into());
+    if admins != telegram.admins {
+        set(&mut doc, "telegram.admins", admins.into());
+    }
+    if let Some(dir) = file.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&file, render(&file, &doc)?)?;
+    Ok(UsersUpdate {
+        users,
+        file: Some(file),
+    })
+}
+
+/// Ask the user to message the bot, then confirm each sender. Empty when
+/// nobody wrote in time or discovery isn't possible.
+pub async fn discover(token: &str, wait: std::time::Duration) -> Vec<i64> {
+    println!(
+        "  Open your bot in Telegram and send it /start (waiting up to {}s)...",
+        wait.as_secs()
+    );
+    let senders = match crate::telegram::poll_senders(token, wait).await {
+        Ok(senders) => senders,
+        Err(e) => {
+            println!("  ⚠ Could not read the bot's messages: {:#}", e);
+            return vec![];
+        }
+    };
+    if senders.is_empty() {
+        println!("  ⚠ Nobody messaged the bot");
+    }
+    let mut confirmed = Vec::new();
+    for sender in senders {
+        match prompt_yn(&format!("Allow {}? [y]: ", sender), true) {
+            Ok(true) => confirmed.push(sender.id),
+            Ok(false) => {}
+            Err(e) => println!("  ⚠ {}", e),
+        }
+    }
+    confirmed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_and_remove_users_in_place() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("rescueclaw.yaml");
+        std::fs::write(
+            &file,
+            "telegram:\n  allowedUsers: [1]\n  admins: [1]\nbackup:\n  maxSnapshots: 4\n",
+        )
+        .unwrap();
+
+        let added = add_users(Some(&file), &[2, 3, 1]).unwrap();
+        assert_eq!(added.users, vec![1, 2, 3]);
+        assert_eq!(added.file.as_deref(), Some(file.as_path()));
+        assert!(add_users(Some(&file), &[2]).unwrap().file.is_none());
+
+        // User 1 is the only admin
+        let err = remove_users(Some(&file), &[1, 3], false).err().unwrap();
+        assert!(err.to_string().contains("last admin"), "{}", err);
+        let cfg = Config::load_from(Some(&file)).unwrap().config;
+        assert_eq!(cfg.telegram.admins, vec![1]);
+
+        let removed = remove_users(Some(&file), &[3], false).unwrap();
+        assert_eq!(removed.users, vec![1, 2]);
+        let removed = remove_users(Some(&file), &[1], true).unwrap();
+        assert_eq!(removed.users, vec![2]);
+        let cfg = Config::load_from(Some(&file)).unwrap().config;
+        assert_eq!(cfg.telegram.allowed_users, vec![2]);
+        assert!(cfg.telegram.admins.is_empty());
+        assert_eq!(cfg.backup.max_snapshots, 4);
+
+        assert!(remove_users(Some(&file), &[2], true).is_err());
+    }
+}
@@ -0,0 +1,286 @@
+use anyhow::{Context, Result};
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::GatewayController;
+
+/// How long a single Engine API call may take
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A container driven through the Docker Engine API on a Unix socket, so
+/// neither the docker CLI nor a shared PID namespace is needed
+pub struct Docker {
+    pub container: String,
+    pub socket: PathBuf,
+    /// How long `stop` gives the gateway before the daemon kills it
+    pub grace: Duration,
+}
+
+/// Status and body of an Engine API response
+struct Response {
+    status: u16,
+    body: Vec<u8>,
+}
+
+impl Response {
+    /// The API's `{"message": ...}` error, or the raw body
+    fn message(&self) -> String {
+        serde_json::from_slice::<serde_json::Value>(&self.body)
+            .ok()
+            .and_then(|v| v["message"].as_str().map(str::to_string))
+            .unwrap_or_else(|| String::from_utf8_lossy(&self.body).trim().to_string())
+    }
+}
+
+impl Docker {
+    fn request(&self, method: &str, path: &str) -> Result<Response> {
+        let mut stream = UnixStream::connect(&self.socket)
+            .with_context(|| format!("Could not connect to {}", self.socket.display()))?;
+        // A stop answers once the container is down, up to the grace period
+        stream.set_read_timeout(Some(TIMEOUT + self.grace))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        // HTTP/1.0: the daemon closes the connection after the response
+        write!(
+            stream,
+            "{} {} HTTP/1.0\r\nHost: docker\r\nContent-Length: 0\r\n\r\n",
+            method, path
+        )?;
+        let mut raw = Vec::new();
+        stream.read_to_end(&mut raw)?;
+        parse_response(&raw).with_context(|| format!("Bad response to {} {}", method, path))
+    }
+
+    fn call(&self, method: &str, action: &str, ok: &[u16]) -> Result<()> {
+        let path = format!("/containers/{}/{}", self.container, action);
+        let resp = self.request(method, &path)?;
+        if !ok.contains(&resp.status) {
+            anyhow::bail!(
+                "Docker API {} {}: {} {}",
+                method,
+                path,
+                resp.status,
+                resp.message()
+            );
+        }
+        Ok(())
+    }
+
+    /// `State.Pid` of the container if it's running
+    fn running_pid(&self) -> Result<Option<u32>> {
+        let path = format!("/containers/{}/json", self.container);
+        let resp = self.request("GET", &path)?;
+        if resp.status != 200 {
+            anyhow::bail!(
+                "Docker API GET {}: {} {}",
+                path,
+                resp.status,
+                resp.message()
+            );
+        }
+        let info: serde_json::Value = serde_json::from_slice(&resp.body)?;
+        let state = &info["State"];
+        Ok(state["Pid"]
+            .as_u64()
+            .and_then(|pid| u32::try_from(pid).ok())
+            .filter(|pid| *pid != 0 && state["Running"].as_bool() == Some(true)))
+    }
+}
+
+impl GatewayController for Docker {
+    fn describe(&self) -> String {
+        format!("container {}", self.container)
+    }
+
+    fn pid(&self) -> Option<u32> {
+        self.running_pid()
+            .inspect_err(|e| tracing::warn!("{}", e))
+            .ok()
+            .flatten()
+    }
+
+    /// A stop rather than a signal: the daemon records it as manual, so a
+    /// `restart: always` policy doesn't bring the gateway back mid-restore
+    fn stop(&self, _pid: u32) -> Result<()> {
+        // 304: it stopped on its own meanwhile
+        let action = format!("stop?t={}", self.grace.as_secs());
+        self.call("POST", &action, &[204, 304])
+    }
+
+    fn kill(&self, _pid: u32) -> Result<()> {
+        self.call("POST", "kill", &[204, 409])
+    }
+
+    fn start(&self) -> Result<()> {
+        // 304: already running
+        self.call("POST", "start", &[204, 304])
+    }
+
+    /// The container's PID is in the host's namespace, not ours, so ask the
+    /// daemon rather than /proc
+    fn alive(&self, pid: u32) -> bool {
+        match self.running_pid() {
+            Ok(running) => running == Some(pid),
+            // Can't tell: assume it's still up so a stop falls through to kill
+            Err(_) => true,
+        }
+    }
+}
+
+fn parse_response(raw: &[u8]) -> Result<Response> {
+    let split = raw
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .context("no end of headers")?;
+    let head = String::from_utf8_lossy(&raw[..split]);
+    let mut lines = head.lines();
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .context("no status line")?;
+    let chunked = lines.any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("transfer-encoding")
+                && value.trim().eq_ignore_ascii_case("chunked")
+        })
+    });
+    let body = &raw[split + 4..];
+    let body = if chunked {
+        dechunk(body)?
+    } else {
+        body.to_vec()
+    };
+    Ok(Response { status, body })
+}
+
+fn dechunk(mut body: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    loop {
+        let eol = body
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .context("truncated chunk")?;
+        let size = std::str::from_utf8(&body[..eol])?;
+        let size = usize::from_str_radix(size.split(';').next().unwrap_or("").trim(), 16)?;
+        if size == 0 {
+            return Ok(out);
+        }
+        let data = body
+            .get(eol + 2..eol + 2 + size)
+            .context("truncated chunk")?;
+        out.extend_from_slice(data);
+        body = body.get(eol + 4 + size..).unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::os::unix::net::UnixListener;
+    use std::sync::{Arc, Mutex};
+
+    /// A Docker daemon with one container named `claw`, serving `requests`
+    /// connections and recording their request lines
+    fn mock_daemon(
+        socket: &std::path::Path,
+        requests: usize,
+    ) -> (Arc<Mutex<Vec<String>>>, std::thread::JoinHandle<()>) {
+        let listener = UnixListener::bind(socket).unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        let handle = std::thread::spawn(move || {
+            let mut running = true;
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                // Drain the headers
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+                let line = line.trim().to_string();
+                log.lock().unwrap().push(line.clone());
+
+                let (status, body) = match line.split_whitespace().nth(1).unwrap() {
+                    "/containers/claw/json" => (
+                        "200 OK",
+                        format!(
+                            r#"{{"State":{{"Running":{},"Pid":{}}}}}"#,
+                            running,
+                            if running { 4242 } else { 0 }
+                        ),
+                    ),
+                    "/containers/claw/stop?t=10" | "/containers/claw/kill" => {
+                        running = false;
+                        ("204 No Content", String::new())
+                    }
+                    "/containers/claw/start" => {
+                        running = true;
+                        ("204 No Content", String::new())
+                    }
+                    _ => (
+                        "404 Not Found",
+                        r#"{"message":"No such container: ghost"}"#.to_string(),
+                    ),
+                };
+                // Chunked, as the daemon sends JSON
+                let reply = if body.is_empty() {
+                    format!("HTTP/1.1 {}\r\n\r\n", status)
+                } else {
+                    format!(
+                        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{}\r\n0\r\n\r\n",
+                        status,
+                        body.len(),
+                        body
+                    )
+                };
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+        });
+        (seen, handle)
+    }
+
+    #[test]
+    fn test_engine_api_over_unix_socket() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let socket = dir.path().join("docker.sock");
+        let (seen, daemon) = mock_daemon(&socket, 6);
+
+        let docker = Docker {
+            container: "claw".into(),
+            socket: socket.clone(),
+            grace: Duration::from_secs(10),
+        };
+        assert_eq!(docker.pid(), Some(4242));
+        docker.stop(4242).unwrap();
+        assert!(!docker.alive(4242));
+        docker.start().unwrap();
+        assert!(docker.alive(4242));
+
+        let ghost = Docker {
+            container: "ghost".into(),
+            socket,
+            grace: Duration::from_secs(10),
+        };
+        let err = ghost.start().unwrap_err().to_string();
+        assert!(err.contains("404 No such container: ghost"), "{}", err);
+
+        daemon.join().unwrap();
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                "GET /containers/claw/json HTTP/1.0",
+                "POST /containers/claw/stop?t=10 HTTP/1.0",
+                "GET /containers/claw/json HTTP/1.0",
+                "POST /containers/claw/start HTTP/1.0",
+                "GET /containers/claw/json HTTP/1.0",
+                "POST /containers/ghost/start HTTP/1.0",
+            ]
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::config::{Config, GatewayDriver, GatewayScheme};
+
+/// The gateway's status endpoint and how to call it, from
+/// `openclaw.gateway` with gaps filled in from openclaw.json
+#[derive(Debug, Clone)]
+pub struct Endpoint {
+    pub url: String,
+    /// Header name and value
+    auth: Option<(String, String)>,
+    ca_cert: Option<PathBuf>,
+    insecure: bool,
+}
+
+pub fn endpoint(cfg: &Config) -> Endpoint {
+    let gateway = &cfg.openclaw.gateway;
+    let oc = cfg.read_openclaw_config().ok();
+
+    let scheme = gateway.scheme.unwrap_or(match &oc {
+        Some(oc) if oc.gateway_tls => GatewayScheme::Https,
+        _ => GatewayScheme::Http,
+    });
+    let host = host(cfg);
+    let host = if host.contains(':') && !host.starts_with('[') {
+        format!("[{}]", host)
+    } else {
+        host
+    };
+    let path = gateway.status_path.trim_start_matches('/');
+    let url = format!(
+        "{}://{}:{}/{}",
+        match scheme {
+            GatewayScheme::Http => "http",
+            GatewayScheme::Https => "https",
+        },
+        host,
+        super::port(cfg),
+        path
+    );
+
+    let token = gateway
+        .token
+        .clone()
+        .or_else(|| oc.as_ref().and_then(|oc| oc.gateway_token()));
+    let auth = token.map(|token| {
+        let header = gateway.auth_header.clone();
+        if header.eq_ignore_ascii_case("authorization") {
+            (header, format!("Bearer {}", token))
+        } else {
+            (header, token)
+        }
+    });
+
+    let ca_cert = match scheme {
+        GatewayScheme::Https => gateway
+            .tls
+            .ca_cert
+            .clone()
+            .or_else(|| oc.and_then(|oc| oc.gateway_ca)),
+        GatewayScheme::Http => None,
+    };
+
+    Endpoint {
+        url,
+        auth,
+        ca_cert,
+        insecure: gateway.tls.insecure,
+    }
+}
+
+/// Host the gateway listens on; in container mode a dockerized gateway is
+/// reached by its container name
+fn host(cfg: &Config) -> String {
+    let gateway = &cfg.openclaw.gateway;
+    match &gateway.host {
+        Some(host) => host.clone(),
+        None if gateway.driver == GatewayDriver::Docker && cfg.openclaw.container_mode() => {
+            gateway.container.clone()
+        }
+        None => "127.0.0.1".to_string(),
+    }
+}
+
+impl Endpoint {
+    /// An HTTP client trusting the gateway's certificate
+    pub fn client(&self, timeout: Duration) -> Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder().timeout(timeout);
+        if self.insecure {
+            builder = builder.danger_accept_invalid_certs(true);
+        } else if let Some(path) = &self.ca_cert {
+            // openclaw.json's path may only exist in the agent's container
+            match std::fs::read(path) {
+                Ok(pem) => {
+                    let cert = reqwest::Certificate::from_pem(&pem)
+                        .with_context(|| format!("Bad certificate in {}", path.display()))?;
+                    builder = builder.add_root_certificate(cert);
+                }
+                Err(e) => tracing::warn!("Could not read {}: {}", path.display(), e),
+            }
+        }
+        Ok(builder.build()?)
+    }
+
+    /// Whether the gateway answers. Any response short of a server error
+    /// counts, so a gateway that rejects the token is still up.
+    pub async fn alive(&self, client: &reqwest::Client) -> bool {
+        let mut request = client.get(&self.url);
+        if let Some((name, value)) = &self.auth {
+            request = request.header(name, value);
+        }
+        match request.send().await {
+            Ok(resp) if resp.status().is_server_error() => false,
+            Ok(resp) => {
+                if matches!(resp.status().as_u16(), 401 | 403) {
+                    tracing::warn!(
+                        "{} answered {}: check openclaw.gateway.token",
+                        self.url,
+                        resp.status()
+                    );
+                }
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// `alive` with a one-off client
+    pub async fn probe(&self, timeout: Duration) -> bool {
+        match self.client(timeout) {
+            Ok(client) => self.alive(&client).await,
+            Err(e) => {
+                tracing::warn!("{:#}", e);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_endpoint_from_config_and_openclaw_json() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut cfg = Config::default();
+        cfg.openclaw.config_path = dir.path().to_path_buf();
+        cfg.openclaw.container_mode = Some(false);
+        let ep = endpoint(&cfg);
+        assert_eq!(ep.url, "http://127.0.0.1:7744/api/status");
+        assert!(ep.auth.is_none());
+
+        std::fs::write(
+            dir.path().join("openclaw.json"),
+            r#"{"gateway": {"port": 8080, "auth": {"token": "t0k"},
+                "tls": {"enabled": true, "certPath": "/certs/gw.pem"}}}"#,
+        )
+        .unwrap();
+        let ep = endpoint(&cfg);
+        assert_eq!(ep.url, "https://127.0.0.1:8080/api/status");
+        assert_eq!(
+            ep.auth,
+            Some(("Authorization".to_string(), "Bearer t0k".to_string()))
+        );
+        assert_eq!(ep.ca_cert, Some(PathBuf::from("/certs/gw.pem")));
+
+        // Container mode reaches a dockerized gateway by name
+        cfg.openclaw.container_mode = Some(true);
+        cfg.openclaw.gateway.driver = GatewayDriver::Docker;
+        cfg.openclaw.gateway.container = "claw".into();
+        assert_eq!(endpoint(&cfg).url, "https://claw:8080/api/status");
+
+        let gateway = &mut cfg.openclaw.gateway;
+        gateway.host = Some("::1".into());
+        gateway.port = Some(9000);
+        gateway.scheme = Some(GatewayScheme::Http);
+        gateway.status_path = "healthz".into();
+        gateway.auth_header = "X-Gateway-Token".into();
+        gateway.token = Some("mine".into());
+        let ep = endpoint(&cfg);
+        assert_eq!(ep.url, "http://[::1]:9000/healthz");
+        assert_eq!(
+            ep.auth,
+            Some(("X-Gateway-Token".to_string(), "mine".to_string()))
+        );
+        assert_eq!(ep.ca_cert, None);
+    }
+
+    /// Serve one request: 204 with the right token, else `status`
+    async fn serve_once(listener: tokio::net::TcpListener, status: &'static str) {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = vec![0; 4096];
+        let n = stream.read(&mut buf).await.unwrap();
+        let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+        let status = if request.contains("authorization: bearer s3cret") {
+            "204 No Content"
+        } else {
+            status
+        };
+        let reply = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status);
+        stream.write_all(reply.as_bytes()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_alive_sends_the_token_and_rejects_server_errors() {
+        let timeout = Duration::from_secs(5);
+        for (token, status, alive) in [
+            (Some("s3cret"), "500 Internal Server Error", true),
+            (None, "401 Unauthorized", true),
+            (None, "502 Bad Gateway", false),
+        ] {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let ep = Endpoint {
+                url: format!("http://{}/api/status", listener.local_addr().unwrap()),
+                auth: token.map(|t| ("Authorization".into(), format!("Bearer {}", t))),
+                ca_cert: None,
+                insecure: false,
+            };
+            let server = tokio::spawn(serve_once(listener, status));
+            assert_eq!(ep.probe(timeout).await, alive, "{}", status);
+            server.await.unwrap();
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api/status", listener.local_addr().unwrap());
+        drop(listener);
+        let down = Endpoint {
+            url,
+            auth: None,
+            ca_cert: None,
+            insecure: false,
+        };
+        assert!(!down.probe(timeout).await);
+    }
+}
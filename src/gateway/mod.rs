@@ -0,0 +1,408 @@
+use anyhow::{Context, Result};
+use std::process::Command;
+use std::time::Duration;
+
+use crate::config::{Config, GatewayDriver};
+
+mod docker;
+mod endpoint;
+pub mod procfs;
+
+use docker::Docker;
+pub use endpoint::{endpoint, Endpoint};
+
+/// Default OpenClaw gateway port
+pub const DEFAULT_PORT: u16 = 7744;
+
+/// How long to wait for the process to go after SIGKILL
+const KILL_WAIT: Duration = Duration::from_secs(5);
+
+/// Stops and starts the OpenClaw gateway, whatever runs it
+pub trait GatewayController: Send + Sync {
+    /// What's being controlled, for messages ("systemd unit openclaw-gateway")
+    fn describe(&self) -> String;
+    /// PID of the running gateway, if it's running
+    fn pid(&self) -> Option<u32>;
+    /// Ask the gateway to exit
+    fn stop(&self, pid: u32) -> Result<()>;
+    /// Force it down once the grace period is over
+    fn kill(&self, pid: u32) -> Result<()>;
+    fn start(&self) -> Result<()>;
+    fn alive(&self, pid: u32) -> bool {
+        procfs::alive(pid)
+    }
+}
+
+/// How a stop went
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stopped {
+    NotRunning,
+    Graceful(u32),
+    /// Still running after the grace period, so killed
+    Killed(u32),
+}
+
+/// Gateway port: `openclaw.gateway.port`, else the one in the OpenClaw config file
+pub fn port(cfg: &Config) -> u16 {
+    if let Some(port) = cfg.openclaw.gateway.port {
+        return port;
+    }
+    cfg.read_openclaw_config()
+        .ok()
+        .and_then(|oc| oc.gateway_port)
+        .and_then(|p| u16::try_from(p).ok())
+        .unwrap_or(DEFAULT_PORT)
+}
+
+/// The controller for `openclaw.gateway.driver`
+pub fn controller(cfg: &Config) -> Box<dyn GatewayController> {
+    let gateway = &cfg.openclaw.gateway;
+    let port = port(cfg);
+    match gateway.driver {
+        GatewayDriver::Cli => Box::new(Cli {
+            port,
+            program: gateway.cli.clone().unwrap_or_else(default_cli),
+        }),
+        GatewayDriver::Systemd => Box::new(Systemd {
+            unit: gateway.unit.clone(),
+            user: gateway.user_unit,
+        }),
+        GatewayDriver::Docker => Box::new(Docker {
+            container: gateway.container.clone(),
+            socket: gateway.docker_socket.clone(),
+            grace: gateway.grace_period.as_std(),
+        }),
+        GatewayDriver::Custom => Box::new(Custom {
+            port,
+            start: gateway.start_command.clone().unwrap_or_default(),
+            stop: gateway.stop_command.clone(),
+            kill: gateway.kill_command.clone(),
+        }),
+    }
+}
+
+/// Stop the gateway, killing it if it outlives `grace`
+pub async fn stop_gracefully(ctl: &dyn GatewayController, grace: Duration) -> Result<Stopped> {
+    let Some(pid) = ctl.pid() else {
+        return Ok(Stopped::NotRunning);
+    };
+    ctl.stop(pid)
+        .with_context(|| format!("Failed to stop {}", ctl.describe()))?;
+    if exited(ctl, pid, grace).await {
+        return Ok(Stopped::Graceful(pid));
+    }
+
+    tracing::warn!(
+        "{} (PID {}) still running after {:?}, killing it",
+        ctl.describe(),
+        pid,
+        grace
+    );
+    ctl.kill(pid)
+        .with_context(|| format!("Failed to kill {}", ctl.describe()))?;
+    if !exited(ctl, pid, KILL_WAIT).await {
+        anyhow::bail!("{} (PID {}) survived being killed", ctl.describe(), pid);
+    }
+    Ok(Stopped::Killed(pid))
+}
+
+/// Wait up to `timeout` for `pid` to exit
+async fn exited(ctl: &dyn GatewayController, pid: u32, timeout: Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if !ctl.alive(pid) {
+            return true;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// `openclaw` if it's installed, else the legacy `clawdbot`
+fn default_cli() -> String {
+    let on_path = |name: &str| {
+        std::env::var_os("PATH")
+            .is_some_and(|path| std::env::split_paths(&path).any(|d| d.join(name).is_file()))
+    };
+    if !on_path("openclaw") && on_path("clawdbot") {
+        "clawdbot".to_string()
+    } else {
+        "openclaw".to_string()
+    }
+}
+
+/// Run a command to completion; its stderr becomes the error
+fn run(cmd: &mut Command) -> Result<String> {
+    let program = cmd.get_program().to_string_lossy().into_owned();
+    let output = cmd
+        .output()
+        .with_context(|| format!("Could not run {}", program))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} failed ({}): {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command]);
+    cmd
+}
+
+/// Started with the OpenClaw CLI, found by its port, stopped with signals
+struct Cli {
+    port: u16,
+    program: String,
+}
+
+impl GatewayController for Cli {
+    fn describe(&self) -> String {
+        format!("gateway on port {}", self.port)
+    }
+
+    fn pid(&self) -> Option<u32> {
+        procfs::listening_pid(self.port)
+    }
+
+    fn stop(&self, pid: u32) -> Result<()> {
+        procfs::signal(pid, libc::SIGTERM)
+    }
+
+    fn kill(&self, pid: u32) -> Result<()> {
+        procfs::signal(pid, libc::SIGKILL)
+    }
+
+    fn start(&self) -> Result<()> {
+        run(Command::new(&self.program).args(["gateway", "start"]))?;
+        Ok(())
+    }
+}
+
+struct Systemd {
+    unit: String,
+    user: bool,
+}
+
+impl Systemd {
+    fn systemctl(&self) -> Command {
+        let mut cmd = Command::new("systemctl");
+        if self.user {
+            cmd.arg("--user");
+        }
+        cmd
+    }
+}
+
+impl GatewayController for Systemd {
+    fn describe(&self) -> String {
+        format!(
+            "systemd {}unit {}",
+            if self.user { "user " } else { "" },
+            self.unit
+        )
+    }
+
+    fn pid(&self) -> Option<u32> {
+        let out = run(self
+            .systemctl()
+            .args(["show", "--property=MainPID", "--value", &self.unit]))
+        .ok()?;
+        out.parse().ok().filter(|pid| *pid != 0)
+    }
+
+    fn stop(&self, _pid: u32) -> Result<()> {
+        // --no-block: the grace period is ours, not the unit's TimeoutStopSec
+        run(self.systemctl().args(["stop", "--no-block", &self.unit]))?;
+        Ok(())
+    }
+
+    fn kill(&self, _pid: u32) -> Result<()> {
+        run(self
+            .systemctl()
+            .args(["kill", "--signal=SIGKILL", &self.unit]))?;
+        Ok(())
+    }
+
+    fn start(&self) -> Result<()> {
+        run(self.systemctl().args(["start", &self.unit]))?;
+        Ok(())
+    }
+}
+
+/// User-supplied commands; found by its port like the CLI driver
+struct Custom {
+    port: u16,
+    start: String,
+    stop: Option<String>,
+    kill: Option<String>,
+}
+
+impl GatewayController for Custom {
+    fn describe(&self) -> String {
+        format!("gateway on port {} (custom commands)", self.port)
+    }
+
+    fn pid(&self) -> Option<u32> {
+        procfs::listening_pid(self.port)
+    }
+
+    fn stop(&self, pid: u32) -> Result<()> {
+        match &self.stop {
+            Some(command) => run(shell(command).env("GATEWAY_PID", pid.to_string())).map(drop),
+            None => procfs::signal(pid, libc::SIGTERM),
+        }
+    }
+
+    fn kill(&self, pid: u32) -> Result<()> {
+        match &self.kill {
+            Some(command) => run(shell(command).env("GATEWAY_PID", pid.to_string())).map(drop),
+            None => procfs::signal(pid, libc::SIGKILL),
+        }
+    }
+
+    fn start(&self) -> Result<()> {
+        if self.start.is_empty() {
+            anyhow::bail!("openclaw.gateway.startCommand is not set");
+        }
+        run(&mut shell(&self.start))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// A gateway that exits on stop, or only on kill if it ignores SIGTERM
+    struct Fake {
+        running: Mutex<bool>,
+        ignores_stop: bool,
+        calls: Mutex<Vec<&'static str>>,
+    }
+
+    impl Fake {
+        fn new(ignores_stop: bool) -> Self {
+            Self {
+                running: Mutex::new(true),
+                ignores_stop,
+                calls: Mutex::new(vec![]),
+            }
+        }
+
+        fn calls(&self) -> Vec<&'static str> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    impl GatewayController for Fake {
+        fn describe(&self) -> String {
+            "fake gateway".to_string()
+        }
+
+        fn pid(&self) -> Option<u32> {
+            self.running.lock().unwrap().then_some(42)
+        }
+
+        fn stop(&self, _pid: u32) -> Result<()> {
+            self.calls.lock().unwrap().push("stop");
+            if !self.ignores_stop {
+                *self.running.lock().unwrap() = false;
+            }
+            Ok(())
+        }
+
+        fn kill(&self, _pid: u32) -> Result<()> {
+            self.calls.lock().unwrap().push("kill");
+            *self.running.lock().unwrap() = false;
+            Ok(())
+        }
+
+        fn start(&self) -> Result<()> {
+            self.calls.lock().unwrap().push("start");
+            *self.running.lock().unwrap() = true;
+            Ok(())
+        }
+
+        fn alive(&self, _pid: u32) -> bool {
+            *self.running.lock().unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stop_waits_out_the_grace_period_then_kills() {
+        let grace = Duration::from_millis(300);
+
+        let polite = Fake::new(false);
+        assert_eq!(
+            stop_gracefully(&polite, grace).await.unwrap(),
+            Stopped::Graceful(42)
+        );
+        assert_eq!(polite.calls(), vec!["stop"]);
+        assert_eq!(
+            stop_gracefully(&polite, grace).await.unwrap(),
+            Stopped::NotRunning
+        );
+
+        let stubborn = Fake::new(true);
+        let started = tokio::time::Instant::now();
+        assert_eq!(
+            stop_gracefully(&stubborn, grace).await.unwrap(),
+            Stopped::Killed(42)
+        );
+        assert!(started.elapsed() >= grace);
+        assert_eq!(stubborn.calls(), vec!["stop", "kill"]);
+    }
+
+    #[test]
+    fn test_controller_follows_the_driver() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut cfg = Config::default();
+        cfg.openclaw.config_path = dir.path().to_path_buf();
+        assert_eq!(controller(&cfg).describe(), "gateway on port 7744");
+
+        cfg.openclaw.gateway.driver = GatewayDriver::Systemd;
+        assert_eq!(
+            controller(&cfg).describe(),
+            "systemd user unit openclaw-gateway"
+        );
+        cfg.openclaw.gateway.user_unit = false;
+        assert_eq!(controller(&cfg).describe(), "systemd unit openclaw-gateway");
+
+        cfg.openclaw.gateway.driver = GatewayDriver::Docker;
+        cfg.openclaw.gateway.container = "claw".into();
+        assert_eq!(controller(&cfg).describe(), "container claw");
+    }
+
+    #[test]
+    fn test_custom_commands_get_the_pid() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let out = dir.path().join("out");
+        let ctl = Custom {
+            port: DEFAULT_PORT,
+            start: format!("echo started >> {}", out.display()),
+            stop: Some(format!("echo stop $GATEWAY_PID >> {}", out.display())),
+            kill: None,
+        };
+        ctl.start().unwrap();
+        ctl.stop(1234).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "started\nstop 1234\n"
+        );
+
+        let failing = Custom {
+            start: "echo nope >&2; exit 3".into(),
+            ..ctl
+        };
+        let err = failing.start().unwrap_err().to_string();
+        assert!(err.contains("nope"), "{}", err);
+    }
+}
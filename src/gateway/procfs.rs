@@ -0,0 +1,98 @@
+use anyhow::Result;
+use std::fs;
+
+/// TCP state of a listening socket in /proc/net/tcp
+const TCP_LISTEN: &str = "0A";
+
+/// PID of the process listening on `port`, found through /proc: the socket
+/// inode from /proc/net/tcp{,6}, then the process holding that inode
+pub fn listening_pid(port: u16) -> Option<u32> {
+    let inodes: Vec<u64> = ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|table| fs::read_to_string(table).ok())
+        .flat_map(|table| listening_inodes(&table, port))
+        .collect();
+    if inodes.is_empty() {
+        return None;
+    }
+    let targets: Vec<String> = inodes.iter().map(|i| format!("socket:[{}]", i)).collect();
+
+    let mut pids: Vec<u32> = fs::read_dir("/proc")
+        .ok()?
+        .filter_map(|e| e.ok()?.file_name().to_str()?.parse().ok())
+        .collect();
+    // Lowest PID first: the parent when workers share the socket
+    pids.sort_unstable();
+    pids.into_iter().find(|pid| {
+        fs::read_dir(format!("/proc/{}/fd", pid))
+            .into_iter()
+            .flatten()
+            .filter_map(|fd| fs::read_link(fd.ok()?.path()).ok())
+            .any(|link| targets.iter().any(|t| link.as_os_str() == t.as_str()))
+    })
+}
+
+/// Socket inodes listening on `port` in a /proc/net/tcp table
+fn listening_inodes(table: &str, port: u16) -> Vec<u64> {
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let local_port = fields.get(1)?.rsplit(':').next()?;
+            if u16::from_str_radix(local_port, 16).ok()? != port || fields.get(3)? != &TCP_LISTEN {
+                return None;
+            }
+            fields.get(9)?.parse().ok().filter(|inode| *inode != 0)
+        })
+        .collect()
+}
+
+/// Whether `pid` is still running (a zombie counts as gone)
+pub fn alive(pid: u32) -> bool {
+    let Ok(stat) = fs::read_to_string(format!("/proc/{}/stat", pid)) else {
+        return false;
+    };
+    // The state follows the parenthesised command name, which may contain spaces
+    let state = stat
+        .rfind(')')
+        .and_then(|i| stat[i + 1..].split_whitespace().next());
+    !matches!(state, Some("Z") | Some("X"))
+}
+
+/// Send `signal` to `pid`; a process that's already gone is not an error
+pub fn signal(pid: u32, signal: libc::c_int) -> Result<()> {
+    let pid = libc::pid_t::try_from(pid)?;
+    // SAFETY: kill(2) takes plain integers and has no memory effects
+    if unsafe { libc::kill(pid, signal) } == 0 {
+        return Ok(());
+    }
+    let err = std::io::Error::last_os_error();
+    if err.raw_os_error() == Some(libc::ESRCH) {
+        return Ok(());
+    }
+    Err(anyhow::anyhow!("Could not signal PID {}: {}", pid, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listening_sockets_from_proc() {
+        let table = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:1E40 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 48213 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:1E40 0100007F:C350 01 00000000:00000000 00:00000000 00000000  1000        0 48990 1 0000000000000000 20 4 30 10 -1
+   2: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 1234 1 0000000000000000 100 0 0 10 0
+";
+        assert_eq!(listening_inodes(table, 7744), vec![48213]);
+        assert_eq!(listening_inodes(table, 22), vec![1234]);
+        assert!(listening_inodes(table, 8080).is_empty());
+
+        // Our own process, and a socket we hold
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert_eq!(listening_pid(port), Some(std::process::id()));
+        assert!(alive(std::process::id()));
+    }
+}
@@ -0,0 +1,383 @@
+use anyhow::{Context, Result};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::{Config, GuardPolicy, LiveConfig, OpenClawFile};
+use crate::health::{CheckpointRequest, CHECKPOINT_REQUEST_PATH};
+use crate::validate::{Severity, ValidationIssue, Validator};
+
+/// Editors write files in several steps; wait for the burst to settle
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// What the guard did about an invalid edit
+#[derive(Debug)]
+pub struct Intervention {
+    pub incident_id: String,
+    /// Human-readable alert, ready to send
+    pub message: String,
+}
+
+/// Validates every edit to openclaw.json and keeps the last valid version
+pub struct ConfigGuard {
+    cfg: Config,
+    checkpoint_path: PathBuf,
+    last_valid: Option<Vec<u8>>,
+}
+
+impl ConfigGuard {
+    /// Start from the live config if it is valid, else the copy saved last run.
+    /// Checkpoints are requested through `checkpoint_path`.
+    pub fn new(cfg: &Config, checkpoint_path: impl Into<PathBuf>) -> Self {
+        let mut guard = Self {
+            cfg: cfg.clone(),
+            checkpoint_path: checkpoint_path.into(),
+            last_valid: None,
+        };
+
+        guard.last_valid = match guard.read_live() {
+            Some((_, content)) if guard.errors().is_empty() => {
+                guard.remember(&content);
+                Some(content)
+            }
+            Some(_) => {
+                tracing::warn!(
+                    "OpenClaw config is currently invalid — using saved last-valid copy"
+                );
+                fs::read(guard.saved_path()).ok()
+            }
+            None => fs::read(guard.saved_path()).ok(),
+        };
+        guard
+    }
+
+    /// Pick up a reloaded config; the last valid version is kept
+    pub fn reconfigure(&mut self, cfg: &Config) {
+        self.cfg = cfg.clone();
+    }
+
+    /// Handle a change to the config file. Returns the intervention, if any;
+    /// valid edits just become the new last-valid version.
+    pub fn on_change(&mut self) -> Option<Intervention> {
+        // A deleted file is usually an editor mid-save; the next event will tell
+        let (file, content) = self.read_live()?;
+        if self.last_valid.as_ref() == Some(&content) {
+            // Unchanged, or our own revert landing
+            return None;
+        }
+
+        let errors = self.errors();
+        if errors.is_empty() {
+            tracing::info!("OpenClaw config change validated");
+            self.remember(&content);
+            self.last_valid = Some(content);
+            return None;
+        }
+
+        Some(self.intervene(&file, &errors))
+    }
+
+    fn intervene(&self, file: &Path, errors: &[ValidationIssue]) -> Intervention {
+        let category = if errors.iter().any(|i| i.rule == "config-parse") {
+            "config-syntax"
+        } else {
+            "config-invalid"
+        };
+        let mut details: Vec<String> = errors
+            .iter()
+            .map(|i| format!("[{}] {}", i.rule, i.message))
+            .collect();
+
+        let mut snapshot = None;
+        let (recovery, action) = match (self.cfg.guard.policy, &self.last_valid) {
+            (GuardPolicy::Alert, _) => {
+                ("alert", "Alert only — the file was left as is".to_string())
+            }
+            (_, None) => (
+                "alert",
+                "No valid version on record — the file was left as is".to_string(),
+            ),
+            (GuardPolicy::Revert, Some(valid)) => match write_atomic(file, valid) {
+                Ok(()) => (
+                    "config-revert",
+                    "Reverted to the last valid version".to_string(),
+                ),
+                Err(e) => ("alert", format!("Revert failed: {:#}", e)),
+            },
+            (GuardPolicy::Checkpoint, Some(valid)) => match self.checkpoint(file, valid) {
+                Ok(id) => {
+                    let action = format!(
+                        "Checkpoint {} armed — rolls back if the gateway fails within {}",
+                        id, self.cfg.guard.rollback_window
+                    );
+                    snapshot = Some(id);
+                    ("config-checkpoint", action)
+                }
+                Err(e) => ("alert", format!("Checkpoint failed: {:#}", e)),
+            },
+        };
+        tracing::warn!(
+            "Invalid OpenClaw config edit ({} error(s)): {}",
+            errors.len(),
+            action
+        );
+        details.push(action.clone());
+
+        let incident = crate::incidents::record_intervention(
+            &self.cfg,
+            category,
+            "config-guard",
+            recovery,
+            &details,
+            snapshot,
+        );
+
+        let mut message = format!("🛡️ Invalid edit to {}\n\n", file.display());
+        for line in &details[..details.len() - 1] {
+            message.push_str(&format!("• {}\n", line));
+        }
+        message.push_str(&format!("\n{}\nIncident: {}", action, incident.id));
+
+        Intervention {
+            incident_id: incident.id,
+            message,
+        }
+    }
+
+    /// Snapshot with the last valid config in place of the broken one and
+    /// hand it to the health loop as a checkpoint
+    fn checkpoint(&self, file: &Path, valid: &[u8]) -> Result<String> {
+        let name = file
+            .file_name()
+            .context("config path has no file name")?
+            .to_string_lossy();
+        let overrides = BTreeMap::from([(format!("config/{}", name), valid.to_vec())]);
+        let snapshot = crate::backup::take_snapshot_with(&self.cfg, &overrides)?;
+
+        CheckpointRequest::new(
+            "config-guard: invalid openclaw.json edit",
+            self.cfg.guard.rollback_window.as_secs(),
+            Some(snapshot.id.clone()),
+        )
+        .write(&self.checkpoint_path)
+        .with_context(|| format!("writing {}", self.checkpoint_path.display()))?;
+        Ok(snapshot.id)
+    }
+
+    fn read_live(&self) -> Option<(PathBuf, Vec<u8>)> {
+        let file = OpenClawFile::find(&self.cfg.openclaw.config_path)?;
+        let content = fs::read(&file).ok()?;
+        Some((file, content))
+    }
+
+    fn errors(&self) -> Vec<ValidationIssue> {
+        Validator::for_config(&self.cfg)
+            .check_config(&self.cfg.openclaw.config_path)
+            .into_iter()
+            .filter(|i| i.severity == Severity::Error)
+            .collect()
+    }
+
+    /// Where the last valid config survives restarts
+    fn saved_path(&self) -> PathBuf {
+        self.cfg.backup.path.join("guard").join("last-valid.json")
+    }
+
+    fn remember(&self, content: &[u8]) {
+        if let Err(e) = write_atomic(&self.saved_path(), content) {
+            tracing::warn!("Could not save last valid OpenClaw config: {}", e);
+        }
+    }
+}
+
+fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, data)?;
+    if let Err(e) = fs::rename(&tmp, path) {
+        let _ = fs::remove_file(&tmp);
+        // A bind-mounted file can't be replaced, only rewritten
+        if e.raw_os_error() != Some(libc::EBUSY) {
+            return Err(e.into());
+        }
+        fs::write(path, data)?;
+    }
+    Ok(())
+}
+
+/// Watch the OpenClaw config directory and guard every edit. The policy
+/// follows config reloads; the watch is set up again when a reload changes
+/// `guard.enabled` or `openclaw.configPath`.
+pub async fn guard_loop(live: &LiveConfig) -> Result<()> {
+    let mut reloads = live.clone();
+    loop {
+        tokio::select! {
+            r = guard_config(live) => return r,
+            _ = reloads.changed_in(|c| (c.guard.enabled, c.openclaw.config_path.clone())) => {
+                tracing::info!("Config guard restarting for the new config")
+            }
+        }
+    }
+}
+
+async fn guard_config(live: &LiveConfig) -> Result<()> {
+    let cfg = live.current();
+    if !cfg.guard.enabled {
+        crate::journal::idle().await
+    }
+
+    // Watch the directory: editors often save by renaming over the file
+    let path = &cfg.openclaw.config_path;
+    let dir = if path.is_file() {
+        path.parent().unwrap_or(path).to_path_buf()
+    } else {
+        path.clone()
+    };
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            let _ = tx.send(event);
+        }
+    })
+    .and_then(|mut w| w.watch(&dir, RecursiveMode::NonRecursive).map(|_| w));
+    let _watcher = match watcher {
+        Ok(w) => w,
+        Err(e) => {
+            tracing::error!(
+                "Config guard cannot watch {}: {} — running without it",
+                dir.display(),
+                e
+            );
+            // Health checks and backups still run
+            crate::journal::idle().await
+        }
+    };
+    tracing::info!(
+        "Config guard watching {} (policy: {:?})",
+        dir.display(),
+        cfg.guard.policy
+    );
+
+    let mut guard = ConfigGuard::new(&cfg, CHECKPOINT_REQUEST_PATH);
+    while let Some(event) = rx.recv().await {
+        if !is_config_event(&event) {
+            continue;
+        }
+        // Drain the rest of the burst
+        while let Ok(Some(_)) = tokio::time::timeout(DEBOUNCE, rx.recv()).await {}
+
+        let cfg = live.current();
+        guard.reconfigure(&cfg);
+        if let Some(intervention) = guard.on_change() {
+            tracing::warn!("Config guard recorded {}", intervention.incident_id);
+            crate::telegram::notify(&cfg, &intervention.message).await;
+        }
+    }
+
+    anyhow::bail!("Config watcher stopped")
+}
+
+fn is_config_event(event: &notify::Event) -> bool {
+    !matches!(event.kind, EventKind::Access(_))
+        && event.paths.iter().any(|p| {
+            p.file_name()
+                .is_some_and(|n| n == "openclaw.json" || n == "clawdbot.json")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::incidents::IncidentStore;
+
+    const VALID: &str = r#"{"defaultModel": "gpt-4", "providers": {"openai": {"apiKey": "sk-1234567890abcdef"}}, "gateway": {"port": 18789}}"#;
+    const BROKEN: &str = r#"{"defaultModel": "gpt-4", "providers": {"#;
+
+    fn setup(policy: GuardPolicy) -> (tempfile::TempDir, ConfigGuard) {
+        let temp = tempfile::tempdir().unwrap();
+        let mut cfg = Config::default();
+        cfg.backup.path = temp.path().join("backups");
+        cfg.openclaw.config_path = temp.path().join("openclaw");
+        cfg.openclaw.workspace = temp.path().join("workspace");
+        cfg.guard.policy = policy;
+        fs::create_dir_all(&cfg.openclaw.config_path).unwrap();
+        fs::create_dir_all(&cfg.openclaw.workspace).unwrap();
+        fs::write(cfg.openclaw.config_path.join("openclaw.json"), VALID).unwrap();
+
+        let guard = ConfigGuard::new(&cfg, temp.path().join("checkpoint.json"));
+        (temp, guard)
+    }
+
+    fn edit(temp: &tempfile::TempDir, content: &str) {
+        fs::write(temp.path().join("openclaw/openclaw.json"), content).unwrap();
+    }
+
+    fn live(temp: &tempfile::TempDir) -> String {
+        fs::read_to_string(temp.path().join("openclaw/openclaw.json")).unwrap()
+    }
+
+    #[test]
+    fn test_valid_edit_becomes_last_valid() {
+        let (temp, mut guard) = setup(GuardPolicy::Revert);
+        let edited = VALID.replace("18789", "18790");
+        edit(&temp, &edited);
+        assert!(guard.on_change().is_none());
+
+        edit(&temp, BROKEN);
+        let intervention = guard.on_change().unwrap();
+        assert!(intervention.message.contains("Reverted"));
+        assert_eq!(live(&temp), edited);
+        // Our own revert is not a new change
+        assert!(guard.on_change().is_none());
+
+        let saved = fs::read_to_string(temp.path().join("backups/guard/last-valid.json")).unwrap();
+        assert_eq!(saved, edited);
+    }
+
+    #[test]
+    fn test_alert_policy_leaves_file() {
+        let (temp, mut guard) = setup(GuardPolicy::Alert);
+        edit(&temp, BROKEN);
+        let intervention = guard.on_change().unwrap();
+        assert_eq!(live(&temp), BROKEN);
+
+        let incident = IncidentStore::at(temp.path().join("backups/incidents"))
+            .get(&intervention.incident_id)
+            .unwrap();
+        assert_eq!(incident.category, "config-syntax");
+        assert_eq!(incident.trigger, "config-guard");
+        assert_eq!(incident.recovery.as_deref(), Some("alert"));
+        assert!(incident.ended_at.is_some());
+    }
+
+    #[test]
+    fn test_checkpoint_policy_snapshots_last_valid() {
+        let (temp, mut guard) = setup(GuardPolicy::Checkpoint);
+        edit(&temp, BROKEN);
+        let intervention = guard.on_change().unwrap();
+        assert_eq!(live(&temp), BROKEN);
+
+        let request = fs::read_to_string(temp.path().join("checkpoint.json")).unwrap();
+        let request: serde_json::Value = serde_json::from_str(&request).unwrap();
+        let backup_id = request["backup_id"].as_str().unwrap();
+
+        let incident = IncidentStore::at(temp.path().join("backups/incidents"))
+            .get(&intervention.incident_id)
+            .unwrap();
+        assert_eq!(incident.snapshot.as_deref(), Some(backup_id));
+
+        // The snapshot holds the last valid config, not the broken edit
+        let extracted = temp.path().join("extracted");
+        let snapshot = crate::backup::find_snapshot(&guard.cfg, Some(backup_id)).unwrap();
+        crate::restore::extract_backup_to(&snapshot.path, &extracted, &guard.cfg).unwrap();
+        assert_eq!(
+            fs::read_to_string(extracted.join("config/openclaw.json")).unwrap(),
+            VALID
+        );
+    }
+}
@@ -0,0 +1,332 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::config::{Config, Duration};
+use crate::incidents::{EscalationRecord, Incident};
+
+/// One rung of `health.escalation`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+    /// Restart the gateway, then give it `wait` to come back
+    Restart { wait: Duration },
+    /// Restore the newest known-good snapshot not tried yet in this incident
+    Restore { wait: Duration },
+    /// Tell the allowed Telegram users
+    Alert { wait: Duration },
+}
+
+impl Step {
+    pub fn wait(&self) -> Duration {
+        match *self {
+            Step::Restart { wait } | Step::Restore { wait } | Step::Alert { wait } => wait,
+        }
+    }
+
+    /// Whether the step acts on the gateway, which only happens with
+    /// `health.autoRestore` on
+    pub fn needs_auto_restore(&self) -> bool {
+        !matches!(self, Step::Alert { .. })
+    }
+}
+
+impl FromStr for Step {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let action = words.next().ok_or("empty step")?;
+        let wait = |default: u64| -> Result<Duration, String> {
+            let rest: Vec<&str> = words.collect();
+            if rest.is_empty() {
+                return Ok(Duration::from_secs(default));
+            }
+            rest.join(" ").parse().map_err(|e| format!("{}", e))
+        };
+        match action {
+            "restart" => Ok(Step::Restart { wait: wait(60)? }),
+            "restore" => Ok(Step::Restore {
+                wait: wait(5 * 60)?,
+            }),
+            "alert" => Ok(Step::Alert { wait: wait(0)? }),
+            other => Err(format!(
+                "unknown step '{}' (expected restart, restore or alert)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (action, wait) = match *self {
+            Step::Restart { wait } => ("restart", wait),
+            Step::Restore { wait } => ("restore", wait),
+            Step::Alert { wait } => ("alert", wait),
+        };
+        if wait.as_std().is_zero() {
+            write!(f, "{}", action)
+        } else {
+            write!(f, "{} {}", action, wait)
+        }
+    }
+}
+
+/// `health.escalation`, parsed
+pub fn ladder(cfg: &Config) -> Result<Vec<Step>> {
+    cfg.health
+        .escalation
+        .iter()
+        .enumerate()
+        .map(|(i, step)| {
+            step.parse()
+                .map_err(|e| anyhow::anyhow!("health.escalation[{}]: {}", i, e))
+        })
+        .collect()
+}
+
+/// What the ladder wants now for an open incident
+#[derive(Debug, PartialEq)]
+pub enum Next {
+    /// Run this rung
+    Run(usize, Step),
+    /// The previous step's wait isn't over
+    Wait(DateTime<Utc>),
+    /// Every step has been taken
+    Exhausted,
+}
+
+pub fn next(ladder: &[Step], incident: &Incident, now: DateTime<Utc>) -> Next {
+    let taken = incident.escalation.len();
+    if let Some(last) = incident.escalation.last() {
+        if now < last.next_after {
+            return Next::Wait(last.next_after);
+        }
+    }
+    match ladder.get(taken) {
+        Some(step) => Next::Run(taken, *step),
+        None => Next::Exhausted,
+    }
+}
+
+/// The newest snapshot taken before the incident started (so from before the
+/// agent broke) that no earlier step of this incident has restored
+pub fn known_good_snapshot(cfg: &Config, incident: &Incident) -> Result<Option<String>> {
+    let snapshots = crate::backup::list_snapshots(cfg)?;
+    let ids = snapshots.iter().map(|s| s.id.as_str());
+    Ok(pick_snapshot(ids, incident).map(str::to_string))
+}
+
+/// When a restore step of an earlier incident last ran, if that is within
+/// `health.autoRestoreCooldown`. Restores within one incident are spaced by
+/// the ladder's waits instead.
+pub fn cooling_down(cfg: &Config, incident: &Incident) -> Result<Option<DateTime<Utc>>> {
+    let Some(cooldown) = cfg.health.auto_restore_cooldown else {
+        return Ok(None);
+    };
+    let store = crate::incidents::IncidentStore::open(cfg);
+    let since = cooldown.before(Utc::now());
+    let mut last = None;
+    for summary in store.index()? {
+        // A restore sets the recovery, and happens before the incident ends
+        if summary.id == incident.id
+            || summary.recovery.is_none()
+            || summary.ended_at.is_some_and(|end| end < since)
+        {
+            continue;
+        }
+        let restored = store
+            .get(&summary.id)?
+            .escalation
+            .iter()
+            .filter(|r| r.snapshot.is_some() && r.at >= since)
+            .map(|r| r.at)
+            .max();
+        last = last.max(restored);
+    }
+    Ok(last)
+}
+
+fn pick_snapshot<'a>(ids: impl Iterator<Item = &'a str>, incident: &Incident) -> Option<&'a str> {
+    let tried: Vec<&str> = incident
+        .escalation
+        .iter()
+        .filter_map(|r| r.snapshot.as_deref())
+        .collect();
+    let mut candidates: Vec<(NaiveDateTime, &str)> = ids
+        .filter(|id| !tried.contains(id))
+        .filter_map(|id| {
+            let taken = NaiveDateTime::parse_from_str(id, "%Y%m%d-%H%M%S").ok()?;
+            (taken.and_utc() < incident.started_at).then_some((taken, id))
+        })
+        .collect();
+    candidates.sort();
+    candidates.last().map(|(_, id)| *id)
+}
+
+/// A record of `step` finishing now with `outcome`
+pub fn record(
+    step: &Step,
+    outcome: impl Into<String>,
+    snapshot: Option<String>,
+) -> EscalationRecord {
+    let at = Utc::now();
+    EscalationRecord {
+        at,
+        step: step.to_string(),
+        outcome: outcome.into(),
+        snapshot,
+        next_after: step.wait().after(at),
+    }
+}
+
+/// The ladder for `rescueclaw status`: each step with how it went, or what's
+/// next, for the open incident
+pub fn describe(ladder: &[Step], incident: Option<&Incident>, auto_restore: bool) -> Vec<String> {
+    let taken = incident.map_or(&[][..], |i| &i.escalation[..]);
+    ladder
+        .iter()
+        .enumerate()
+        .map(|(i, step)| (i, step, !auto_restore && step.needs_auto_restore()))
+        .map(|(i, step, off)| match taken.get(i) {
+            Some(record) => format!(
+                "✓ {} — {} ({})",
+                step,
+                record.outcome,
+                record.at.with_timezone(&chrono::Local).format("%H:%M:%S")
+            ),
+            None if incident.is_some() && i == taken.len() => match taken.last() {
+                Some(last) if last.next_after > Utc::now() => format!(
+                    "▶ {} — next, not before {}",
+                    step,
+                    last.next_after
+                        .with_timezone(&chrono::Local)
+                        .format("%H:%M:%S")
+                ),
+                _ => format!("▶ {} — next", step),
+            },
+            None if off => format!("· {} (skipped: autoRestore is off)", step),
+            None => format!("· {}", step),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps_parse_and_print() {
+        let steps: Vec<Step> = ["restart", "restart 2m", "restore 1h30m", "alert"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        assert_eq!(
+            steps.iter().map(Step::to_string).collect::<Vec<_>>(),
+            vec!["restart 1m", "restart 2m", "restore 1h30m", "alert"]
+        );
+        assert!("reboot"
+            .parse::<Step>()
+            .unwrap_err()
+            .contains("unknown step"));
+        assert!("restart soon".parse::<Step>().is_err());
+        let forever: Step = "alert 100000000d".parse().unwrap();
+        assert_eq!(
+            record(&forever, "alerted", None).next_after,
+            DateTime::<Utc>::MAX_UTC
+        );
+        assert!(ladder(&Config::default()).is_ok());
+    }
+
+    #[test]
+    fn test_ladder_walks_steps_and_older_snapshots() {
+        let started = "2026-03-01T12:00:00Z".parse().unwrap();
+        let mut incident = Incident::new(started, "unresponsive", "health-check");
+        let ladder: Vec<Step> = ["restart 1m", "restore", "restore", "alert"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let now = Utc::now();
+
+        assert_eq!(next(&ladder, &incident, now), Next::Run(0, ladder[0]));
+        incident
+            .escalation
+            .push(record(&ladder[0], "restarted", None));
+        let Next::Wait(until) = next(&ladder, &incident, Utc::now()) else {
+            panic!("should wait after a restart");
+        };
+        assert_eq!(next(&ladder, &incident, until), Next::Run(1, ladder[1]));
+
+        // Snapshots from during the outage don't count as known-good
+        let ids = [
+            "20260301-130000",
+            "20260301-110000",
+            "20260301-100000",
+            "bogus",
+        ];
+        assert_eq!(
+            pick_snapshot(ids.into_iter(), &incident),
+            Some("20260301-110000")
+        );
+        incident.escalation.push(record(
+            &ladder[1],
+            "restored",
+            Some("20260301-110000".into()),
+        ));
+        assert_eq!(
+            pick_snapshot(ids.into_iter(), &incident),
+            Some("20260301-100000")
+        );
+
+        let status = describe(&ladder, Some(&incident), true);
+        assert!(status[0].starts_with("✓ restart 1m — restarted"));
+        assert!(status[2].starts_with("▶ restore 5m — next"));
+        assert_eq!(status[3], "· alert");
+
+        incident
+            .escalation
+            .push(record(&ladder[2], "skipped", None));
+        incident
+            .escalation
+            .push(record(&ladder[3], "alerted", None));
+        let later = Utc::now() + chrono::Duration::hours(1);
+        assert_eq!(next(&ladder, &incident, later), Next::Exhausted);
+        assert_eq!(describe(&ladder, None, true)[0], "· restart 1m");
+        assert_eq!(
+            describe(&ladder, None, false)[1],
+            "· restore 5m (skipped: autoRestore is off)"
+        );
+    }
+
+    #[test]
+    fn test_cooldown_spans_incidents() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut cfg = Config::default();
+        cfg.backup.path = temp.path().to_path_buf();
+        let store = crate::incidents::IncidentStore::open(&cfg);
+        let restore: Step = "restore".parse().unwrap();
+
+        let mut earlier = Incident::new(
+            Utc::now() - chrono::Duration::minutes(30),
+            "unresponsive",
+            "health-check",
+        );
+        let mut restored = record(&restore, "restored", Some("20260301-110000".into()));
+        restored.at = Utc::now() - chrono::Duration::minutes(25);
+        earlier.escalation.push(restored.clone());
+        earlier.close("auto-restore");
+        store.insert(&mut earlier).unwrap();
+
+        let mut current = Incident::new(Utc::now(), "unresponsive", "health-check");
+        store.insert(&mut current).unwrap();
+        assert_eq!(cooling_down(&cfg, &current).unwrap(), Some(restored.at));
+        // The earlier incident's own restores aren't limited by it
+        assert_eq!(cooling_down(&cfg, &earlier).unwrap(), None);
+
+        cfg.health.auto_restore_cooldown = Some("10m".parse().unwrap());
+        assert_eq!(cooling_down(&cfg, &current).unwrap(), None);
+        cfg.health.auto_restore_cooldown = None;
+        assert_eq!(cooling_down(&cfg, &current).unwrap(), None);
+    }
+}
@@ -3,19 +3,48 @@ use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
-use crate::config::Config;
+use crate::config::{Config, LiveConfig};
+
+pub mod escalation;
+
+/// Where the OpenClaw skill (and the config guard) drop checkpoint requests
+pub const CHECKPOINT_REQUEST_PATH: &str = "/var/rescueclaw/checkpoint-request.json";
 
 /// Checkpoint request from OpenClaw skill
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[allow(dead_code)]
-struct CheckpointRequest {
+pub struct CheckpointRequest {
     action: String,
     reason: String,
     timestamp: String,
     rollback_window_seconds: u64,
+    /// Snapshot to roll back to; the daemon takes a fresh one when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    backup_id: Option<String>,
+}
+
+impl CheckpointRequest {
+    pub fn new(reason: &str, rollback_window_seconds: u64, backup_id: Option<String>) -> Self {
+        Self {
+            action: "checkpoint".to_string(),
+            reason: reason.to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            rollback_window_seconds,
+            backup_id,
+        }
+    }
+
+    /// Write the request for `health_loop` to pick up
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
 }
 
 /// State for active checkpoint monitoring
@@ -36,6 +65,9 @@ pub struct HealthStatus {
     pub backup_count: usize,
     pub consecutive_failures: u32,
     pub skill_installed: bool,
+    /// The escalation ladder, with progress on the open incident
+    #[serde(default)]
+    pub escalation: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -88,6 +120,12 @@ impl fmt::Display for HealthStatus {
                 "⚠️  Not installed"
             }
         )?;
+        if !self.escalation.is_empty() {
+            writeln!(f, "Escalation:")?;
+            for step in &self.escalation {
+                writeln!(f, "  {}", step)?;
+            }
+        }
         Ok(())
     }
 }
@@ -95,6 +133,14 @@ impl fmt::Display for HealthStatus {
 /// Check current status of the agent and watchdog
 pub async fn check_status(cfg: &Config) -> Result<HealthStatus> {
     let agent_online = check_agent_alive(cfg).await;
+    let open = crate::incidents::IncidentStore::open(cfg)
+        .current()
+        .ok()
+        .flatten();
+    let escalation = match escalation::ladder(cfg) {
+        Ok(ladder) => escalation::describe(&ladder, open.as_ref(), cfg.health.auto_restore),
+        Err(e) => vec![format!("❌ {}", e)],
+    };
     let backup_count = crate::backup::list_snapshots(cfg)?.len();
     let last_backup = crate::backup::list_snapshots(cfg)?
         .first()
@@ -115,22 +161,17 @@ pub async fn check_status(cfg: &Config) -> Result<HealthStatus> {
         watchdog_memory_mb: get_memory_usage_mb(),
         last_backup,
         backup_count,
-        consecutive_failures: 0, // TODO: track in state file
+        consecutive_failures: open.as_ref().map_or(0, |i| i.checks_failed),
         skill_installed,
+        escalation,
     })
 }
 
-/// Check if OpenClaw gateway is responding on the configured port
+/// Check if OpenClaw gateway is responding at its configured endpoint
 async fn check_agent_alive(cfg: &Config) -> bool {
-    let port = crate::restore::read_gateway_port(cfg);
-    let client = reqwest::Client::new();
-    let result = client
-        .get(format!("http://127.0.0.1:{}/api/status", port))
-        .timeout(std::time::Duration::from_secs(5))
-        .send()
-        .await;
-
-    result.is_ok()
+    crate::gateway::endpoint(cfg)
+        .probe(std::time::Duration::from_secs(5))
+        .await
 }
 
 /// Check if rescueclaw skill is installed via clawhub
@@ -157,22 +198,29 @@ fn get_memory_usage_mb() -> f64 {
 }
 
 /// Continuous health monitoring loop
-pub async fn health_loop(cfg: &Config) -> Result<()> {
-    let interval = parse_health_interval(&cfg.health.check_interval)?;
+pub async fn health_loop(live: &LiveConfig) -> Result<()> {
     let mut consecutive_failures: u32 = 0;
-    let incidents_path = cfg.backup.path.join("incidents.jsonl");
-    let checkpoint_path = PathBuf::from("/var/rescueclaw/checkpoint-request.json");
+    let checkpoint_path = PathBuf::from(CHECKPOINT_REQUEST_PATH);
     let mut active_checkpoint: Option<CheckpointState> = None;
+    let mut suppression_noted = false;
 
     loop {
-        tokio::time::sleep(interval).await;
+        tokio::time::sleep(live.current().health.check_interval.as_std()).await;
+        // Re-read every cycle so a reload applies without losing the state above
+        let current = live.current();
+        let cfg = current.as_ref();
+        let quiet_hours = crate::schedule::QuietHours::health(cfg)?;
 
         // Check for checkpoint requests
         if let Some(checkpoint_req) = read_checkpoint_request(&checkpoint_path) {
             if active_checkpoint.is_none() {
                 // New checkpoint requested - take immediate backup
                 tracing::info!("Checkpoint requested: {}", checkpoint_req.reason);
-                match crate::backup::take_snapshot(cfg) {
+                let snapshot = match &checkpoint_req.backup_id {
+                    Some(id) => crate::backup::find_snapshot(cfg, Some(id)),
+                    None => crate::backup::take_snapshot(cfg),
+                };
+                match snapshot {
                     Ok(snapshot) => {
                         let deadline = SystemTime::now()
                             + std::time::Duration::from_secs(
@@ -202,6 +250,8 @@ pub async fn health_loop(cfg: &Config) -> Result<()> {
             if SystemTime::now() > checkpoint.deadline {
                 tracing::info!("Checkpoint rollback window expired");
                 active_checkpoint = None;
+                // Don't re-arm from a request nobody cleared
+                let _ = fs::remove_file(&checkpoint_path);
             }
         }
 
@@ -215,6 +265,15 @@ pub async fn health_loop(cfg: &Config) -> Result<()> {
                 );
             }
             consecutive_failures = 0;
+            suppression_noted = false;
+            // Also closes an incident left open by a previous daemon run
+            if let Some(closed) = crate::incidents::check_passed(cfg) {
+                tracing::info!(
+                    "Incident {} closed: {}",
+                    closed.id,
+                    closed.recovery.as_deref().unwrap_or("recovered")
+                );
+            }
         } else {
             consecutive_failures += 1;
             tracing::warn!(
@@ -223,46 +282,73 @@ pub async fn health_loop(cfg: &Config) -> Result<()> {
                 cfg.health.unhealthy_threshold
             );
 
-            // Log the incident
-            let incident = IncidentLog {
-                timestamp: Utc::now().to_rfc3339(),
-                cause: format!("Agent unresponsive (check #{})", consecutive_failures),
-                recovery: "pending".to_string(),
+            let in_checkpoint_window = active_checkpoint
+                .as_ref()
+                .filter(|c| SystemTime::now() <= c.deadline);
+            let trigger = match in_checkpoint_window {
+                Some(checkpoint) => format!("checkpoint: {}", checkpoint.reason),
+                None => "health-check".to_string(),
             };
-            if let Ok(line) = serde_json::to_string(&incident) {
-                let _ = fs::OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(&incidents_path)
-                    .and_then(|mut f| {
-                        use std::io::Write;
-                        writeln!(f, "{}", line)
-                    });
-            }
+            let checkpoint_backup = in_checkpoint_window.map(|c| c.backup_id.clone());
+            let current = crate::incidents::check_failed(cfg, &trigger);
 
             // If there's an active checkpoint and agent is down, restore immediately
-            if let Some(ref checkpoint) = active_checkpoint {
-                if SystemTime::now() <= checkpoint.deadline {
-                    tracing::error!(
-                        "Agent unresponsive within checkpoint window! Restoring immediately..."
-                    );
-                    if let Err(e) = crate::restore::restore(cfg, Some(&checkpoint.backup_id)).await
-                    {
-                        tracing::error!("Checkpoint restore failed: {}", e);
-                    } else {
+            if let Some(backup_id) = checkpoint_backup {
+                tracing::error!(
+                    "Agent unresponsive within checkpoint window! Restoring immediately..."
+                );
+                let category = crate::analysis::classify_current(cfg).map(|c| c.slug());
+                crate::incidents::note(cfg, "Checkpoint rollback started");
+                match crate::restore::restore(cfg, Some(&backup_id)).await {
+                    Ok(outcome) => {
+                        crate::incidents::record_restore(
+                            cfg,
+                            category,
+                            &trigger,
+                            "checkpoint-rollback",
+                            &outcome,
+                            None,
+                        );
                         consecutive_failures = 0;
                         active_checkpoint = None;
                     }
-                    continue;
+                    Err(e) => {
+                        tracing::error!("Checkpoint restore failed: {}", e);
+                        crate::incidents::note(cfg, &format!("Checkpoint rollback failed: {}", e));
+                    }
                 }
+                continue;
             }
 
-            // Auto-restore if enabled and threshold reached
-            if cfg.health.auto_restore && consecutive_failures >= cfg.health.unhealthy_threshold {
-                tracing::error!("Threshold reached! Initiating auto-restore...");
-                if let Err(e) = crate::restore::restore(cfg, None).await {
-                    tracing::error!("Auto-restore failed: {}", e);
-                } else {
+            if consecutive_failures < cfg.health.unhealthy_threshold {
+                continue;
+            }
+            if let Some(window) = quiet_hours.active(Utc::now()) {
+                if !suppression_noted {
+                    tracing::warn!("Escalation suppressed: quiet hours {}", window);
+                    crate::incidents::note(
+                        cfg,
+                        &format!("Escalation suppressed: quiet hours {}", window),
+                    );
+                    suppression_noted = true;
+                }
+                continue;
+            }
+            let Some(incident) = current else {
+                continue;
+            };
+            let ladder = match escalation::ladder(cfg) {
+                Ok(ladder) => ladder,
+                Err(e) => {
+                    tracing::error!("Cannot escalate: {}", e);
+                    continue;
+                }
+            };
+            if let escalation::Next::Run(index, step) =
+                escalation::next(&ladder, &incident, Utc::now())
+            {
+                tracing::warn!("Escalating ({}/{}): {}", index + 1, ladder.len(), step);
+                if run_step(cfg, &step, &incident, &trigger, consecutive_failures).await {
                     consecutive_failures = 0;
                 }
             }
@@ -270,63 +356,192 @@ pub async fn health_loop(cfg: &Config) -> Result<()> {
     }
 }
 
-/// Read and parse checkpoint request file
-fn read_checkpoint_request(path: &PathBuf) -> Option<CheckpointRequest> {
-    if !path.exists() {
-        return None;
+/// Take one escalation step and record it on the incident. True when a
+/// restore brought the agent back.
+async fn run_step(
+    cfg: &Config,
+    step: &escalation::Step,
+    incident: &crate::incidents::Incident,
+    trigger: &str,
+    failures: u32,
+) -> bool {
+    use escalation::Step;
+
+    if !cfg.health.auto_restore && step.needs_auto_restore() {
+        let record = escalation::record(step, "skipped: autoRestore is off", None);
+        crate::incidents::record_escalation(cfg, record, None);
+        return false;
     }
+    match step {
+        Step::Restart { .. } => {
+            let outcome = match restart_gateway(cfg).await {
+                Ok(outcome) => outcome,
+                Err(e) => format!("restart failed: {:#}", e),
+            };
+            tracing::info!("Escalation: {}", outcome);
+            crate::incidents::record_escalation(
+                cfg,
+                escalation::record(step, outcome, None),
+                Some("gateway-restart"),
+            );
+            false
+        }
+        Step::Restore { .. } => {
+            match escalation::cooling_down(cfg, incident) {
+                Ok(Some(last)) => {
+                    let outcome = format!(
+                        "skipped: last auto-restore at {}, autoRestoreCooldown is {}",
+                        last.with_timezone(&chrono::Local).format("%H:%M"),
+                        cfg.health
+                            .auto_restore_cooldown
+                            .map_or(String::new(), |c| c.to_string())
+                    );
+                    let record = escalation::record(step, outcome, None);
+                    crate::incidents::record_escalation(cfg, record, None);
+                    return false;
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Could not check autoRestoreCooldown: {:#}", e),
+            }
+            let snapshot = match escalation::known_good_snapshot(cfg, incident) {
+                Ok(Some(id)) => id,
+                Ok(None) => {
+                    let record =
+                        escalation::record(step, "skipped: no known-good snapshot left", None);
+                    crate::incidents::record_escalation(cfg, record, None);
+                    return false;
+                }
+                Err(e) => {
+                    let record = escalation::record(step, format!("failed: {:#}", e), None);
+                    crate::incidents::record_escalation(cfg, record, None);
+                    return false;
+                }
+            };
+            auto_restore(cfg, step, incident, trigger, failures, snapshot).await
+        }
+        Step::Alert { .. } => {
+            let taken: Vec<String> = incident
+                .escalation
+                .iter()
+                .map(|r| format!("• {} — {}", r.step, r.outcome))
+                .collect();
+            let message = format!(
+                "🚨 OpenClaw is still down ({} failed checks since {}).\n\nTried:\n{}\n\nAutomatic recovery has stopped — please take a look. /status, /logs, /rescue",
+                failures,
+                incident.started_at.with_timezone(&chrono::Local).format("%H:%M"),
+                if taken.is_empty() { "• nothing".to_string() } else { taken.join("\n") }
+            );
+            crate::telegram::notify(cfg, &message).await;
+            let outcome = format!(
+                "alerted {} Telegram user(s)",
+                cfg.telegram.allowed_users.len()
+            );
+            crate::incidents::record_escalation(cfg, escalation::record(step, outcome, None), None);
+            false
+        }
+    }
+}
 
-    let content = fs::read_to_string(path).ok()?;
-    serde_json::from_str(&content).ok()
+/// Stop the gateway (within the grace period) and start it again
+async fn restart_gateway(cfg: &Config) -> Result<String> {
+    let gateway = crate::gateway::controller(cfg);
+    let grace = cfg.openclaw.gateway.grace_period;
+    let stopped = crate::gateway::stop_gracefully(gateway.as_ref(), grace.as_std()).await?;
+    gateway.start()?;
+    Ok(match stopped {
+        crate::gateway::Stopped::NotRunning => "gateway was not running; started".to_string(),
+        crate::gateway::Stopped::Graceful(pid) => format!("restarted (PID {} stopped)", pid),
+        crate::gateway::Stopped::Killed(pid) => {
+            format!("restarted (PID {} killed after {})", pid, grace)
+        }
+    })
 }
 
-/// Read recent incident logs
-pub fn recent_incidents(cfg: &Config, n: usize) -> Result<Vec<IncidentLog>> {
-    let incidents_path = cfg.backup.path.join("incidents.jsonl");
-    if !incidents_path.exists() {
-        return Ok(vec![]);
+/// Restore `snapshot`, analyzing first if configured; true if the agent is back
+async fn auto_restore(
+    cfg: &Config,
+    step: &escalation::Step,
+    incident: &crate::incidents::Incident,
+    trigger: &str,
+    failures: u32,
+    snapshot: String,
+) -> bool {
+    tracing::error!("Initiating auto-restore from {}...", snapshot);
+    let category = crate::analysis::classify_current(cfg).map(|c| c.slug());
+    let result = if cfg.health.analyze_on_restore {
+        let log = IncidentLog {
+            timestamp: incident.started_at.to_rfc3339(),
+            cause: format!(
+                "Agent unresponsive ({} consecutive failed checks)",
+                failures
+            ),
+            recovery: "auto-restore".to_string(),
+        };
+        crate::restore::restore_and_analyze(cfg, Some(&snapshot), &log)
+            .await
+            .map(|(outcome, report)| {
+                let summary = report.map(|r| crate::analysis::summarize(&r.analysis));
+                (outcome, summary)
+            })
+    } else {
+        crate::restore::restore(cfg, Some(&snapshot))
+            .await
+            .map(|outcome| (outcome, None))
+    };
+    match result {
+        Ok((outcome, summary)) => {
+            let how = format!(
+                "restored {} ({} file(s)), agent {}",
+                outcome.snapshot_id,
+                outcome.restored_files.len(),
+                if outcome.agent_online {
+                    "online"
+                } else {
+                    "still offline"
+                }
+            );
+            // Before record_restore, which closes the incident if the agent is back
+            crate::incidents::record_escalation(
+                cfg,
+                escalation::record(step, how, Some(snapshot)),
+                None,
+            );
+            crate::incidents::record_restore(
+                cfg,
+                category,
+                trigger,
+                "auto-restore",
+                &outcome,
+                summary,
+            );
+            outcome.agent_online
+        }
+        Err(e) => {
+            tracing::error!("Auto-restore failed: {}", e);
+            crate::incidents::record_escalation(
+                cfg,
+                escalation::record(step, format!("restore failed: {:#}", e), Some(snapshot)),
+                None,
+            );
+            false
+        }
     }
-
-    let content = fs::read_to_string(&incidents_path)?;
-    let incidents: Vec<IncidentLog> = content
-        .lines()
-        .filter_map(|line| serde_json::from_str(line).ok())
-        .collect();
-
-    Ok(incidents.into_iter().rev().take(n).collect())
 }
 
-fn parse_health_interval(s: &str) -> Result<tokio::time::Duration> {
-    let s = s.trim();
-    if let Some(m) = s.strip_suffix('m') {
-        Ok(tokio::time::Duration::from_secs(m.parse::<u64>()? * 60))
-    } else if let Some(s_val) = s.strip_suffix('s') {
-        Ok(tokio::time::Duration::from_secs(s_val.parse::<u64>()?))
-    } else if let Some(h) = s.strip_suffix('h') {
-        Ok(tokio::time::Duration::from_secs(h.parse::<u64>()? * 3600))
-    } else {
-        anyhow::bail!("Invalid interval: {}", s)
+/// Read and parse checkpoint request file
+fn read_checkpoint_request(path: &PathBuf) -> Option<CheckpointRequest> {
+    if !path.exists() {
+        return None;
     }
+
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_parse_health_interval_valid() {
-        assert_eq!(parse_health_interval("5m").unwrap().as_secs(), 300);
-        assert_eq!(parse_health_interval("30s").unwrap().as_secs(), 30);
-        assert_eq!(parse_health_interval("2h").unwrap().as_secs(), 7200);
-    }
-
-    #[test]
-    fn test_parse_health_interval_invalid() {
-        assert!(parse_health_interval("bad").is_err());
-        assert!(parse_health_interval("").is_err());
-        assert!(parse_health_interval("5x").is_err());
-    }
-
     #[test]
     fn test_incident_log_roundtrip() {
         let incident = IncidentLog {
@@ -343,6 +558,42 @@ mod tests {
         assert_eq!(parsed.recovery, incident.recovery);
     }
 
+    #[tokio::test]
+    async fn test_ladder_leaves_gateway_alone_without_auto_restore() {
+        let temp = tempfile::tempdir().unwrap();
+        let started = temp.path().join("started");
+        let mut cfg = Config::default();
+        cfg.backup.path = temp.path().join("backups");
+        cfg.openclaw.config_path = temp.path().to_path_buf();
+        let gateway = &mut cfg.openclaw.gateway;
+        gateway.driver = crate::config::GatewayDriver::Custom;
+        // Nothing listens here, so a restart would only run the start command
+        gateway.port = Some(1);
+        gateway.start_command = Some(format!("touch {}", started.display()));
+
+        let ladder = escalation::ladder(&cfg).unwrap();
+        let incident = crate::incidents::check_failed(&cfg, "health-check").unwrap();
+        for step in &ladder {
+            assert!(!run_step(&cfg, step, &incident, "health-check", 3).await);
+        }
+        assert!(!started.exists(), "the gateway controller was called");
+        let incident = crate::incidents::IncidentStore::open(&cfg)
+            .current()
+            .unwrap()
+            .unwrap();
+        let outcomes: Vec<&str> = incident
+            .escalation
+            .iter()
+            .map(|r| r.outcome.as_str())
+            .collect();
+        assert_eq!(outcomes[..4], ["skipped: autoRestore is off"; 4]);
+        assert!(outcomes[4].starts_with("alerted"));
+
+        cfg.health.auto_restore = true;
+        run_step(&cfg, &ladder[0], &incident, "health-check", 3).await;
+        assert!(started.exists());
+    }
+
     #[test]
     fn test_health_status_display() {
         let status = HealthStatus {
@@ -354,11 +605,13 @@ mod tests {
             backup_count: 5,
             consecutive_failures: 0,
             skill_installed: true,
+            escalation: vec!["· restart 1m".to_string()],
         };
 
         let display = format!("{}", status);
         assert!(display.contains("Online"));
         assert!(display.contains("12345"));
         assert!(display.contains("5 snapshots"));
+        assert!(display.contains("Escalation:\n  · restart 1m"));
     }
 }
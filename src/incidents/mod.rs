@@ -0,0 +1,897 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::restore::RestoreOutcome;
+
+/// A structured incident: one outage or intervention, from detection to recovery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Incident {
+    pub id: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Failure class, e.g. `config-syntax`, `port-in-use`, `unresponsive`
+    pub category: String,
+    /// What led to it, e.g. `health-check`, `checkpoint: config.apply`, `manual`
+    pub trigger: String,
+    /// How it was resolved (`self-recovered`, `auto-restore`,
+    /// `checkpoint-rollback`, `manual-restore`); while open, the recovery
+    /// attempted so far
+    pub recovery: Option<String>,
+    /// Snapshot restored to recover, if any
+    pub snapshot: Option<String>,
+    /// Files that differed from the snapshot and were rolled back
+    #[serde(default)]
+    pub restored_files: Vec<String>,
+    pub analysis_summary: Option<String>,
+    /// Failed health checks during the outage
+    #[serde(default)]
+    pub checks_failed: u32,
+    #[serde(default)]
+    pub timeline: Vec<TimelineEvent>,
+    /// Escalation ladder steps taken so far, in order
+    #[serde(default)]
+    pub escalation: Vec<EscalationRecord>,
+}
+
+/// One step in an incident's history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEvent {
+    pub at: DateTime<Utc>,
+    pub event: String,
+}
+
+/// An escalation step the health loop took, and how it went
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationRecord {
+    pub at: DateTime<Utc>,
+    /// The step as configured, e.g. `restart 30s`
+    pub step: String,
+    pub outcome: String,
+    /// Snapshot a restore step used
+    #[serde(default)]
+    pub snapshot: Option<String>,
+    /// The next step may not run before this
+    pub next_after: DateTime<Utc>,
+}
+
+impl Incident {
+    pub fn new(
+        started_at: DateTime<Utc>,
+        category: impl Into<String>,
+        trigger: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: format!("inc-{}", started_at.format("%Y%m%d-%H%M%S")),
+            started_at,
+            ended_at: None,
+            category: category.into(),
+            trigger: trigger.into(),
+            recovery: None,
+            snapshot: None,
+            restored_files: Vec::new(),
+            analysis_summary: None,
+            checks_failed: 0,
+            timeline: Vec::new(),
+            escalation: Vec::new(),
+        }
+    }
+
+    /// Add a timeline entry
+    pub fn note(&mut self, event: impl Into<String>) {
+        self.timeline.push(TimelineEvent {
+            at: Utc::now(),
+            event: event.into(),
+        });
+    }
+
+    /// Close the incident with the recovery method that resolved it
+    pub fn close(&mut self, recovery: impl Into<String>) {
+        let recovery = recovery.into();
+        self.note(format!("Closed: {}", recovery));
+        self.recovery = Some(recovery);
+        self.ended_at = Some(Utc::now());
+    }
+
+    pub fn summary(&self) -> IncidentSummary {
+        IncidentSummary {
+            id: self.id.clone(),
+            started_at: self.started_at,
+            ended_at: self.ended_at,
+            category: self.category.clone(),
+            trigger: self.trigger.clone(),
+            recovery: self.recovery.clone(),
+            snapshot: self.snapshot.clone(),
+            restored_files: self.restored_files.clone(),
+        }
+    }
+}
+
+/// Index row for an incident — everything needed to list and aggregate
+/// without opening each incident file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentSummary {
+    pub id: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub category: String,
+    pub trigger: String,
+    pub recovery: Option<String>,
+    pub snapshot: Option<String>,
+    #[serde(default)]
+    pub restored_files: Vec<String>,
+}
+
+impl IncidentSummary {
+    /// Outage length so far (open) or in total (closed)
+    pub fn duration(&self) -> Duration {
+        self.ended_at.unwrap_or_else(Utc::now) - self.started_at
+    }
+}
+
+impl fmt::Display for IncidentSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} │ {} │ {:>7} │ {} │ {} │ {}",
+            self.id,
+            self.started_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M"),
+            format_duration(self.duration()),
+            self.category,
+            self.trigger,
+            match (&self.recovery, self.ended_at) {
+                (Some(r), Some(_)) => r.clone(),
+                (_, None) => "⏳ open".to_string(),
+                (None, Some(_)) => "closed".to_string(),
+            }
+        )
+    }
+}
+
+impl fmt::Display for Incident {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let summary = self.summary();
+        writeln!(f, "Incident {}\n", self.id)?;
+        writeln!(f, "Category:   {}", self.category)?;
+        writeln!(f, "Trigger:    {}", self.trigger)?;
+        writeln!(
+            f,
+            "Started:    {}",
+            self.started_at.with_timezone(&chrono::Local).to_rfc2822()
+        )?;
+        match self.ended_at {
+            Some(end) => writeln!(
+                f,
+                "Ended:      {} ({})",
+                end.with_timezone(&chrono::Local).to_rfc2822(),
+                format_duration(summary.duration())
+            )?,
+            None => writeln!(
+                f,
+                "Ended:      still open ({} so far)",
+                format_duration(summary.duration())
+            )?,
+        }
+        writeln!(
+            f,
+            "Recovery:   {}",
+            self.recovery.as_deref().unwrap_or("none yet")
+        )?;
+        writeln!(f, "Checks:     {} failed", self.checks_failed)?;
+        if let Some(snapshot) = &self.snapshot {
+            writeln!(f, "Snapshot:   {}", snapshot)?;
+        }
+        if !self.restored_files.is_empty() {
+            writeln!(f, "Restored:   {}", self.restored_files.join(", "))?;
+        }
+        if let Some(analysis) = &self.analysis_summary {
+            writeln!(f, "\nAnalysis:\n{}", analysis)?;
+        }
+        if !self.escalation.is_empty() {
+            writeln!(f, "\nEscalation:")?;
+            for (i, step) in self.escalation.iter().enumerate() {
+                writeln!(f, "  {}. {} — {}", i + 1, step.step, step.outcome)?;
+            }
+        }
+
+        writeln!(f, "\nTimeline:")?;
+        for entry in &self.timeline {
+            writeln!(
+                f,
+                "  {} {}",
+                entry.at.with_timezone(&chrono::Local).format("%H:%M:%S"),
+                entry.event
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Local incident store: one JSON file per incident plus an `index.json`
+/// of summaries, under `<backup.path>/incidents/`
+pub struct IncidentStore {
+    dir: PathBuf,
+}
+
+/// Where older versions logged failed health checks, one line each
+const LEGACY_LOG: &str = "incidents.jsonl";
+
+impl IncidentStore {
+    /// The store for `cfg`. The first time, history from the legacy
+    /// `incidents.jsonl` is imported into it.
+    pub fn open(cfg: &Config) -> Self {
+        let store = Self::at(cfg.backup.path.join("incidents"));
+        let legacy = cfg.backup.path.join(LEGACY_LOG);
+        if !store.index_path().exists() && legacy.exists() {
+            match store.import_legacy(&legacy) {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("Imported {} incident(s) from {}", n, legacy.display()),
+                Err(e) => tracing::warn!("Could not import {}: {:#}", legacy.display(), e),
+            }
+        }
+        store
+    }
+
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn incident_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join("index.json")
+    }
+
+    /// Store a new incident. If its ID is already taken (two incidents in
+    /// the same second) it gets a numeric suffix.
+    pub fn insert(&self, incident: &mut Incident) -> Result<()> {
+        if self.incident_path(&incident.id).exists() {
+            let base = incident.id.clone();
+            let mut n = 2;
+            while self.incident_path(&format!("{}-{}", base, n)).exists() {
+                n += 1;
+            }
+            incident.id = format!("{}-{}", base, n);
+        }
+        self.save(incident)
+    }
+
+    /// Write an incident, keeping the index in sync
+    pub fn save(&self, incident: &Incident) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("creating {}", self.dir.display()))?;
+
+        let mut index = self.index()?;
+        let existing = index.iter().position(|s| s.id == incident.id);
+
+        write_atomic(
+            &self.incident_path(&incident.id),
+            &serde_json::to_vec_pretty(incident)?,
+        )?;
+
+        match existing {
+            Some(i) => index[i] = incident.summary(),
+            None => index.push(incident.summary()),
+        }
+        index.sort_by_key(|s| s.started_at);
+        write_atomic(&self.index_path(), &serde_json::to_vec_pretty(&index)?)
+    }
+
+    /// Turn the legacy per-check log into closed incidents, one per run of
+    /// consecutive failed checks (`check #1`, `#2`, ...)
+    fn import_legacy(&self, path: &Path) -> Result<usize> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        let mut incidents: Vec<Incident> = Vec::new();
+        let mut last_check = 0;
+        for line in content.lines() {
+            let Ok(log) = serde_json::from_str::<crate::health::IncidentLog>(line) else {
+                continue;
+            };
+            let Ok(at) = DateTime::parse_from_rfc3339(&log.timestamp) else {
+                continue;
+            };
+            let at = at.with_timezone(&Utc);
+            let check = log
+                .cause
+                .rsplit_once('#')
+                .and_then(|(_, n)| n.trim_end_matches(')').parse().ok())
+                .unwrap_or(1);
+            match incidents.last_mut() {
+                Some(incident) if check > last_check => incident.checks_failed += 1,
+                _ => {
+                    let mut incident = Incident::new(at, "unresponsive", "health-check");
+                    incident.timeline.push(TimelineEvent {
+                        at,
+                        event: format!("Imported from {}", LEGACY_LOG),
+                    });
+                    incident.checks_failed = 1;
+                    incidents.push(incident);
+                }
+            }
+            let incident = incidents.last_mut().expect("just pushed");
+            incident.ended_at = Some(at);
+            incident.timeline.push(TimelineEvent {
+                at,
+                event: log.cause,
+            });
+            last_check = check;
+        }
+        for incident in &mut incidents {
+            self.insert(incident)?;
+        }
+        Ok(incidents.len())
+    }
+
+    /// Load a full incident by ID
+    pub fn get(&self, id: &str) -> Result<Incident> {
+        let path = self.incident_path(id);
+        let content =
+            fs::read_to_string(&path).with_context(|| format!("incident '{}' not found", id))?;
+        serde_json::from_str(&content).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// The most recent incident that is still open
+    pub fn current(&self) -> Result<Option<Incident>> {
+        match self
+            .index()?
+            .into_iter()
+            .rev()
+            .find(|s| s.ended_at.is_none())
+        {
+            Some(s) => self.get(&s.id).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// The last `n` incidents, newest first
+    pub fn recent(&self, n: usize) -> Result<Vec<IncidentSummary>> {
+        Ok(self.index()?.into_iter().rev().take(n).collect())
+    }
+
+    /// All incident summaries, oldest first
+    pub fn index(&self) -> Result<Vec<IncidentSummary>> {
+        let path = self.index_path();
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let content = fs::read_to_string(&path)?;
+        serde_json::from_str(&content).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// Summaries of incidents that started within the window
+    pub fn since(&self, since: DateTime<Utc>) -> Result<Vec<IncidentSummary>> {
+        Ok(self
+            .index()?
+            .into_iter()
+            .filter(|s| s.started_at >= since)
+            .collect())
+    }
+}
+
+/// Write via a temp file + rename so readers never see a half-written file
+fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).with_context(|| format!("writing {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("writing {}", path.display()))?;
+    Ok(())
+}
+
+// ─── Lifecycle ─────────────────────────────────────────────────────
+//
+// The store is the source of truth for the open incident, so the health
+// loop, Telegram commands and a separate `rescueclaw restore` process all
+// update the same record. Failures to record are logged; they must never
+// get in the way of recovery itself.
+
+/// A health check failed: open an incident on the first failure of an
+/// outage, otherwise count the check against the open one
+pub fn check_failed(cfg: &Config, trigger: &str) -> Option<Incident> {
+    let store = IncidentStore::open(cfg);
+    let (mut incident, new) = match store.current() {
+        Ok(Some(open)) => (open, false),
+        Ok(None) => {
+            let mut fresh = Incident::new(Utc::now(), "unresponsive", trigger);
+            fresh.note(format!("Opened: agent unresponsive ({})", trigger));
+            (fresh, true)
+        }
+        Err(e) => {
+            tracing::warn!("Could not read incidents: {}", e);
+            return None;
+        }
+    };
+
+    incident.checks_failed += 1;
+    if incident.checks_failed == 1 {
+        incident.note("Health check failed");
+    }
+    persist(&store, &mut incident, new);
+    Some(incident)
+}
+
+/// A health check passed: close the open incident, if any. It counts as
+/// self-recovered unless a restore was already attempted.
+pub fn check_passed(cfg: &Config) -> Option<Incident> {
+    let store = IncidentStore::open(cfg);
+    let mut incident = store.current().ok().flatten()?;
+    let recovery = incident
+        .recovery
+        .clone()
+        .unwrap_or_else(|| "self-recovered".to_string());
+    incident.note(format!(
+        "Agent responding again after {} failed check(s)",
+        incident.checks_failed
+    ));
+    incident.close(recovery);
+    persist(&store, &mut incident, false);
+    Some(incident)
+}
+
+/// Note a recovery attempt on the open incident
+pub fn note(cfg: &Config, event: &str) {
+    let store = IncidentStore::open(cfg);
+    if let Ok(Some(mut incident)) = store.current() {
+        incident.note(event);
+        persist(&store, &mut incident, false);
+    }
+}
+
+/// The health loop took an escalation step. `recovery`, when given, becomes
+/// the open incident's recovery so far (and how it closes if the agent
+/// comes back).
+pub fn record_escalation(cfg: &Config, record: EscalationRecord, recovery: Option<&str>) {
+    let store = IncidentStore::open(cfg);
+    if let Ok(Some(mut incident)) = store.current() {
+        incident.note(format!(
+            "Escalation step {}: {} — {}",
+            incident.escalation.len() + 1,
+            record.step,
+            record.outcome
+        ));
+        incident.escalation.push(record);
+        if let Some(recovery) = recovery {
+            incident.recovery = Some(recovery.to_string());
+        }
+        persist(&store, &mut incident, false);
+    }
+}
+
+/// A restore finished. It is attached to the open incident, or opens and
+/// records a new one (e.g. a manual restore with no outage being tracked).
+/// The incident is closed once the agent is back online; otherwise it stays
+/// open until a health check passes.
+pub fn record_restore(
+    cfg: &Config,
+    category: Option<&str>,
+    trigger: &str,
+    recovery: &str,
+    outcome: &RestoreOutcome,
+    analysis_summary: Option<String>,
+) -> Incident {
+    let store = IncidentStore::open(cfg);
+    let (mut incident, new) = match store.current().ok().flatten() {
+        Some(open) => (open, false),
+        None => (Incident::new(Utc::now(), "manual", trigger), true),
+    };
+    if let Some(category) = category {
+        incident.category = category.to_string();
+    }
+
+    incident.snapshot = Some(outcome.snapshot_id.clone());
+    incident.restored_files = outcome.restored_files.clone();
+    if analysis_summary.is_some() {
+        incident.analysis_summary = analysis_summary;
+    }
+    incident.note(format!(
+        "{} from {} ({} file(s) rolled back)",
+        recovery,
+        outcome.snapshot_id,
+        outcome.restored_files.len()
+    ));
+
+    if outcome.agent_online {
+        incident.close(recovery);
+    } else {
+        incident.recovery = Some(recovery.to_string());
+        incident.note("Gateway not responding after restore");
+    }
+    persist(&store, &mut incident, new);
+    incident
+}
+
+/// Record a preventive intervention (e.g. by the config guard) as its own
+/// incident, closed straight away with `recovery`
+pub fn record_intervention(
+    cfg: &Config,
+    category: &str,
+    trigger: &str,
+    recovery: &str,
+    details: &[String],
+    snapshot: Option<String>,
+) -> Incident {
+    let store = IncidentStore::open(cfg);
+    let mut incident = Incident::new(Utc::now(), category, trigger);
+    for detail in details {
+        incident.note(detail.clone());
+    }
+    incident.snapshot = snapshot;
+    incident.close(recovery);
+    persist(&store, &mut incident, true);
+    incident
+}
+
+fn persist(store: &IncidentStore, incident: &mut Incident, new: bool) {
+    let result = if new {
+        store.insert(incident)
+    } else {
+        store.save(incident)
+    };
+    if let Err(e) = result {
+        tracing::warn!("Could not record incident {}: {}", incident.id, e);
+    }
+}
+
+/// Aggregates over incidents in a time window
+#[derive(Debug, Clone)]
+pub struct Insights {
+    pub window_days: i64,
+    pub total: usize,
+    pub open: usize,
+    /// (category, count), most frequent first
+    pub categories: Vec<(String, usize)>,
+    /// Mean time to recovery over closed incidents
+    pub mttr: Option<Duration>,
+    /// (file, times restored), most frequent first
+    pub most_restored_files: Vec<(String, usize)>,
+    /// Triggers seen more than once, (trigger, count)
+    pub recurring_triggers: Vec<(String, usize)>,
+}
+
+/// How many entries each ranked list shows
+const TOP_N: usize = 5;
+
+impl Insights {
+    pub fn from_incidents(incidents: &[IncidentSummary], window_days: i64) -> Self {
+        let closed: Vec<Duration> = incidents
+            .iter()
+            .filter_map(|i| i.ended_at.map(|end| end - i.started_at))
+            .collect();
+        let mttr = (!closed.is_empty())
+            .then(|| closed.iter().fold(Duration::zero(), |a, d| a + *d) / closed.len() as i32);
+
+        let mut recurring = ranked(incidents.iter().map(|i| i.trigger.as_str()));
+        recurring.retain(|(_, n)| *n > 1);
+
+        Self {
+            window_days,
+            total: incidents.len(),
+            open: incidents.iter().filter(|i| i.ended_at.is_none()).count(),
+            categories: ranked(incidents.iter().map(|i| i.category.as_str())),
+            mttr,
+            most_restored_files: ranked(
+                incidents
+                    .iter()
+                    .flat_map(|i| i.restored_files.iter().map(String::as_str)),
+            ),
+            recurring_triggers: recurring,
+        }
+    }
+}
+
+/// Count occurrences, most frequent first (ties alphabetical), top N
+fn ranked<'a>(items: impl Iterator<Item = &'a str>) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for item in items {
+        *counts.entry(item).or_default() += 1;
+    }
+    let mut out: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(k, n)| (k.to_string(), n))
+        .collect();
+    out.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    out.truncate(TOP_N);
+    out
+}
+
+/// Short human duration: 45s, 12m 5s, 3h 20m, 2d 4h
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.num_seconds().max(0);
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m {}s", s / 60, s % 60),
+        s if s < 86400 => format!("{}h {}m", s / 3600, (s % 3600) / 60),
+        s => format!("{}d {}h", s / 86400, (s % 86400) / 3600),
+    }
+}
+
+/// Suggestion for the dominant incident category
+fn suggestion(category: &str) -> Option<&'static str> {
+    Some(match category {
+        "config-syntax" => {
+            "Validate config edits before applying them (checkpoint before `config.apply`)."
+        }
+        "port-in-use" => {
+            "A stale gateway keeps holding the port; make sure restarts stop the old process first."
+        }
+        "provider-auth" => "Provider keys keep failing — check key rotation and billing limits.",
+        "out-of-memory" => "Raise the gateway's memory limit or trim session history.",
+        "missing-module" => {
+            "Skill installs keep breaking dependencies; checkpoint before `clawhub install`."
+        }
+        "disk-full" => "Lower `maxSnapshots` or move backups/sessions to a bigger disk.",
+        _ => return None,
+    })
+}
+
+impl fmt::Display for Insights {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "🔍 Incident insights (last {} days)\n", self.window_days)?;
+
+        if self.total == 0 {
+            return writeln!(f, "✅ No incidents recorded.");
+        }
+
+        writeln!(f, "Incidents:   {} ({} still open)", self.total, self.open)?;
+        writeln!(
+            f,
+            "MTTR:        {}",
+            self.mttr.map_or("n/a".to_string(), format_duration)
+        )?;
+
+        writeln!(f, "\nBy category:")?;
+        for (category, n) in &self.categories {
+            writeln!(f, "  • {} — {} ({}%)", category, n, n * 100 / self.total)?;
+        }
+
+        if !self.most_restored_files.is_empty() {
+            writeln!(f, "\nMost-restored files:")?;
+            for (file, n) in &self.most_restored_files {
+                writeln!(f, "  • {} — {}×", file, n)?;
+            }
+        }
+
+        if !self.recurring_triggers.is_empty() {
+            writeln!(f, "\nRecurring triggers:")?;
+            for (trigger, n) in &self.recurring_triggers {
+                writeln!(f, "  • {} — {}×", trigger, n)?;
+            }
+        }
+
+        if let Some((top, n)) = self.categories.first() {
+            if self.total >= 3 && n * 100 / self.total >= 40 {
+                writeln!(
+                    f,
+                    "\nPattern: {} failures are {}% of incidents.",
+                    top,
+                    n * 100 / self.total
+                )?;
+                if let Some(s) = suggestion(top) {
+                    writeln!(f, "   Suggestion: {}", s)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Longest window `insights` accepts: ten years
+pub const MAX_INSIGHTS_DAYS: i64 = 3650;
+
+/// Aggregate the last `days` days of incidents
+pub fn insights(cfg: &Config, days: i64) -> Result<Insights> {
+    if !(1..=MAX_INSIGHTS_DAYS).contains(&days) {
+        anyhow::bail!("days must be between 1 and {}", MAX_INSIGHTS_DAYS);
+    }
+    let since = Utc::now() - Duration::days(days);
+    let incidents = IncidentStore::open(cfg).since(since)?;
+    Ok(Insights::from_incidents(&incidents, days))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(
+        category: &str,
+        trigger: &str,
+        ttr_secs: Option<i64>,
+        files: &[&str],
+    ) -> IncidentSummary {
+        let start = Utc::now() - Duration::hours(1);
+        IncidentSummary {
+            id: format!("inc-{}", category),
+            started_at: start,
+            ended_at: ttr_secs.map(|s| start + Duration::seconds(s)),
+            category: category.to_string(),
+            trigger: trigger.to_string(),
+            recovery: ttr_secs.map(|_| "auto-restore".to_string()),
+            snapshot: None,
+            restored_files: files.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_store_save_update_and_index() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = IncidentStore::at(temp.path());
+
+        let start = Utc::now();
+        let mut a = Incident::new(start, "unresponsive", "health-check");
+        store.insert(&mut a).unwrap();
+        // Same second → same ID, must not overwrite
+        let mut b = Incident::new(start, "port-in-use", "manual");
+        store.insert(&mut b).unwrap();
+        assert_ne!(a.id, b.id);
+
+        a.ended_at = Some(a.started_at + Duration::seconds(30));
+        a.recovery = Some("auto-restore".to_string());
+        store.save(&a).unwrap();
+
+        let index = store.index().unwrap();
+        assert_eq!(index.len(), 2);
+        let loaded = index.iter().find(|s| s.id == a.id).unwrap();
+        assert_eq!(loaded.recovery.as_deref(), Some("auto-restore"));
+        assert!(store.incident_path(&b.id).exists());
+    }
+
+    #[test]
+    fn test_one_incident_per_outage() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut cfg = Config::default();
+        cfg.backup.path = temp.path().to_path_buf();
+        let store = IncidentStore::open(&cfg);
+
+        // First outage: three failed checks, then recovers on its own
+        for _ in 0..3 {
+            check_failed(&cfg, "health-check").unwrap();
+        }
+        let closed = check_passed(&cfg).unwrap();
+        assert_eq!(closed.checks_failed, 3);
+        assert_eq!(closed.recovery.as_deref(), Some("self-recovered"));
+        assert!(check_passed(&cfg).is_none());
+
+        // Second outage: fixed by a restore while open
+        check_failed(&cfg, "health-check").unwrap();
+        let outcome = RestoreOutcome {
+            snapshot_id: "20260101-000000".to_string(),
+            restored_files: vec!["config/openclaw.json".to_string()],
+            agent_online: true,
+        };
+        let restored = record_restore(
+            &cfg,
+            Some("config-syntax"),
+            "health-check",
+            "auto-restore",
+            &outcome,
+            None,
+        );
+        assert_eq!(restored.recovery.as_deref(), Some("auto-restore"));
+        assert_eq!(restored.category, "config-syntax");
+        assert!(restored.ended_at.is_some());
+
+        let index = store.index().unwrap();
+        assert_eq!(index.len(), 2);
+        assert!(index.iter().all(|i| i.ended_at.is_some()));
+        let timeline = store.get(&restored.id).unwrap().timeline;
+        assert!(timeline.len() >= 3);
+        assert!(timeline
+            .last()
+            .unwrap()
+            .event
+            .contains("Closed: auto-restore"));
+    }
+
+    #[test]
+    fn test_imports_legacy_incident_log_once() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut cfg = Config::default();
+        cfg.backup.path = temp.path().to_path_buf();
+        fs::write(
+            temp.path().join(LEGACY_LOG),
+            include_str!("../../tests/fixtures/incidents.jsonl"),
+        )
+        .unwrap();
+
+        let store = IncidentStore::open(&cfg);
+        let index = store.index().unwrap();
+        assert_eq!(index.len(), 2);
+        assert_eq!(index[0].id, "inc-20260110-080000");
+        assert_eq!(index[0].duration(), Duration::minutes(10));
+        assert!(index.iter().all(|s| s.ended_at.is_some()));
+        assert_eq!(store.get(&index[0].id).unwrap().checks_failed, 3);
+        assert_eq!(store.get(&index[1].id).unwrap().checks_failed, 2);
+
+        // Shows up in insights, and is not imported twice
+        let insights = insights(&cfg, MAX_INSIGHTS_DAYS).unwrap();
+        assert_eq!(insights.total, 2);
+        assert_eq!(IncidentStore::open(&cfg).index().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_restore_without_online_agent_stays_open() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut cfg = Config::default();
+        cfg.backup.path = temp.path().to_path_buf();
+
+        let outcome = RestoreOutcome {
+            snapshot_id: "20260101-000000".to_string(),
+            restored_files: vec![],
+            agent_online: false,
+        };
+        let incident = record_restore(&cfg, None, "manual (cli)", "manual-restore", &outcome, None);
+        assert!(incident.ended_at.is_none());
+
+        // The next passing check credits the restore, not self-recovery
+        let closed = check_passed(&cfg).unwrap();
+        assert_eq!(closed.id, incident.id);
+        assert_eq!(closed.recovery.as_deref(), Some("manual-restore"));
+    }
+
+    #[test]
+    fn test_insights_aggregates() {
+        let incidents = vec![
+            summary(
+                "config-syntax",
+                "checkpoint: config.apply",
+                Some(30),
+                &["config/openclaw.json"],
+            ),
+            summary(
+                "config-syntax",
+                "checkpoint: config.apply",
+                Some(90),
+                &["config/openclaw.json", "workspace/SOUL.md"],
+            ),
+            summary("port-in-use", "health-check", None, &[]),
+        ];
+        let insights = Insights::from_incidents(&incidents, 30);
+
+        assert_eq!(insights.total, 3);
+        assert_eq!(insights.open, 1);
+        assert_eq!(insights.mttr.unwrap().num_seconds(), 60);
+        assert_eq!(insights.categories[0], ("config-syntax".to_string(), 2));
+        assert_eq!(
+            insights.most_restored_files[0],
+            ("config/openclaw.json".to_string(), 2)
+        );
+        assert_eq!(
+            insights.recurring_triggers,
+            vec![("checkpoint: config.apply".to_string(), 2)]
+        );
+
+        let text = insights.to_string();
+        assert!(text.contains("MTTR:        1m 0s"));
+        assert!(text.contains("Pattern: config-syntax failures are 66% of incidents."));
+    }
+
+    #[test]
+    fn test_insights_rejects_huge_windows() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut cfg = Config::default();
+        cfg.backup.path = temp.path().to_path_buf();
+
+        assert_eq!(insights(&cfg, MAX_INSIGHTS_DAYS).unwrap().total, 0);
+        for days in [0, MAX_INSIGHTS_DAYS + 1, i64::MAX] {
+            let err = insights(&cfg, days).unwrap_err().to_string();
+            assert!(err.contains("between 1 and 3650"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::seconds(45)), "45s");
+        assert_eq!(format_duration(Duration::seconds(725)), "12m 5s");
+        assert_eq!(format_duration(Duration::seconds(12_000)), "3h 20m");
+        assert_eq!(format_duration(Duration::seconds(187_200)), "2d 4h");
+    }
+}
@@ -0,0 +1,504 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local, Utc};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, LiveConfig};
+
+/// How long a burst of events must be quiet before the journal records it
+const QUIET: std::time::Duration = std::time::Duration::from_secs(1);
+/// Longest a burst is held before it is written anyway
+const MAX_BATCH_WAIT: std::time::Duration = std::time::Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChangeKind::Created => write!(f, "+"),
+            ChangeKind::Modified => write!(f, "~"),
+            ChangeKind::Deleted => write!(f, "-"),
+        }
+    }
+}
+
+/// One journal entry. Paths are `workspace/...` or `config/...`, as in
+/// snapshot archives.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeEvent {
+    pub at: DateTime<Utc>,
+    pub kind: ChangeKind,
+    pub path: String,
+}
+
+impl fmt::Display for ChangeEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}  {} {}",
+            self.at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+            self.kind,
+            self.path
+        )
+    }
+}
+
+/// Append-only change log (JSON lines) under `<backup.path>/journal/`
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub fn open(cfg: &Config) -> Self {
+        Self::at(cfg.backup.path.join("journal").join("changes.jsonl"))
+    }
+
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn append(&self, events: &[ChangeEvent]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("opening {}", self.path.display()))?;
+        for event in events {
+            writeln!(file, "{}", serde_json::to_string(event)?)?;
+        }
+        Ok(())
+    }
+
+    /// Changes at or after `since`, oldest first
+    pub fn since(&self, since: DateTime<Utc>) -> Result<Vec<ChangeEvent>> {
+        Ok(self.read()?.into_iter().filter(|e| e.at >= since).collect())
+    }
+
+    /// Drop entries older than `before`
+    pub fn prune(&self, before: DateTime<Utc>) -> Result<()> {
+        let kept = self.since(before)?;
+        let mut data = Vec::new();
+        for event in &kept {
+            data.extend(serde_json::to_vec(event)?);
+            data.push(b'\n');
+        }
+        let tmp = self.path.with_extension("jsonl.tmp");
+        fs::write(&tmp, data)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    fn read(&self) -> Result<Vec<ChangeEvent>> {
+        if !self.path.exists() {
+            return Ok(vec![]);
+        }
+        let file = fs::File::open(&self.path)?;
+        // Skip lines that don't parse (e.g. a write cut short by a crash)
+        Ok(BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect())
+    }
+}
+
+/// Watches the workspace and config dirs and yields settled batches of changes
+pub struct ChangeWatcher {
+    _watcher: RecommendedWatcher,
+    rx: tokio::sync::mpsc::UnboundedReceiver<notify::Event>,
+    /// (root, archive prefix), longest root first
+    roots: Vec<(PathBuf, &'static str)>,
+    ignore: Vec<PathBuf>,
+    ignore_names: Vec<String>,
+}
+
+impl ChangeWatcher {
+    pub fn start(cfg: &Config) -> Result<Self> {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+                if let Ok(event) = res {
+                    let _ = tx.send(event);
+                }
+            })?;
+
+        let mut roots: Vec<(PathBuf, &'static str)> = [
+            (cfg.openclaw.workspace.clone(), "workspace"),
+            (cfg.openclaw.config_path.clone(), "config"),
+        ]
+        .into_iter()
+        .filter(|(root, _)| root.is_dir())
+        .collect();
+        if roots.is_empty() {
+            anyhow::bail!("neither the workspace nor the config dir exists");
+        }
+        for (root, _) in &roots {
+            watcher
+                .watch(root, RecursiveMode::Recursive)
+                .with_context(|| format!("watching {}", root.display()))?;
+        }
+        // A workspace nested in the config dir belongs to the workspace
+        roots.sort_by_key(|(root, _)| std::cmp::Reverse(root.as_os_str().len()));
+
+        Ok(Self {
+            _watcher: watcher,
+            rx,
+            roots,
+            // Our own backups and journal must not feed back into it
+            ignore: vec![cfg.backup.path.clone()],
+            ignore_names: cfg.journal.ignore.clone(),
+        })
+    }
+
+    /// Wait for changes, then until none has happened for `quiet`, but no
+    /// longer than `max_wait` after the first. Ignored paths don't count.
+    /// Returns `None` when the watcher has stopped.
+    pub async fn next_batch(
+        &mut self,
+        quiet: std::time::Duration,
+        max_wait: std::time::Duration,
+    ) -> Option<Vec<ChangeEvent>> {
+        let mut batch = Vec::new();
+        while batch.is_empty() {
+            let event = self.rx.recv().await?;
+            batch.extend(self.classify(&event));
+        }
+        let deadline = tokio::time::Instant::now() + max_wait;
+        let mut settled = tokio::time::Instant::now() + quiet;
+        while let Ok(Some(event)) =
+            tokio::time::timeout_at(settled.min(deadline), self.rx.recv()).await
+        {
+            let changes = self.classify(&event);
+            if !changes.is_empty() {
+                batch.extend(changes);
+                settled = tokio::time::Instant::now() + quiet;
+            }
+        }
+        Some(coalesce(batch))
+    }
+
+    fn classify(&self, event: &notify::Event) -> Vec<ChangeEvent> {
+        let kinds: Vec<(ChangeKind, &PathBuf)> = match event.kind {
+            EventKind::Create(_) => event
+                .paths
+                .iter()
+                .map(|p| (ChangeKind::Created, p))
+                .collect(),
+            EventKind::Remove(_) => event
+                .paths
+                .iter()
+                .map(|p| (ChangeKind::Deleted, p))
+                .collect(),
+            EventKind::Modify(ModifyKind::Name(mode)) => match (mode, event.paths.as_slice()) {
+                (RenameMode::Both, [from, to]) => {
+                    vec![(ChangeKind::Deleted, from), (ChangeKind::Created, to)]
+                }
+                (RenameMode::From, paths) => {
+                    paths.iter().map(|p| (ChangeKind::Deleted, p)).collect()
+                }
+                (RenameMode::To, paths) => paths.iter().map(|p| (ChangeKind::Created, p)).collect(),
+                (_, paths) => paths
+                    .iter()
+                    .map(|p| {
+                        let kind = if p.exists() {
+                            ChangeKind::Created
+                        } else {
+                            ChangeKind::Deleted
+                        };
+                        (kind, p)
+                    })
+                    .collect(),
+            },
+            EventKind::Modify(ModifyKind::Metadata(_)) => vec![],
+            EventKind::Modify(_) => event
+                .paths
+                .iter()
+                .map(|p| (ChangeKind::Modified, p))
+                .collect(),
+            _ => vec![],
+        };
+
+        let now = Utc::now();
+        kinds
+            .into_iter()
+            .filter_map(|(kind, path)| {
+                Some(ChangeEvent {
+                    at: now,
+                    kind,
+                    path: self.relative(path)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Archive-style path, or `None` for paths we don't journal
+    fn relative(&self, path: &Path) -> Option<String> {
+        if self.ignore.iter().any(|i| path.starts_with(i)) {
+            return None;
+        }
+        let (root, prefix) = self.roots.iter().find(|(root, _)| path.starts_with(root))?;
+        let rel = path.strip_prefix(root).ok()?;
+        if rel.as_os_str().is_empty()
+            || rel.components().any(|c| {
+                let name = c.as_os_str().to_string_lossy();
+                self.ignore_names.iter().any(|i| match i.strip_prefix('*') {
+                    Some(suffix) => name.ends_with(suffix),
+                    None => name == i.as_str(),
+                })
+            })
+        {
+            return None;
+        }
+        Some(format!("{}/{}", prefix, rel.display()))
+    }
+}
+
+/// Collapse a burst into one event per path. An editor's save-by-rename
+/// (delete + create) becomes a modify; a temp file created and deleted
+/// within the burst disappears.
+fn coalesce(events: Vec<ChangeEvent>) -> Vec<ChangeEvent> {
+    let mut out: Vec<ChangeEvent> = Vec::new();
+    for event in events {
+        let Some(i) = out.iter().position(|e| e.path == event.path) else {
+            out.push(event);
+            continue;
+        };
+        let merged = match (out[i].kind, event.kind) {
+            (ChangeKind::Created, ChangeKind::Deleted) => None,
+            (ChangeKind::Created, _) => Some(ChangeKind::Created),
+            (ChangeKind::Deleted, ChangeKind::Created) => Some(ChangeKind::Modified),
+            (_, kind) => Some(kind),
+        };
+        match merged {
+            Some(kind) => {
+                out[i].kind = kind;
+                out[i].at = event.at;
+            }
+            None => {
+                out.remove(i);
+            }
+        }
+    }
+    out
+}
+
+/// Record workspace and config changes for as long as the daemon runs. The
+/// watch is set up again when a reload changes what it covers.
+pub async fn journal_loop(live: &LiveConfig) -> Result<()> {
+    let mut reloads = live.clone();
+    let mut pruned_at: Option<DateTime<Utc>> = None;
+    loop {
+        tokio::select! {
+            r = record_changes(live, &mut pruned_at) => return r,
+            _ = reloads.changed_in(|c| {
+                (
+                    c.journal.enabled,
+                    c.journal.ignore.clone(),
+                    c.openclaw.workspace.clone(),
+                    c.openclaw.config_path.clone(),
+                    c.backup.path.clone(),
+                )
+            }) => tracing::info!("Change journal restarting for the new config"),
+        }
+    }
+}
+
+async fn record_changes(live: &LiveConfig, pruned_at: &mut Option<DateTime<Utc>>) -> Result<()> {
+    let cfg = live.current();
+    let mut watcher = match cfg.journal.enabled.then(|| ChangeWatcher::start(&cfg)) {
+        Some(Ok(w)) => w,
+        Some(Err(e)) => {
+            tracing::error!("Change journal disabled: {:#}", e);
+            idle().await
+        }
+        None => idle().await,
+    };
+
+    while let Some(batch) = watcher.next_batch(QUIET, MAX_BATCH_WAIT).await {
+        if batch.is_empty() {
+            continue;
+        }
+        let cfg = live.current();
+        let journal = Journal::open(&cfg);
+        if let Err(e) = journal.append(&batch) {
+            tracing::warn!("Could not write change journal: {}", e);
+        }
+
+        if cfg.journal.snapshot_on_protected_change {
+            let protected: Vec<&str> = batch
+                .iter()
+                .filter(|e| cfg.journal.protected_files.contains(&e.path))
+                .map(|e| e.path.as_str())
+                .collect();
+            if !protected.is_empty() {
+                match crate::backup::take_snapshot(&cfg) {
+                    Ok(snap) => tracing::info!(
+                        "Protected file changed ({}): snapshot {}",
+                        protected.join(", "),
+                        snap.id
+                    ),
+                    Err(e) => tracing::error!("Protected-file snapshot failed: {}", e),
+                }
+            }
+        }
+
+        let now = Utc::now();
+        if pruned_at.is_none_or(|t| now - t > Duration::days(1)) {
+            if let Err(e) = journal.prune(cfg.journal.retention.before(now)) {
+                tracing::warn!("Could not prune change journal: {}", e);
+            }
+            *pruned_at = Some(now);
+        }
+    }
+
+    anyhow::bail!("Change watcher stopped")
+}
+
+pub(crate) async fn idle() -> ! {
+    // Sleep forever so tokio::select doesn't exit
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(3600)).await;
+    }
+}
+
+/// Changes in a time window, for the CLI and analysis evidence
+pub fn changes_since(cfg: &Config, since: DateTime<Utc>) -> Result<Vec<ChangeEvent>> {
+    Journal::open(cfg).since(since)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: ChangeKind, path: &str) -> ChangeEvent {
+        ChangeEvent {
+            at: Utc::now(),
+            kind,
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_coalesce() {
+        let batch = coalesce(vec![
+            event(ChangeKind::Modified, "workspace/SOUL.md"),
+            event(ChangeKind::Created, "workspace/.SOUL.md.swp"),
+            event(ChangeKind::Deleted, "config/openclaw.json"),
+            event(ChangeKind::Modified, "workspace/SOUL.md"),
+            event(ChangeKind::Deleted, "workspace/.SOUL.md.swp"),
+            event(ChangeKind::Created, "config/openclaw.json"),
+            event(ChangeKind::Created, "workspace/memory/new.md"),
+            event(ChangeKind::Modified, "workspace/memory/new.md"),
+        ]);
+        let summary: Vec<(ChangeKind, &str)> =
+            batch.iter().map(|e| (e.kind, e.path.as_str())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ChangeKind::Modified, "workspace/SOUL.md"),
+                (ChangeKind::Modified, "config/openclaw.json"),
+                (ChangeKind::Created, "workspace/memory/new.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_journal_since_and_prune() {
+        let temp = tempfile::tempdir().unwrap();
+        let journal = Journal::at(temp.path().join("changes.jsonl"));
+        let mut old = event(ChangeKind::Deleted, "workspace/old.md");
+        old.at = Utc::now() - Duration::days(10);
+        journal
+            .append(&[old, event(ChangeKind::Created, "workspace/new.md")])
+            .unwrap();
+
+        let recent = journal.since(Utc::now() - Duration::hours(2)).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].path, "workspace/new.md");
+
+        journal.prune(Utc::now() - Duration::days(7)).unwrap();
+        assert_eq!(journal.since(DateTime::<Utc>::MIN_UTC).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_watcher_records_workspace_changes() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut cfg = Config::default();
+        cfg.backup.path = temp.path().join("workspace/backups");
+        cfg.openclaw.workspace = temp.path().join("workspace");
+        cfg.openclaw.config_path = temp.path().join("openclaw");
+        fs::create_dir_all(cfg.openclaw.workspace.join(".git")).unwrap();
+        let sessions = cfg.openclaw.config_path.join("agents/main/sessions");
+        fs::create_dir_all(&sessions).unwrap();
+        fs::create_dir_all(&cfg.backup.path).unwrap();
+
+        let mut watcher = ChangeWatcher::start(&cfg).unwrap();
+        fs::write(cfg.openclaw.workspace.join("SOUL.md"), "# Soul").unwrap();
+        fs::write(cfg.openclaw.workspace.join(".git/HEAD"), "ref").unwrap();
+        fs::write(cfg.openclaw.config_path.join("gateway.log"), "up").unwrap();
+        fs::write(sessions.join("s1.jsonl"), "{}").unwrap();
+        fs::write(cfg.backup.path.join("backup.tar.gz"), "x").unwrap();
+        fs::write(cfg.openclaw.config_path.join("openclaw.json"), "{}").unwrap();
+
+        let batch = tokio::time::timeout(
+            std::time::Duration::from_secs(10),
+            watcher.next_batch(
+                std::time::Duration::from_millis(300),
+                std::time::Duration::from_secs(60),
+            ),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let mut paths: Vec<&str> = batch.iter().map(|e| e.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(paths, vec!["config/openclaw.json", "workspace/SOUL.md"]);
+        assert!(batch.iter().all(|e| e.kind == ChangeKind::Created));
+
+        // A busy log doesn't hold the batch open
+        let busy = |path: PathBuf| {
+            std::thread::spawn(move || {
+                for i in 0..30 {
+                    fs::write(&path, format!("line {}", i)).unwrap();
+                    std::thread::sleep(std::time::Duration::from_millis(50));
+                }
+            })
+        };
+        let quiet = std::time::Duration::from_millis(300);
+        let log = busy(cfg.openclaw.config_path.join("gateway.log"));
+        fs::write(cfg.openclaw.workspace.join("AGENTS.md"), "# Agents").unwrap();
+        let started = std::time::Instant::now();
+        let batch = watcher
+            .next_batch(quiet, std::time::Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(batch[0].path, "workspace/AGENTS.md");
+        assert!(started.elapsed() < std::time::Duration::from_millis(1000));
+        log.join().unwrap();
+
+        // Constant real changes are cut off at the ceiling
+        let soul = busy(cfg.openclaw.workspace.join("SOUL.md"));
+        let started = std::time::Instant::now();
+        let batch = watcher
+            .next_batch(quiet, std::time::Duration::from_millis(500))
+            .await
+            .unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_millis(1000));
+        assert!(batch.iter().any(|e| e.path == "workspace/SOUL.md"));
+        soul.join().unwrap();
+    }
+}
@@ -2,7 +2,14 @@
 pub mod analysis;
 pub mod backup;
 pub mod config;
+pub mod gateway;
+pub mod guard;
 pub mod health;
+pub mod incidents;
+pub mod journal;
+pub mod redact;
 pub mod restore;
+pub mod schedule;
+pub mod service;
 pub mod telegram;
 pub mod validate;
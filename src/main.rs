@@ -1,8 +1,15 @@
 mod analysis;
 mod backup;
 mod config;
+mod gateway;
+mod guard;
 mod health;
+mod incidents;
+mod journal;
+mod redact;
 mod restore;
+mod schedule;
+mod service;
 mod telegram;
 mod validate;
 
@@ -14,14 +21,17 @@ use clap::{Parser, Subcommand};
 #[command(about = "Your AI agent's always-on safety net 🛟")]
 #[command(version)]
 struct Cli {
+    /// Config file to use instead of the standard locations (.json, .toml or .yaml)
+    #[arg(long, global = true)]
+    config: Option<std::path::PathBuf>,
     #[command(subcommand)]
     command: Commands,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Interactive setup wizard
-    Setup,
+    /// Setup wizard, or unattended setup with --non-interactive
+    Setup(config::setup::SetupOptions),
     /// Start the watchdog daemon
     Start,
     /// Show status of agent and watchdog
@@ -41,14 +51,125 @@ enum Commands {
         #[arg(long)]
         dry_run: bool,
     },
-    /// Show recent incident logs
+    /// Show recent incidents, or one incident's timeline
     Logs {
+        /// Incident ID to show in full
+        id: Option<String>,
         /// Number of entries to show
         #[arg(short, default_value = "10")]
         n: usize,
     },
-    /// Uninstall watchdog service
+    /// Analyze the current state with the agent's LLM and save an incident report
+    Analyze {
+        /// Use only the built-in rule-based analyzer (no LLM call)
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Validate the OpenClaw config and workspace (live by default)
+    Validate {
+        /// Validate a backup snapshot instead of the live files
+        #[arg(long, conflicts_with = "live")]
+        snapshot: Option<String>,
+        /// Validate the live config and workspace (the default)
+        #[arg(long)]
+        live: bool,
+        /// List rule IDs and their configured levels
+        #[arg(long)]
+        list_rules: bool,
+    },
+    /// Preview what secret redaction would mask in a file
+    RedactTest {
+        /// File to scan (e.g. gateway.log or openclaw.json)
+        file: std::path::PathBuf,
+        /// Print the full redacted text, not just the matches
+        #[arg(long)]
+        show: bool,
+    },
+    /// Show workspace and config changes recorded by the daemon
+    Changes {
+        /// How far back to look, e.g. 30m, 2h, 1d
+        #[arg(long, default_value = "24h")]
+        since: config::Duration,
+    },
+    /// Show the backup schedule, quiet hours and next planned runs
+    Schedule {
+        /// Number of upcoming runs to show
+        #[arg(short, default_value = "10")]
+        n: usize,
+    },
+    /// Show incident patterns: categories, MTTR, most-restored files
+    Insights {
+        /// Time window in days (at most 3650)
+        #[arg(long, default_value = "30", value_parser = clap::value_parser!(i64).range(1..=incidents::MAX_INSIGHTS_DAYS))]
+        days: i64,
+    },
+    /// Manage the Telegram users allowed to control the bot
+    Users {
+        #[command(subcommand)]
+        action: UsersAction,
+    },
+    /// Check or print rescueclaw's own config
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Install, remove or inspect the watchdog service
+    Service {
+        #[command(subcommand)]
+        action: ServiceAction,
+    },
+    /// Remove the watchdog service; --purge also deletes backups and config
+    Uninstall(service::uninstall::UninstallOptions),
+}
+
+#[derive(Subcommand)]
+enum ServiceAction {
+    /// Install and start the service (systemd, systemd --user, OpenRC or runit)
+    Install {
+        #[command(flatten)]
+        opts: service::ServiceOptions,
+        /// Print the service definition instead of installing it
+        #[arg(long)]
+        print: bool,
+    },
+    /// Stop and remove the service
     Uninstall,
+    /// Show the service manager's status for the watchdog
+    Status,
+}
+
+#[derive(Subcommand)]
+enum UsersAction {
+    /// Show allowed users and admins
+    List,
+    /// Allow users by ID, or detect them as they message the bot
+    Add {
+        /// Telegram user IDs (omit to detect: stop the daemon and send /start to the bot)
+        ids: Vec<i64>,
+        /// How long to wait for messages when detecting
+        #[arg(long, default_value = "2m")]
+        wait: config::Duration,
+    },
+    /// Revoke users
+    Remove {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+        /// Allow removing the last admin, which makes every remaining user one
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Report every problem in the config, by JSON path
+    Check,
+    /// Print the effective config and where each value came from (secrets masked)
+    Show {
+        /// Print the merged config as JSON instead
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[tokio::main]
@@ -56,16 +177,29 @@ async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
 
     let cli = Cli::parse();
-    let cfg = config::Config::load()?;
 
-    match cli.command {
-        Commands::Setup => {
-            config::setup_wizard().await?;
+    // Setup creates the config, so it runs before loading one
+    if let Commands::Setup(opts) = &cli.command {
+        if opts.non_interactive {
+            let summary = config::setup::non_interactive(opts, cli.config.as_deref()).await?;
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        } else {
+            config::setup_wizard(opts, cli.config.as_deref()).await?;
         }
+        return Ok(());
+    }
+
+    let loaded = config::Config::load_from(cli.config.as_deref())?;
+    let cfg = loaded.config.clone();
+
+    match cli.command {
+        Commands::Setup(_) => unreachable!("handled before loading the config"),
         Commands::Start => {
             println!("🛟 RescueClaw starting...");
-            let cfg = config::Config::load()?;
-            run_daemon(cfg).await?;
+            for warning in loaded.require_valid()? {
+                println!("  ⚠️ {}", warning);
+            }
+            run_daemon(cfg, cli.config).await?;
         }
         Commands::Status => {
             let status = health::check_status(&cfg).await?;
@@ -88,35 +222,302 @@ async fn main() -> Result<()> {
             }
         }
         Commands::Restore { id, force, dry_run } => {
-            restore::restore_with_options(&cfg, id.as_deref(), force, dry_run).await?;
+            let category = analysis::classify_current(&cfg).map(|c| c.slug());
+            if let Some(outcome) =
+                restore::restore_with_options(&cfg, id.as_deref(), force, dry_run).await?
+            {
+                incidents::record_restore(
+                    &cfg,
+                    category,
+                    "manual (cli)",
+                    "manual-restore",
+                    &outcome,
+                    None,
+                );
+            }
+        }
+        Commands::Logs { id: Some(id), .. } => {
+            let incident = incidents::IncidentStore::open(&cfg).get(&id)?;
+            print!("{}", incident);
+        }
+        Commands::Logs { id: None, n } => {
+            let recent = incidents::IncidentStore::open(&cfg).recent(n)?;
+            if recent.is_empty() {
+                println!("✅ No incidents recorded.");
+            }
+            for incident in recent {
+                println!("  {}", incident);
+            }
+        }
+        Commands::Analyze { offline } => {
+            println!("📊 Analyzing current state...");
+            let incident = analysis::on_demand_incident("CLI");
+            let report = analysis::run_analysis(&cfg, &incident, None, offline).await?;
+            println!("{}", report.analysis);
+            println!();
+            println!("✓ Incident report saved to: {}", report.path.display());
+        }
+        Commands::Validate {
+            snapshot,
+            live: _,
+            list_rules,
+        } => {
+            let validator = validate::Validator::new(&cfg.validation)?;
+            if list_rules {
+                for rule in validator.rules() {
+                    let level = match validator.level(rule.id()) {
+                        Some(config::RuleLevel::Off) => "off",
+                        Some(config::RuleLevel::Warning) => "warning",
+                        Some(config::RuleLevel::Error) => "error",
+                        None => "default",
+                    };
+                    println!(
+                        "  {:<22} {:<9} {:<8} {}",
+                        rule.id(),
+                        rule.scope().to_string(),
+                        level,
+                        rule.description()
+                    );
+                }
+                return Ok(());
+            }
+
+            // Keep the extracted snapshot alive until validation is done
+            let (_extracted, target) = match snapshot {
+                Some(id) => {
+                    let snap = backup::find_snapshot(&cfg, Some(&id))?;
+                    let dir = tempfile::TempDir::new()?;
+                    restore::extract_backup_to(&snap.path, dir.path(), &cfg)?;
+                    println!("🔎 Validating snapshot {}", snap.id);
+                    let target = validate::ValidationTarget::new(
+                        dir.path().join("config"),
+                        dir.path().join("workspace"),
+                    );
+                    (Some(dir), target)
+                }
+                None => {
+                    println!("🔎 Validating live OpenClaw install");
+                    (None, validate::ValidationTarget::live(&cfg))
+                }
+            };
+
+            let issues = validator.check(&target, None);
+            for issue in &issues {
+                let icon = match issue.severity {
+                    validate::Severity::Error => "❌",
+                    validate::Severity::Warning => "⚠️",
+                };
+                println!("  {} [{}] {}", icon, issue.rule, issue.message);
+            }
+            let errors = issues
+                .iter()
+                .filter(|i| i.severity == validate::Severity::Error)
+                .count();
+            if issues.is_empty() {
+                println!("  ✓ No issues found");
+            }
+            if errors > 0 {
+                anyhow::bail!("Validation failed with {} error(s)", errors);
+            }
+        }
+        Commands::Changes { since } => {
+            let changes = journal::changes_since(&cfg, since.before(chrono::Utc::now()))?;
+            if changes.is_empty() {
+                println!("No changes recorded in the last {}.", since);
+            }
+            for change in changes {
+                println!("  {}", change);
+            }
+        }
+        Commands::Schedule { n } => {
+            print!("{}", schedule::overview(&cfg, n)?);
+        }
+        Commands::Insights { days } => {
+            println!("{}", incidents::insights(&cfg, days)?);
+        }
+        Commands::RedactTest { file, show } => {
+            let text = std::fs::read_to_string(&file)?;
+            let redactor = redact::Redactor::new(&cfg.redaction)?;
+            let (redacted, found) = redactor.scan(&text);
+            for r in &found {
+                println!(
+                    "  line {:>5} │ {:<14} │ {} chars",
+                    r.line,
+                    r.kind.to_string(),
+                    r.length
+                );
+            }
+            println!(
+                "\n{} secret(s) would be masked in {}",
+                found.len(),
+                file.display()
+            );
+            if show {
+                println!("\n{}", redacted);
+            }
+        }
+        Commands::Users {
+            action: UsersAction::List,
+        } => {
+            let telegram = &cfg.telegram;
+            println!(
+                "Allowed users ({}):",
+                loaded.origin("telegram.allowedUsers")
+            );
+            if telegram.allowed_users.is_empty() {
+                println!("  ⚠️ none — anyone who finds the bot can control it");
+            }
+            for user in &telegram.allowed_users {
+                let admin = telegram.admins.is_empty() || telegram.admins.contains(user);
+                println!("  {}{}", user, if admin { " (admin)" } else { "" });
+            }
         }
-        Commands::Logs { n } => {
-            let logs = health::recent_incidents(&cfg, n)?;
-            for log in logs {
-                println!("  {} │ {} │ {}", log.timestamp, log.cause, log.recovery);
+        Commands::Users {
+            action: UsersAction::Add { mut ids, wait },
+        } => {
+            if ids.is_empty() {
+                if cfg.telegram.token.is_empty() {
+                    anyhow::bail!("No telegram.token configured — pass user IDs instead");
+                }
+                ids = config::users::discover(&cfg.telegram.token, wait.as_std()).await;
+                if ids.is_empty() {
+                    anyhow::bail!("No users added");
+                }
             }
+            let update = config::users::add_users(cli.config.as_deref(), &ids)?;
+            print_users_update(&update);
         }
-        Commands::Uninstall => {
-            config::uninstall()?;
+        Commands::Users {
+            action: UsersAction::Remove { ids, force },
+        } => {
+            let update = config::users::remove_users(cli.config.as_deref(), &ids, force)?;
+            print_users_update(&update);
+        }
+        Commands::Config {
+            action: ConfigAction::Check,
+        } => {
+            println!("🔎 Checking {}", loaded.sources());
+            let problems = loaded.problems();
+            for p in &problems {
+                let icon = match p.severity {
+                    validate::Severity::Error => "❌",
+                    validate::Severity::Warning => "⚠️",
+                };
+                println!("  {} {}", icon, p);
+            }
+            if problems.is_empty() {
+                println!("  ✓ Config is valid");
+            }
+            if config::Config::has_errors(&problems) {
+                anyhow::bail!("Config has errors — the daemon will refuse to start");
+            }
+        }
+        Commands::Config {
+            action: ConfigAction::Show { json },
+        } => {
+            if json {
+                println!("{}", serde_json::to_string_pretty(&cfg.masked())?);
+            } else {
+                println!("# {}", loaded.sources());
+                print!("{}", loaded.show()?);
+            }
+        }
+        Commands::Service {
+            action: ServiceAction::Install { opts, print },
+        } => {
+            let manager = opts.manager()?;
+            let spec = service::ServiceSpec::new(&cfg, cli.config.as_deref());
+            if print {
+                println!("# {}", manager.unit_path(&spec.home).display());
+                print!("{}", manager.render(&spec));
+            } else {
+                service::install(manager, &spec)?;
+            }
+        }
+        Commands::Service {
+            action: ServiceAction::Uninstall,
+        } => {
+            let spec = service::ServiceSpec::new(&cfg, cli.config.as_deref());
+            match service::uninstall(&spec.home)? {
+                Some(manager) => println!("✓ Removed {:?} service", manager),
+                None => println!("Service not installed"),
+            }
+        }
+        Commands::Service {
+            action: ServiceAction::Status,
+        } => {
+            let spec = service::ServiceSpec::new(&cfg, cli.config.as_deref());
+            service::status(&spec.home)?;
+        }
+        Commands::Uninstall(opts) => {
+            println!("🛟 Uninstalling RescueClaw...");
+            let spec = service::ServiceSpec::new(&cfg, cli.config.as_deref());
+            let removed = service::uninstall::uninstall(&cfg, &loaded.files, &spec.home, &opts)?;
+            if removed.is_empty() {
+                println!("  Nothing to remove (no service installed)");
+            }
+            for item in &removed {
+                println!("  ✓ Removed {}", item);
+            }
+            if !opts.purge {
+                println!("  Backups preserved at {}", cfg.backup.path.display());
+                for file in &loaded.files {
+                    println!("  Config preserved at {}", file.display());
+                }
+            }
         }
     }
 
     Ok(())
 }
 
-/// Main daemon loop: health checks, scheduled backups, Telegram listener
-async fn run_daemon(cfg: config::Config) -> Result<()> {
+fn print_users_update(update: &config::users::UsersUpdate) {
+    match &update.file {
+        Some(file) => println!(
+            "✓ Allowed users now {:?} ({})",
+            update.users,
+            file.display()
+        ),
+        None => println!("Allowed users unchanged: {:?}", update.users),
+    }
+}
+
+/// Main daemon loop: health checks, scheduled backups, Telegram listener,
+/// config guard, change journal and config reloads
+async fn run_daemon(cfg: config::Config, config_file: Option<std::path::PathBuf>) -> Result<()> {
     println!("  Watchdog PID: {}", std::process::id());
     println!("  Health check: every {}", cfg.health.check_interval);
-    println!("  Backup: every {}", cfg.backup.interval);
+    match cfg.backup.mode {
+        config::BackupMode::Interval => println!("  Backup: every {}", cfg.backup.interval),
+        config::BackupMode::Changes => println!(
+            "  Backup: on changes (after {} quiet, {} apart at least)",
+            cfg.backup.debounce, cfg.backup.min_interval
+        ),
+        config::BackupMode::Schedule => println!(
+            "  Backup: {} cron schedule(s) — see `rescueclaw schedule`",
+            cfg.backup.schedules.len()
+        ),
+    }
     println!("  Telegram: listening for commands");
+    if cfg.guard.enabled {
+        println!("  Config guard: {:?} on invalid edits", cfg.guard.policy);
+    }
+    if cfg.journal.enabled {
+        println!("  Change journal: workspace + config");
+    }
+    println!("  Config: reloads on SIGHUP or file change");
     println!();
 
-    // Run all three loops concurrently
+    let (reload_tx, live) = config::LiveConfig::new(cfg.clone());
+
+    // Run all loops concurrently
     tokio::select! {
-        r = health::health_loop(&cfg) => r?,
-        r = backup::backup_loop(&cfg) => r?,
-        r = telegram::listen(&cfg) => r?,
+        r = health::health_loop(&live) => r?,
+        r = backup::backup_loop(&live) => r?,
+        r = telegram::listen(&live, config_file.clone()) => r?,
+        r = config::live::reload_loop(config_file, reload_tx) => r?,
+        r = guard::guard_loop(&live) => r?,
+        r = journal::journal_loop(&live) => r?,
     }
 
     Ok(())
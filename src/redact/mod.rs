@@ -0,0 +1,283 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::config::{Config, RedactionConfig};
+
+/// What kind of secret a match looked like
+#[derive(Debug, Clone, PartialEq)]
+pub enum SecretKind {
+    /// Value of a `apiKey`/`token`/`password`-style field
+    SecretField,
+    BearerToken,
+    TelegramToken,
+    ApiKey,
+    Email,
+    /// A user pattern from `redaction.customPatterns` (by index)
+    Custom(usize),
+}
+
+impl fmt::Display for SecretKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SecretKind::SecretField => write!(f, "secret"),
+            SecretKind::BearerToken => write!(f, "bearer"),
+            SecretKind::TelegramToken => write!(f, "telegram-token"),
+            SecretKind::ApiKey => write!(f, "api-key"),
+            SecretKind::Email => write!(f, "email"),
+            SecretKind::Custom(i) => write!(f, "custom-{}", i + 1),
+        }
+    }
+}
+
+/// One masked span, for reports. Nothing of the secret itself is kept.
+#[derive(Debug, Clone)]
+pub struct Redaction {
+    pub kind: SecretKind,
+    /// 1-based line number
+    pub line: usize,
+    /// Length of the masked part, in characters
+    pub length: usize,
+}
+
+/// A detection rule. A prefix of the match can be kept verbatim: capture
+/// group 1 for built-in rules, a group named `keep` for custom ones. The rest
+/// of the match is masked.
+struct Rule {
+    kind: SecretKind,
+    re: Regex,
+}
+
+/// Built-in rules, applied in order
+fn builtin_rules() -> &'static [Rule] {
+    static RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+    RULES.get_or_init(|| {
+        [
+            // "apiKey": "...", "token": "...", password = ... (JSON and key=value forms)
+            (
+                SecretKind::SecretField,
+                r#"(?i)("?(?:api[_-]?key|token|secret|password|passwd|auth[_-]?token|access[_-]?token|bot[_-]?token|client[_-]?secret)"?\s*[:=]\s*"?)[^"\s,}\[]+"#,
+            ),
+            // Authorization: Bearer <token>
+            (
+                SecretKind::BearerToken,
+                r"(?i)(bearer\s+)[A-Za-z0-9._~+/=-]+",
+            ),
+            // Telegram bot tokens (123456789:AA...)
+            (
+                SecretKind::TelegramToken,
+                r"\b()\d{6,12}:[A-Za-z0-9_-]{30,}\b",
+            ),
+            // Provider keys: sk-..., sk-ant-..., sk-or-..., gsk_..., xoxb-..., ghp_..., AWS access keys
+            (
+                SecretKind::ApiKey,
+                r"\b()(?:sk-[A-Za-z0-9_-]{16,}|gsk_[A-Za-z0-9]{20,}|xox[abp]-[A-Za-z0-9-]{10,}|gh[pousr]_[A-Za-z0-9]{30,}|AKIA[0-9A-Z]{16})",
+            ),
+        ]
+        .into_iter()
+        .map(|(kind, p)| Rule {
+            kind,
+            re: Regex::new(p).expect("built-in redaction pattern"),
+        })
+        .collect()
+    })
+}
+
+fn email_rule() -> &'static Rule {
+    static RULE: OnceLock<Rule> = OnceLock::new();
+    RULE.get_or_init(|| Rule {
+        kind: SecretKind::Email,
+        re: Regex::new(r"\b()[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b")
+            .expect("built-in email pattern"),
+    })
+}
+
+/// Masks secrets in text before it leaves the box (LLM prompts, reports,
+/// Telegram replies, diffs)
+pub struct Redactor {
+    redact_emails: bool,
+    custom: Vec<Rule>,
+}
+
+impl Redactor {
+    /// Build from the `redaction` config section; fails on an invalid custom pattern
+    pub fn new(redaction: &RedactionConfig) -> Result<Self> {
+        let custom = redaction
+            .custom_patterns
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                Regex::new(p)
+                    .map(|re| Rule {
+                        kind: SecretKind::Custom(i),
+                        re,
+                    })
+                    .with_context(|| format!("invalid redaction.customPatterns[{}]: {}", i, p))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            redact_emails: redaction.redact_emails,
+            custom,
+        })
+    }
+
+    /// Redactor for a loaded config. Invalid custom patterns are logged and
+    /// skipped so the built-in rules always apply.
+    pub fn for_config(cfg: &Config) -> Self {
+        Self::new(&cfg.redaction).unwrap_or_else(|e| {
+            tracing::warn!("{} — using built-in redaction rules only", e);
+            Self::builtin()
+        })
+    }
+
+    /// Built-in rules only (emails included)
+    pub fn builtin() -> Self {
+        Self {
+            redact_emails: true,
+            custom: Vec::new(),
+        }
+    }
+
+    pub fn redact(&self, text: &str) -> String {
+        self.apply(text, |_| {})
+    }
+
+    /// Redact and report every masked span
+    pub fn scan(&self, text: &str) -> (String, Vec<Redaction>) {
+        let mut found = Vec::new();
+        let redacted = self.apply(text, |r| found.push(r));
+        found.sort_by_key(|r| r.line);
+        (redacted, found)
+    }
+
+    fn rules(&self) -> impl Iterator<Item = &Rule> {
+        builtin_rules()
+            .iter()
+            .chain(self.redact_emails.then(email_rule))
+            .chain(self.custom.iter())
+    }
+
+    fn apply(&self, text: &str, mut on_match: impl FnMut(Redaction)) -> String {
+        let mut out = text.to_string();
+        for rule in self.rules() {
+            let current = out.clone();
+            out = rule
+                .re
+                .replace_all(&current, |caps: &regex::Captures| {
+                    let whole = caps.get(0).expect("match");
+                    let keep = match rule.kind {
+                        SecretKind::Custom(_) => caps.name("keep"),
+                        _ => caps.get(1),
+                    }
+                    .filter(|k| k.start() == whole.start())
+                    .map_or(whole.start(), |k| k.end());
+                    let secret = &current[keep..whole.end()];
+                    let keep = &current[whole.start()..keep];
+                    on_match(Redaction {
+                        kind: rule.kind.clone(),
+                        line: current[..whole.start()].matches('\n').count() + 1,
+                        length: secret.chars().count(),
+                    });
+                    format!("{}[REDACTED:{}]", keep, rule.kind)
+                })
+                .into_owned();
+        }
+        out
+    }
+}
+
+/// Mask secrets using the config's redaction settings
+pub fn redact(cfg: &Config, text: &str) -> String {
+    Redactor::for_config(cfg).redact(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor(custom: &[&str], emails: bool) -> Redactor {
+        Redactor::new(&RedactionConfig {
+            custom_patterns: custom.iter().map(|s| s.to_string()).collect(),
+            redact_emails: emails,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_redacts_json_api_key() {
+        let out =
+            Redactor::builtin().redact(r#"{"apiKey": "abc123def456", "baseUrl": "https://x"}"#);
+        assert!(!out.contains("abc123def456"));
+        assert!(out.contains(r#""apiKey": "[REDACTED:secret]""#));
+        assert!(out.contains("https://x"));
+    }
+
+    #[test]
+    fn test_redacts_bearer_and_bot_tokens() {
+        let out = Redactor::builtin().redact(
+            "Authorization: Bearer eyJhbGciOi.abc\nbot 123456789:AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw1",
+        );
+        assert!(out.contains("Bearer [REDACTED:bearer]"));
+        assert!(out.contains("[REDACTED:telegram-token]"));
+        assert!(!out.contains("AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw1"));
+    }
+
+    #[test]
+    fn test_redacts_provider_keys_in_free_text() {
+        let out = Redactor::builtin()
+            .redact("using key sk-ant-REDACTED for requests");
+        assert_eq!(out, "using key [REDACTED:api-key] for requests");
+    }
+
+    #[test]
+    fn test_emails_optional() {
+        let text = "contact ops@example.com now";
+        assert_eq!(
+            redactor(&[], true).redact(text),
+            "contact [REDACTED:email] now"
+        );
+        assert_eq!(redactor(&[], false).redact(text), text);
+    }
+
+    #[test]
+    fn test_custom_patterns() {
+        let r = redactor(&[r"ACME-\d{6}"], false);
+        assert_eq!(r.redact("ticket ACME-123456"), "ticket [REDACTED:custom-1]");
+
+        // Plain groups are masked with the rest; only a leading `keep` group stays
+        let r = redactor(&[r"acct=([0-9]+)", r"(?P<keep>pin:)\d+", r"é(1)"], false);
+        assert_eq!(
+            r.redact("acct=4711 pin:1234 café1"),
+            "[REDACTED:custom-1] pin:[REDACTED:custom-2] caf[REDACTED:custom-3]"
+        );
+        let r = redactor(&[r"x(?P<keep>y)z"], false);
+        assert_eq!(r.redact("xyz"), "[REDACTED:custom-1]");
+
+        assert!(Redactor::new(&RedactionConfig {
+            custom_patterns: vec!["(unclosed".to_string()],
+            redact_emails: true,
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_scan_reports_lines() {
+        let (out, found) = Redactor::builtin().scan("line one\n\"token\": \"abcdef\"\nmail a@b.io");
+        assert!(!out.contains("abcdef"));
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].line, 2);
+        assert_eq!(found[0].kind, SecretKind::SecretField);
+        assert_eq!(found[0].length, 6);
+        assert!(!format!("{:?}", found).contains("abcd"));
+        assert_eq!(found[1].line, 3);
+        assert_eq!(found[1].kind, SecretKind::Email);
+    }
+
+    #[test]
+    fn test_leaves_plain_text_alone() {
+        let text = "Gateway listening on port 7744";
+        assert_eq!(Redactor::builtin().redact(text), text);
+    }
+}
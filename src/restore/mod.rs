@@ -1,41 +1,40 @@
 use anyhow::{Context, Result};
 use flate2::read::GzDecoder;
 use std::fs;
+use std::io::{self, Read};
+use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
-use std::process::Command;
 use tempfile::TempDir;
 
 use crate::config::Config;
+use crate::gateway::{Endpoint, Stopped};
 use crate::validate::Severity;
 
+/// What a completed restore did
+#[derive(Debug, Clone)]
+pub struct RestoreOutcome {
+    pub snapshot_id: String,
+    /// Archive paths that differed from the snapshot and were rolled back
+    pub restored_files: Vec<String>,
+    pub agent_online: bool,
+}
+
 /// Restore OpenClaw from a backup snapshot
-pub async fn restore(cfg: &Config, backup_id: Option<&str>) -> Result<()> {
-    restore_with_options(cfg, backup_id, false, false).await
+pub async fn restore(cfg: &Config, backup_id: Option<&str>) -> Result<RestoreOutcome> {
+    restore_with_options(cfg, backup_id, false, false)
+        .await?
+        .context("restore did not run")
 }
 
-/// Restore with validation and dry-run options
+/// Restore with validation and dry-run options. Returns `None` when nothing
+/// was restored (dry run).
 pub async fn restore_with_options(
     cfg: &Config,
     backup_id: Option<&str>,
     force: bool,
     dry_run: bool,
-) -> Result<()> {
-    let snapshots = crate::backup::list_snapshots(cfg)?;
-
-    if snapshots.is_empty() {
-        anyhow::bail!("No backups available. Run `rescueclaw backup` first.");
-    }
-
-    let snapshot = if let Some(id) = backup_id {
-        snapshots.iter().find(|s| s.id == id).ok_or_else(|| {
-            anyhow::anyhow!(
-                "Backup '{}' not found. Use `rescueclaw list` to see available backups.",
-                id
-            )
-        })?
-    } else {
-        &snapshots[0]
-    };
+) -> Result<Option<RestoreOutcome>> {
+    let snapshot = crate::backup::find_snapshot(cfg, backup_id)?;
 
     println!(
         "🛟 Restoring from backup: {} ({})",
@@ -48,15 +47,13 @@ pub async fn restore_with_options(
         let temp_dir = TempDir::new()?;
         extract_backup_to(&snapshot.path, temp_dir.path(), cfg)?;
 
-        let config_issues =
-            crate::validate::validate_openclaw_config(&temp_dir.path().join("config"))?;
-        let workspace_issues =
-            crate::validate::validate_workspace(&temp_dir.path().join("workspace"))?;
-
-        let all_issues: Vec<_> = config_issues
-            .into_iter()
-            .chain(workspace_issues.into_iter())
-            .collect();
+        let all_issues = crate::validate::Validator::for_config(cfg).check(
+            &crate::validate::ValidationTarget::new(
+                temp_dir.path().join("config"),
+                temp_dir.path().join("workspace"),
+            ),
+            None,
+        );
 
         let errors: Vec<_> = all_issues
             .iter()
@@ -70,7 +67,7 @@ pub async fn restore_with_options(
                     Severity::Error => "❌",
                     Severity::Warning => "⚠️",
                 };
-                println!("    {} {}", icon, issue.message);
+                println!("    {} [{}] {}", icon, issue.rule, issue.message);
             }
             println!();
         }
@@ -78,7 +75,7 @@ pub async fn restore_with_options(
         if !errors.is_empty() {
             if dry_run {
                 println!("  ❌ Restore would fail due to validation errors");
-                return Ok(());
+                return Ok(None);
             }
             anyhow::bail!(
                 "Backup validation failed with {} error(s). Use --force to override.",
@@ -99,241 +96,104 @@ pub async fn restore_with_options(
         println!("\n  Would restore:");
         println!("    - Workspace to: {}", cfg.openclaw.workspace.display());
         println!("    - Config to:    {}", cfg.openclaw.config_path.display());
-        return Ok(());
+        return Ok(None);
     }
 
-    // Step 2: Identify the target gateway by port (from OpenClaw config)
-    let target_port = read_gateway_port(cfg);
-    let gateway_pid = find_gateway_pid(target_port);
-    let was_running = gateway_pid.is_some();
-
-    println!(
-        "  Target gateway: port {} (PID: {})",
-        target_port,
-        gateway_pid.map_or("not running".to_string(), |p| p.to_string())
-    );
-
-    // Step 3: Stop the specific gateway by PID (only if it was running)
-    if let Some(pid) = gateway_pid {
-        println!("  Stopping gateway (PID {})...", pid);
-        kill_process(pid)?;
-        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-    }
+    // Step 2: Stop the gateway (if running), through whatever manages it
+    let endpoint = crate::gateway::endpoint(cfg);
+    let gateway = crate::gateway::controller(cfg);
+    println!("  Target gateway: {}", gateway.describe());
+    let grace = cfg.openclaw.gateway.grace_period;
+    let was_running =
+        match crate::gateway::stop_gracefully(gateway.as_ref(), grace.as_std()).await? {
+            Stopped::NotRunning => {
+                println!("  Gateway not running");
+                false
+            }
+            Stopped::Graceful(pid) => {
+                println!("  ✓ Gateway stopped (PID {})", pid);
+                true
+            }
+            Stopped::Killed(pid) => {
+                println!(
+                    "  ⚠ Gateway (PID {}) ignored the stop for {}, killed",
+                    pid, grace
+                );
+                true
+            }
+        };
 
-    // Step 4: Restore files
+    // Step 3: Restore files
+    let restored_files = crate::backup::changed_files(cfg, &snapshot).unwrap_or_else(|e| {
+        tracing::warn!("Could not determine changed files: {}", e);
+        Vec::new()
+    });
     println!("  Extracting backup...");
     extract_backup(&snapshot.path, cfg)?;
     println!("  ✓ Files restored.");
 
-    // Step 5: Always try to start the gateway after restore
+    // Step 4: Always try to start the gateway after restore
     // (The whole point of rescue is to bring the agent back online)
     if was_running {
-        println!("  Restarting gateway on port {}...", target_port);
+        println!("  Restarting {}...", gateway.describe());
     } else {
-        println!("  Starting gateway on port {}...", target_port);
+        println!("  Starting {}...", gateway.describe());
+    }
+    if let Err(e) = gateway.start() {
+        // The files are restored either way; report and let the check below confirm
+        println!("  ⚠ Could not start the gateway: {:#}", e);
     }
-    start_openclaw_with_config(cfg)?;
 
     println!("  Verifying gateway is responsive...");
-    let alive = wait_for_agent(target_port, 30).await;
+    let alive = wait_for_agent(&endpoint, 30).await;
 
-    let recovery_status = if alive {
-        println!("  ✓ Agent restored and online on port {}!", target_port);
-        format!("restored from {} — agent online", snapshot.id)
+    if alive {
+        println!("  ✓ Agent restored and online at {}!", endpoint.url);
     } else {
+        println!("  ⚠ Gateway not responding at {} after 30s.", endpoint.url);
         println!(
-            "  ⚠ Gateway not responding on port {} after 30s.",
-            target_port
+            "    Check how it's started: openclaw.gateway in rescueclaw's config ({:?} driver)",
+            cfg.openclaw.gateway.driver
         );
-        println!("    Try manually: openclaw gateway start");
-        format!("restored from {} — gateway not responding", snapshot.id)
-    };
-
-    // Log the restore event
-    log_incident(cfg, "restore", &recovery_status);
+    }
 
-    Ok(())
+    Ok(Some(RestoreOutcome {
+        snapshot_id: snapshot.id,
+        restored_files,
+        agent_online: alive,
+    }))
 }
 
-/// Extract backup and optionally analyze incident
-#[allow(dead_code)]
+/// Restore a backup, then analyze the incident that triggered it
 pub async fn restore_and_analyze(
     cfg: &Config,
     backup_id: Option<&str>,
-    incident: Option<&crate::health::IncidentLog>,
-) -> Result<()> {
-    restore(cfg, backup_id).await?;
-
-    if let Some(inc) = incident {
-        println!("\n  📊 Analyzing incident...");
-        match crate::analysis::analyze_incident(cfg).await {
-            Ok(analysis) => {
-                let backup_id_str = backup_id.unwrap_or("latest");
-                let report = crate::analysis::format_incident_report(&analysis, inc, backup_id_str);
-                let report_path = cfg
-                    .backup
-                    .path
-                    .join(format!("incident-report-{}.md", backup_id_str));
-                fs::write(&report_path, &report)?;
-                println!("  ✓ Incident report saved to: {}", report_path.display());
-            }
-            Err(e) => {
-                println!("  ⚠ Analysis failed: {}", e);
-            }
+    incident: &crate::health::IncidentLog,
+) -> Result<(RestoreOutcome, Option<crate::analysis::AnalysisReport>)> {
+    // Resolve "latest" up front so the report names the snapshot actually used
+    let snapshot_id = crate::backup::find_snapshot(cfg, backup_id)?.id;
+    let outcome = restore(cfg, Some(&snapshot_id)).await?;
+
+    println!("\n  📊 Analyzing incident...");
+    let report = match crate::analysis::run_analysis(cfg, incident, Some(&snapshot_id), false).await
+    {
+        Ok(report) => {
+            println!("  ✓ Incident report saved to: {}", report.path.display());
+            Some(report)
         }
-    }
-
-    Ok(())
-}
-
-/// Log an incident/event to the incidents.jsonl file
-fn log_incident(cfg: &Config, cause: &str, recovery: &str) {
-    let incident = crate::health::IncidentLog {
-        timestamp: chrono::Utc::now().to_rfc3339(),
-        cause: cause.to_string(),
-        recovery: recovery.to_string(),
-    };
-    let incidents_path = cfg.backup.path.join("incidents.jsonl");
-    if let Ok(line) = serde_json::to_string(&incident) {
-        let _ = fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&incidents_path)
-            .and_then(|mut f| {
-                use std::io::Write;
-                writeln!(f, "{}", line)
-            });
-    }
-}
-
-// ─── Gateway targeting ─────────────────────────────────────────────
-
-/// Read the gateway port from the OpenClaw config file
-pub fn read_gateway_port(cfg: &Config) -> u16 {
-    let config_file = cfg.openclaw.config_path.join("openclaw.json");
-    let legacy_file = cfg.openclaw.config_path.join("clawdbot.json");
-
-    let path = if config_file.exists() {
-        config_file
-    } else {
-        legacy_file
-    };
-
-    if let Ok(content) = fs::read_to_string(&path) {
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-            if let Some(port) = json
-                .get("gateway")
-                .and_then(|g| g.get("port"))
-                .and_then(|p| p.as_u64())
-            {
-                return port as u16;
-            }
-        }
-    }
-
-    // Default OpenClaw port
-    7744
-}
-
-/// Find the PID of the gateway process listening on a specific port
-fn find_gateway_pid(port: u16) -> Option<u32> {
-    // Use ss/lsof to find which PID is listening on this port
-    let output = Command::new("ss")
-        .args(["-tlnp", &format!("sport = :{}", port)])
-        .output()
-        .ok()?;
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-
-    // Parse ss output for pid=NNNN
-    for line in stdout.lines() {
-        if let Some(pid_start) = line.find("pid=") {
-            let after_pid = &line[pid_start + 4..];
-            let pid_str: String = after_pid
-                .chars()
-                .take_while(|c| c.is_ascii_digit())
-                .collect();
-            if let Ok(pid) = pid_str.parse::<u32>() {
-                return Some(pid);
-            }
-        }
-    }
-
-    // Fallback: try lsof
-    let output = Command::new("lsof")
-        .args(["-ti", &format!(":{}", port)])
-        .output()
-        .ok()?;
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    stdout
-        .trim()
-        .lines()
-        .next()
-        .and_then(|line| line.trim().parse::<u32>().ok())
-}
-
-/// Kill a specific process by PID (SIGTERM, then SIGKILL if needed)
-fn kill_process(pid: u32) -> Result<()> {
-    // Send SIGTERM
-    let _ = Command::new("kill").arg(pid.to_string()).output();
-
-    // Wait briefly, check if dead
-    std::thread::sleep(std::time::Duration::from_millis(500));
-
-    // Check if still alive
-    let check = Command::new("kill").args(["-0", &pid.to_string()]).output();
-
-    if let Ok(o) = check {
-        if o.status.success() {
-            // Still alive, SIGKILL
-            tracing::warn!("Process {} didn't stop with SIGTERM, sending SIGKILL", pid);
-            let _ = Command::new("kill").args(["-9", &pid.to_string()]).output();
+        Err(e) => {
+            println!("  ⚠ Analysis failed: {}", e);
+            None
         }
-    }
-
-    Ok(())
-}
-
-/// Start OpenClaw gateway
-fn start_openclaw_with_config(_cfg: &Config) -> Result<()> {
-    // Try openclaw CLI first, then clawdbot (legacy)
-    let cli = if Command::new("openclaw").arg("--version").output().is_ok() {
-        "openclaw"
-    } else {
-        "clawdbot"
     };
 
-    let result = Command::new(cli).args(["gateway", "start"]).output();
-
-    match result {
-        Ok(o) if o.status.success() => Ok(()),
-        Ok(_) => {
-            // Try systemd as fallback
-            tracing::info!("CLI start failed, trying systemd restart");
-            let _ = Command::new("systemctl")
-                .args(["--user", "restart", "openclaw-gateway"])
-                .output();
-            // Also try system-level systemd
-            let _ = Command::new("sudo")
-                .args(["systemctl", "restart", "openclaw-gateway"])
-                .output();
-            Ok(())
-        }
-        Err(e) => {
-            anyhow::bail!(
-                "Could not start gateway: {}. Start manually: {} gateway start",
-                e,
-                cli
-            );
-        }
-    }
+    Ok((outcome, report))
 }
 
 // ─── Backup extraction ─────────────────────────────────────────────
 
 /// Extract backup to a specific directory (for validation / dry-run)
-fn extract_backup_to(backup_path: &Path, dest_dir: &Path, _cfg: &Config) -> Result<()> {
+pub fn extract_backup_to(backup_path: &Path, dest_dir: &Path, _cfg: &Config) -> Result<()> {
     let tar_file = fs::File::open(backup_path)?;
     let decoder = GzDecoder::new(tar_file);
     let mut archive = tar::Archive::new(decoder);
@@ -367,6 +227,8 @@ fn extract_backup(backup_path: &Path, cfg: &Config) -> Result<()> {
         .with_context(|| format!("opening backup: {}", backup_path.display()))?;
     let decoder = GzDecoder::new(tar_file);
     let mut archive = tar::Archive::new(decoder);
+    // The agent's container usually runs as another user than ours
+    archive.set_preserve_ownerships(cfg.openclaw.container_mode() && crate::service::is_root());
 
     for entry in archive.entries()? {
         let mut entry = entry?;
@@ -392,28 +254,47 @@ fn extract_backup(backup_path: &Path, cfg: &Config) -> Result<()> {
         if let Some(parent) = dest.parent() {
             fs::create_dir_all(parent)?;
         }
-        entry.unpack(&dest)?;
+        unpack(&mut entry, &dest).with_context(|| format!("restoring {}", dest.display()))?;
     }
 
     Ok(())
 }
 
+/// Unpack `entry` to `dest`, rewriting an existing file in place rather than
+/// replacing it: a bind-mounted file can't be unlinked, and another container
+/// mounting it would keep seeing the old inode
+fn unpack<R: Read>(entry: &mut tar::Entry<R>, dest: &Path) -> Result<()> {
+    let existing_file = fs::symlink_metadata(dest).is_ok_and(|m| m.is_file());
+    if !existing_file || !entry.header().entry_type().is_file() {
+        entry.unpack(dest)?;
+        return Ok(());
+    }
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(dest)?;
+    io::copy(entry, &mut file)?;
+    if let Ok(mode) = entry.header().mode() {
+        file.set_permissions(fs::Permissions::from_mode(mode & 0o7777))?;
+    }
+    Ok(())
+}
+
 // ─── Health check (port-aware) ─────────────────────────────────────
 
-/// Wait for the agent to come back online on the correct port
-async fn wait_for_agent(port: u16, timeout_secs: u64) -> bool {
-    let client = reqwest::Client::new();
-    let url = format!("http://127.0.0.1:{}/api/status", port);
+/// Wait for the agent to come back online at its status endpoint
+async fn wait_for_agent(endpoint: &Endpoint, timeout_secs: u64) -> bool {
+    let client = match endpoint.client(std::time::Duration::from_secs(3)) {
+        Ok(client) => client,
+        Err(e) => {
+            println!("  ⚠ {:#}", e);
+            return false;
+        }
+    };
     let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_secs(timeout_secs);
 
     while tokio::time::Instant::now() < deadline {
-        if client
-            .get(&url)
-            .timeout(std::time::Duration::from_secs(3))
-            .send()
-            .await
-            .is_ok()
-        {
+        if endpoint.alive(&client).await {
             return true;
         }
         tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
@@ -421,3 +302,38 @@ async fn wait_for_agent(port: u16, timeout_secs: u64) -> bool {
 
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unpack_rewrites_existing_files_in_place() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let dest = dir.path().join("openclaw.json");
+        fs::write(&dest, "broken").unwrap();
+        // A second name for the same inode stands in for another container's mount
+        let other_view = dir.path().join("mounted.json");
+        fs::hard_link(&dest, &other_view).unwrap();
+
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(4);
+        header.set_mode(0o600);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "config/openclaw.json", &b"good"[..])
+            .unwrap();
+        let data = builder.into_inner().unwrap();
+
+        let mut archive = tar::Archive::new(&data[..]);
+        let mut entry = archive.entries().unwrap().next().unwrap().unwrap();
+        unpack(&mut entry, &dest).unwrap();
+
+        assert_eq!(fs::read_to_string(&other_view).unwrap(), "good");
+        assert_eq!(
+            fs::metadata(&dest).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+    }
+}
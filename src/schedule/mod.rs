@@ -0,0 +1,530 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::config::{BackupMode, Config};
+
+/// Time zone schedules and quiet hours are written in
+#[derive(Debug, Clone, Copy)]
+pub enum Zone {
+    Local,
+    Named(Tz),
+}
+
+impl Zone {
+    pub fn from_config(cfg: &Config) -> Result<Self> {
+        match &cfg.timezone {
+            None => Ok(Zone::Local),
+            Some(name) => name
+                .parse::<Tz>()
+                .map(Zone::Named)
+                .map_err(|_| anyhow::anyhow!("timezone: unknown time zone '{}'", name)),
+        }
+    }
+
+    /// Wall-clock time in this zone
+    fn wall_clock(&self, at: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Zone::Local => at.with_timezone(&Local).naive_local(),
+            Zone::Named(tz) => at.with_timezone(tz).naive_local(),
+        }
+    }
+
+    fn next_cron(&self, schedule: &cron::Schedule, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Zone::Local => next_in(schedule, &Local, after),
+            Zone::Named(tz) => next_in(schedule, tz, after),
+        }
+    }
+
+    pub fn format(&self, at: DateTime<Utc>) -> String {
+        match self {
+            Zone::Local => at.with_timezone(&Local).format("%a %Y-%m-%d %H:%M %Z"),
+            Zone::Named(tz) => at.with_timezone(tz).format("%a %Y-%m-%d %H:%M %Z"),
+        }
+        .to_string()
+    }
+}
+
+fn next_in<Z: TimeZone>(
+    schedule: &cron::Schedule,
+    tz: &Z,
+    after: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    schedule
+        .after(&after.with_timezone(tz))
+        .next()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+impl fmt::Display for Zone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Zone::Local => write!(f, "system local"),
+            Zone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+/// A daily time window, optionally limited to some weekdays:
+/// `02:00-03:00`, `mon-fri 09:00-18:00`, `sat,sun 22:00-06:00`.
+/// A window that ends before it starts runs past midnight and belongs to
+/// the day it starts on.
+#[derive(Debug, Clone)]
+pub struct TimeWindow {
+    spec: String,
+    days: Option<Vec<Weekday>>,
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl TimeWindow {
+    pub fn contains(&self, at: NaiveDateTime) -> bool {
+        let (day, time) = (at.weekday(), at.time());
+        let on = |d: Weekday| self.days.as_ref().is_none_or(|days| days.contains(&d));
+        if self.start < self.end {
+            on(day) && time >= self.start && time < self.end
+        } else {
+            (on(day) && time >= self.start) || (on(day.pred()) && time < self.end)
+        }
+    }
+}
+
+impl FromStr for TimeWindow {
+    type Err = anyhow::Error;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        let (days, times) = match spec.rsplit_once(' ') {
+            Some((days, times)) => (Some(parse_days(days.trim())?), times),
+            None => (None, spec),
+        };
+        let (start, end) = times
+            .split_once('-')
+            .ok_or_else(|| anyhow::anyhow!("expected HH:MM-HH:MM, got '{}'", times))?;
+        let time = |t: &str| {
+            NaiveTime::parse_from_str(t.trim(), "%H:%M")
+                .map_err(|_| anyhow::anyhow!("invalid time '{}' (use HH:MM)", t))
+        };
+        Ok(Self {
+            spec: spec.to_string(),
+            days,
+            start: time(start)?,
+            end: time(end)?,
+        })
+    }
+}
+
+impl fmt::Display for TimeWindow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.spec)
+    }
+}
+
+/// `mon-fri`, `sat,sun`, `mon,wed-fri`
+fn parse_days(s: &str) -> Result<Vec<Weekday>> {
+    let day = |d: &str| {
+        d.trim()
+            .parse::<Weekday>()
+            .map_err(|_| anyhow::anyhow!("invalid weekday '{}'", d))
+    };
+    let mut days = Vec::new();
+    for part in s.split(',') {
+        match part.split_once('-') {
+            Some((from, to)) => {
+                let (mut d, to) = (day(from)?, day(to)?);
+                days.push(d);
+                while d != to {
+                    d = d.succ();
+                    days.push(d);
+                }
+            }
+            None => days.push(day(part)?),
+        }
+    }
+    Ok(days)
+}
+
+/// A set of quiet-hour windows in the configured zone
+#[derive(Debug, Clone)]
+pub struct QuietHours {
+    windows: Vec<TimeWindow>,
+    zone: Zone,
+}
+
+impl QuietHours {
+    /// Parse windows from config; `key` names the setting in errors
+    pub fn new(specs: &[String], zone: Zone, key: &str) -> Result<Self> {
+        let windows = specs
+            .iter()
+            .enumerate()
+            .map(|(i, spec)| spec.parse().with_context(|| format!("{}[{}]", key, i)))
+            .collect::<Result<_>>()?;
+        Ok(Self { windows, zone })
+    }
+
+    /// Auto-restore suppression windows
+    pub fn health(cfg: &Config) -> Result<Self> {
+        Self::new(
+            &cfg.health.quiet_hours,
+            Zone::from_config(cfg)?,
+            "health.quietHours",
+        )
+    }
+
+    /// Windows with no scheduled backups
+    pub fn backup(cfg: &Config) -> Result<Self> {
+        Self::new(
+            &cfg.backup.quiet_hours,
+            Zone::from_config(cfg)?,
+            "backup.quietHours",
+        )
+    }
+
+    /// The window `at` falls in, if any
+    pub fn active(&self, at: DateTime<Utc>) -> Option<&TimeWindow> {
+        let local = self.zone.wall_clock(at);
+        self.windows.iter().find(|w| w.contains(local))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+}
+
+impl fmt::Display for QuietHours {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let windows: Vec<&str> = self.windows.iter().map(|w| w.spec.as_str()).collect();
+        write!(f, "{}", windows.join(", "))
+    }
+}
+
+/// A named backup schedule with its parsed cron expression
+pub struct NamedSchedule {
+    pub name: String,
+    cron: cron::Schedule,
+    pub include_sessions: Option<bool>,
+}
+
+/// One upcoming scheduled backup
+#[derive(Debug, Clone)]
+pub struct PlannedRun {
+    pub at: DateTime<Utc>,
+    pub schedule: String,
+    pub include_sessions: Option<bool>,
+    /// Quiet-hours window that will skip this run
+    pub skipped_by: Option<String>,
+}
+
+/// The backup schedules and quiet hours from config
+pub struct BackupPlan {
+    schedules: Vec<NamedSchedule>,
+    quiet: QuietHours,
+    zone: Zone,
+}
+
+/// Give up looking for a run outside quiet hours after this many candidates
+const MAX_CANDIDATES: usize = 10_000;
+
+impl BackupPlan {
+    pub fn from_config(cfg: &Config) -> Result<Self> {
+        let zone = Zone::from_config(cfg)?;
+        let schedules = cfg
+            .backup
+            .schedules
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                Ok(NamedSchedule {
+                    name: s.name.clone(),
+                    cron: parse_cron(&s.cron)
+                        .with_context(|| format!("backup.schedules[{}].cron", i))?,
+                    include_sessions: s.include_sessions,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            schedules,
+            quiet: QuietHours::backup(cfg)?,
+            zone,
+        })
+    }
+
+    /// The next `n` runs after `after` across all schedules, including
+    /// those quiet hours will skip
+    pub fn upcoming(&self, after: DateTime<Utc>, n: usize) -> Vec<PlannedRun> {
+        let mut runs = Vec::new();
+        let mut cursor = after;
+        while runs.len() < n {
+            let Some(run) = self.next_any(cursor) else {
+                break;
+            };
+            cursor = run.at;
+            runs.push(run);
+        }
+        runs
+    }
+
+    /// The next run that quiet hours don't skip
+    pub fn next(&self, after: DateTime<Utc>) -> Option<PlannedRun> {
+        let mut cursor = after;
+        for _ in 0..MAX_CANDIDATES {
+            let run = self.next_any(cursor)?;
+            if run.skipped_by.is_none() {
+                return Some(run);
+            }
+            cursor = run.at;
+        }
+        None
+    }
+
+    /// The next instant any schedule fires, as one run for every schedule
+    /// due then: sessions are included if any of them asks for it
+    fn next_any(&self, after: DateTime<Utc>) -> Option<PlannedRun> {
+        let due: Vec<(DateTime<Utc>, &NamedSchedule)> = self
+            .schedules
+            .iter()
+            .filter_map(|s| Some((self.zone.next_cron(&s.cron, after)?, s)))
+            .collect();
+        let at = due.iter().map(|(at, _)| *at).min()?;
+        let due: Vec<&NamedSchedule> = due
+            .into_iter()
+            .filter(|(t, _)| *t == at)
+            .map(|(_, s)| s)
+            .collect();
+        let flags = due.iter().map(|s| s.include_sessions);
+        let include_sessions = if flags.clone().any(|f| f == Some(true)) {
+            Some(true)
+        } else if flags.clone().any(|f| f.is_none()) {
+            None
+        } else {
+            Some(false)
+        };
+        Some(PlannedRun {
+            at,
+            schedule: due
+                .iter()
+                .map(|s| s.name.as_str())
+                .collect::<Vec<_>>()
+                .join(" + "),
+            include_sessions,
+            skipped_by: self.quiet.active(at).map(|w| w.to_string()),
+        })
+    }
+}
+
+/// Standard five-field cron, or six/seven fields with seconds (and year)
+pub fn parse_cron(expr: &str) -> Result<cron::Schedule> {
+    let expr = expr.trim();
+    let full = if expr.split_whitespace().count() == 5 {
+        format!("0 {}", expr)
+    } else {
+        expr.to_string()
+    };
+    cron::Schedule::from_str(&full)
+        .map_err(|e| anyhow::anyhow!("invalid cron expression '{}': {}", expr, e))
+}
+
+/// What `rescueclaw schedule` prints: mode, quiet hours and the next runs
+pub fn overview(cfg: &Config, n: usize) -> Result<String> {
+    let zone = Zone::from_config(cfg)?;
+    let backup_quiet = QuietHours::backup(cfg)?;
+    let health_quiet = QuietHours::health(cfg)?;
+    let mut out = format!("🗓️ Backup schedule (time zone: {})\n\n", zone);
+
+    match cfg.backup.mode {
+        BackupMode::Interval => {
+            out.push_str(&format!(
+                "  Every {}, counted from daemon start\n",
+                cfg.backup.interval
+            ));
+        }
+        BackupMode::Changes => {
+            out.push_str(&format!(
+                "  On changes, after {} quiet; at least {} and at most {} apart\n",
+                cfg.backup.debounce,
+                cfg.backup.min_interval,
+                cfg.backup.max_interval.unwrap_or(cfg.backup.interval)
+            ));
+        }
+        BackupMode::Schedule => {
+            let plan = BackupPlan::from_config(cfg)?;
+            for s in &cfg.backup.schedules {
+                out.push_str(&format!("  {:<12} {}\n", s.name, s.cron));
+            }
+            out.push_str("\n  Next runs:\n");
+            let runs = plan.upcoming(Utc::now(), n);
+            if runs.is_empty() {
+                out.push_str("    (none)\n");
+            }
+            for run in runs {
+                let full = if run.include_sessions == Some(true) {
+                    " (with sessions)"
+                } else {
+                    ""
+                };
+                out.push_str(&format!(
+                    "    {}  {}{}",
+                    zone.format(run.at),
+                    run.schedule,
+                    full
+                ));
+                if let Some(window) = run.skipped_by {
+                    out.push_str(&format!("  — skipped, quiet hours {}", window));
+                }
+                out.push('\n');
+            }
+        }
+    }
+
+    out.push('\n');
+    if !backup_quiet.is_empty() {
+        out.push_str(&format!("  Backup quiet hours: {}\n", backup_quiet));
+    }
+    if !health_quiet.is_empty() {
+        out.push_str(&format!("  Auto-restore suppressed: {}\n", health_quiet));
+    }
+    if let Some(window) = health_quiet.active(Utc::now()) {
+        out.push_str(&format!(
+            "  ⏸ Auto-restore is suppressed right now ({})\n",
+            window
+        ));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BackupSchedule;
+    use chrono::Timelike;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_time_windows() {
+        let w: TimeWindow = "02:00-03:00".parse().unwrap();
+        assert!(w.contains(at("2026-03-04 02:30")));
+        assert!(!w.contains(at("2026-03-04 03:00")));
+
+        // 2026-03-06 is a Friday
+        let w: TimeWindow = "fri 22:00-06:00".parse().unwrap();
+        assert!(w.contains(at("2026-03-06 23:00")));
+        assert!(w.contains(at("2026-03-07 05:59")));
+        assert!(!w.contains(at("2026-03-07 23:00")));
+
+        let w: TimeWindow = "mon-fri,sun 09:00-18:00".parse().unwrap();
+        assert!(w.contains(at("2026-03-08 10:00")));
+        assert!(!w.contains(at("2026-03-07 10:00")));
+
+        assert!("25:00-26:00".parse::<TimeWindow>().is_err());
+        assert!("funday 01:00-02:00".parse::<TimeWindow>().is_err());
+        assert!("01:00".parse::<TimeWindow>().is_err());
+    }
+
+    #[test]
+    fn test_plan_skips_quiet_hours() {
+        let mut cfg = Config {
+            timezone: Some("Europe/Berlin".to_string()),
+            ..Default::default()
+        };
+        cfg.backup.mode = BackupMode::Schedule;
+        cfg.backup.schedules = vec![
+            BackupSchedule {
+                name: "hourly".to_string(),
+                cron: "0 * * * *".to_string(),
+                include_sessions: None,
+            },
+            BackupSchedule {
+                name: "nightly".to_string(),
+                cron: "30 3 * * *".to_string(),
+                include_sessions: Some(true),
+            },
+        ];
+        cfg.backup.quiet_hours = vec!["02:00-03:00".to_string()];
+        let plan = BackupPlan::from_config(&cfg).unwrap();
+
+        // 00:30 UTC is 01:30 in Berlin (CET)
+        let start = Utc.with_ymd_and_hms(2026, 1, 10, 0, 30, 0).unwrap();
+        let runs = plan.upcoming(start, 4);
+        let summary: Vec<(String, bool)> = runs
+            .iter()
+            .map(|r| {
+                (
+                    Zone::Named(Tz::Europe__Berlin).format(r.at),
+                    r.skipped_by.is_some(),
+                )
+            })
+            .collect();
+        assert_eq!(summary[0], ("Sat 2026-01-10 02:00 CET".to_string(), true));
+        assert_eq!(summary[1], ("Sat 2026-01-10 03:00 CET".to_string(), false));
+        assert_eq!(runs[2].schedule, "nightly");
+        assert_eq!(runs[2].include_sessions, Some(true));
+
+        let next = plan.next(start).unwrap();
+        assert_eq!(next.at, Utc.with_ymd_and_hms(2026, 1, 10, 2, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_coinciding_schedules_run_once_with_sessions() {
+        let mut cfg = Config {
+            timezone: Some("UTC".to_string()),
+            ..Default::default()
+        };
+        cfg.backup.mode = BackupMode::Schedule;
+        cfg.backup.schedules = vec![
+            BackupSchedule {
+                name: "hourly".to_string(),
+                cron: "0 * * * *".to_string(),
+                include_sessions: Some(false),
+            },
+            BackupSchedule {
+                name: "nightly".to_string(),
+                cron: "0 3 * * *".to_string(),
+                include_sessions: Some(true),
+            },
+        ];
+        let plan = BackupPlan::from_config(&cfg).unwrap();
+
+        let start = Utc.with_ymd_and_hms(2026, 1, 10, 1, 30, 0).unwrap();
+        let runs = plan.upcoming(start, 3);
+        let summary: Vec<(u32, &str, Option<bool>)> = runs
+            .iter()
+            .map(|r| (r.at.hour(), r.schedule.as_str(), r.include_sessions))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (2, "hourly", Some(false)),
+                (3, "hourly + nightly", Some(true)),
+                (4, "hourly", Some(false)),
+            ]
+        );
+        let after_two = Utc.with_ymd_and_hms(2026, 1, 10, 2, 0, 0).unwrap();
+        assert_eq!(plan.next(after_two).unwrap().include_sessions, Some(true));
+    }
+
+    #[test]
+    fn test_config_errors_name_the_key() {
+        let mut cfg = Config::default();
+        cfg.backup.schedules = vec![BackupSchedule {
+            name: "bad".to_string(),
+            cron: "every day".to_string(),
+            include_sessions: None,
+        }];
+        let err = BackupPlan::from_config(&cfg).err().unwrap();
+        assert!(format!("{:#}", err).starts_with("backup.schedules[0].cron"));
+
+        cfg.backup.schedules.clear();
+        cfg.health.quiet_hours = vec!["02:00-03:00".to_string(), "2am".to_string()];
+        let err = QuietHours::health(&cfg).err().unwrap();
+        assert!(format!("{:#}", err).starts_with("health.quietHours[1]"));
+
+        cfg.timezone = Some("Mars/Olympus".to_string());
+        assert!(Zone::from_config(&cfg).is_err());
+    }
+}
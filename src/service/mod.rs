@@ -0,0 +1,624 @@
+use anyhow::{Context, Result};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config::Config;
+
+pub mod uninstall;
+
+const NAME: &str = "rescueclaw";
+
+/// Init systems the watchdog can be installed under
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Manager {
+    /// systemd system unit, running as your user
+    Systemd,
+    /// systemd user unit (`systemctl --user`), no root needed
+    SystemdUser,
+    Openrc,
+    Runit,
+}
+
+/// Which manager to use; detected from the running system when not given
+#[derive(Debug, Default, clap::Args)]
+pub struct ServiceOptions {
+    #[arg(long, value_enum)]
+    pub manager: Option<Manager>,
+    /// Install as a systemd user unit (same as --manager systemd-user)
+    #[arg(long, conflicts_with = "manager")]
+    pub user: bool,
+}
+
+impl ServiceOptions {
+    pub fn manager(&self) -> Result<Manager> {
+        if self.user {
+            return Ok(Manager::SystemdUser);
+        }
+        match self.manager {
+            Some(manager) => Ok(manager),
+            None => Manager::detect(),
+        }
+    }
+}
+
+/// What goes into a service definition, taken from the config
+#[derive(Debug, Clone)]
+pub struct ServiceSpec {
+    pub binary: PathBuf,
+    /// Account the daemon runs as (system services only)
+    pub user: String,
+    pub home: PathBuf,
+    /// `--config` to pass through, if the daemon isn't using the standard locations
+    pub config_file: Option<PathBuf>,
+    /// The only paths the sandboxed daemon may write to
+    pub writable: Vec<PathBuf>,
+}
+
+impl ServiceSpec {
+    pub fn new(cfg: &Config, config_file: Option<&Path>) -> Self {
+        let binary = std::env::current_exe()
+            .ok()
+            .unwrap_or_else(|| PathBuf::from("/usr/local/bin/rescueclaw"));
+        // Under sudo, run as the user who invoked it rather than root
+        let user = ["SUDO_USER", "USER"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|u| !u.is_empty())
+            .unwrap_or_else(|| "root".to_string());
+        let home = home_of(&user)
+            .or_else(dirs::home_dir)
+            .unwrap_or_else(|| PathBuf::from("/root"));
+        let config_file = config_file.map(|f| std::path::absolute(f).unwrap_or(f.to_path_buf()));
+        let config_dir = match &config_file {
+            Some(file) => file.parent().map(Path::to_path_buf),
+            None => Some(home.join(".config/rescueclaw")),
+        };
+
+        let mut writable = vec![cfg.backup.path.clone()];
+        writable.extend(
+            Path::new(crate::health::CHECKPOINT_REQUEST_PATH)
+                .parent()
+                .map(Path::to_path_buf),
+        );
+        writable.push(cfg.openclaw.workspace.clone());
+        writable.push(cfg.openclaw.config_path.clone());
+        // `/pair` and `users add` write the allowed users back to the config
+        writable.extend(config_dir);
+        let mut seen = std::collections::HashSet::new();
+        writable.retain(|p| !p.as_os_str().is_empty() && seen.insert(p.clone()));
+
+        Self {
+            binary,
+            user,
+            home,
+            config_file,
+            writable,
+        }
+    }
+
+    fn args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if let Some(file) = &self.config_file {
+            args.push("--config".to_string());
+            args.push(file.display().to_string());
+        }
+        args.push("start".to_string());
+        args
+    }
+}
+
+impl Manager {
+    /// The init system this host is running
+    pub fn detect() -> Result<Self> {
+        if Path::new("/run/systemd/system").is_dir() {
+            Ok(Manager::Systemd)
+        } else if Path::new("/run/openrc").is_dir() {
+            Ok(Manager::Openrc)
+        } else if Path::new("/run/runit").is_dir() || Path::new("/etc/runit").is_dir() {
+            Ok(Manager::Runit)
+        } else {
+            anyhow::bail!(
+                "Could not detect the init system — pick one with --manager systemd|systemd-user|openrc|runit"
+            )
+        }
+    }
+
+    /// The manager rescueclaw is currently installed under, if any
+    pub fn installed(home: &Path) -> Option<Self> {
+        [
+            Manager::Systemd,
+            Manager::SystemdUser,
+            Manager::Openrc,
+            Manager::Runit,
+        ]
+        .into_iter()
+        .find(|m| m.unit_path(home).exists())
+    }
+
+    /// Where the service definition lives
+    pub fn unit_path(&self, home: &Path) -> PathBuf {
+        match self {
+            Manager::Systemd => PathBuf::from("/etc/systemd/system/rescueclaw.service"),
+            Manager::SystemdUser => home.join(".config/systemd/user/rescueclaw.service"),
+            Manager::Openrc => PathBuf::from("/etc/init.d/rescueclaw"),
+            Manager::Runit => PathBuf::from("/etc/sv/rescueclaw/run"),
+        }
+    }
+
+    /// Only user units can be installed without root
+    fn privileged(&self) -> bool {
+        *self != Manager::SystemdUser
+    }
+
+    /// The service definition for `spec`
+    pub fn render(&self, spec: &ServiceSpec) -> String {
+        match self {
+            Manager::Systemd | Manager::SystemdUser => self.render_systemd(spec),
+            Manager::Openrc => render_openrc(spec),
+            Manager::Runit => render_runit(spec),
+        }
+    }
+
+    fn render_systemd(&self, spec: &ServiceSpec) -> String {
+        let system = *self == Manager::Systemd;
+        let exec = std::iter::once(spec.binary.display().to_string())
+            .chain(spec.args())
+            .map(|a| systemd_quote(&a))
+            .collect::<Vec<_>>()
+            .join(" ");
+        // '-' prefix: a path that doesn't exist yet is skipped instead of failing the start
+        let writable = spec
+            .writable
+            .iter()
+            .map(|p| systemd_quote(&format!("-{}", p.display())))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let mut unit = String::from(
+            "[Unit]
+Description=RescueClaw - AI Agent Watchdog
+After=network-online.target
+Wants=network-online.target
+# Crash loop protection: max 5 restarts per 5 minutes
+StartLimitBurst=5
+StartLimitIntervalSec=300
+
+[Service]
+Type=simple
+",
+        );
+        if system {
+            unit += &format!("User={}\n", spec.user);
+        }
+        unit += &format!(
+            "ExecStart={}
+ExecReload=/bin/kill -HUP $MAINPID
+Restart=on-failure
+RestartSec=15
+Environment=RUST_LOG=info
+",
+            exec
+        );
+        if system {
+            unit += &format!(
+                "Environment={}\n",
+                systemd_quote(&format!("HOME={}", spec.home.display()))
+            );
+        }
+        unit += &format!(
+            "
+# Security hardening
+NoNewPrivileges=true
+PrivateTmp=true
+ProtectSystem=strict
+ProtectHome=read-only
+ReadWritePaths={}
+ProtectKernelTunables=true
+ProtectKernelModules=true
+ProtectControlGroups=true
+RestrictSUIDSGID=true
+LockPersonality=true
+
+[Install]
+WantedBy={}
+",
+            writable,
+            if system {
+                "multi-user.target"
+            } else {
+                "default.target"
+            }
+        );
+        unit
+    }
+
+    /// Commands that register and start the service
+    fn enable_commands(&self) -> Vec<Vec<String>> {
+        let cmds: &[&[&str]] = match self {
+            Manager::Systemd => &[
+                &["systemctl", "daemon-reload"],
+                &["systemctl", "enable", "--now", NAME],
+            ],
+            Manager::SystemdUser => &[
+                &["systemctl", "--user", "daemon-reload"],
+                &["systemctl", "--user", "enable", "--now", NAME],
+            ],
+            Manager::Openrc => &[
+                &["rc-update", "add", NAME, "default"],
+                &["rc-service", NAME, "start"],
+            ],
+            Manager::Runit => {
+                let link = runit_service_dir().join(NAME);
+                return vec![to_strings(&["ln", "-sfn", "/etc/sv/rescueclaw"])
+                    .into_iter()
+                    .chain([link.display().to_string()])
+                    .collect()];
+            }
+        };
+        cmds.iter().map(|c| to_strings(c)).collect()
+    }
+
+    /// Commands that stop and deregister the service (before the files go)
+    fn disable_commands(&self) -> Vec<Vec<String>> {
+        let cmds: &[&[&str]] = match self {
+            Manager::Systemd => &[&["systemctl", "disable", "--now", NAME]],
+            Manager::SystemdUser => &[&["systemctl", "--user", "disable", "--now", NAME]],
+            Manager::Openrc => &[
+                &["rc-service", NAME, "stop"],
+                &["rc-update", "del", NAME, "default"],
+            ],
+            Manager::Runit => {
+                let link = runit_service_dir().join(NAME);
+                return vec![
+                    to_strings(&["sv", "down", NAME]),
+                    vec![
+                        "rm".to_string(),
+                        "-f".to_string(),
+                        link.display().to_string(),
+                    ],
+                ];
+            }
+        };
+        cmds.iter().map(|c| to_strings(c)).collect()
+    }
+
+    fn status_command(&self) -> Vec<String> {
+        to_strings(match self {
+            Manager::Systemd => &["systemctl", "status", "--no-pager", NAME],
+            Manager::SystemdUser => &["systemctl", "--user", "status", "--no-pager", NAME],
+            Manager::Openrc => &["rc-service", NAME, "status"],
+            Manager::Runit => &["sv", "status", NAME],
+        })
+    }
+
+    fn logs_hint(&self) -> &'static str {
+        match self {
+            Manager::Systemd => "sudo journalctl -u rescueclaw -f",
+            Manager::SystemdUser => "journalctl --user -u rescueclaw -f",
+            Manager::Openrc => "tail -f /var/log/rescueclaw.log",
+            Manager::Runit => "sv status rescueclaw (output goes to runsvdir's log)",
+        }
+    }
+}
+
+fn render_openrc(spec: &ServiceSpec) -> String {
+    let args = spec
+        .args()
+        .iter()
+        .map(|a| sh_quote(a))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        r#"#!/sbin/openrc-run
+
+name="rescueclaw"
+description="RescueClaw - AI Agent Watchdog"
+command={}
+command_args={}
+command_user={}
+supervisor=supervise-daemon
+# Crash loop protection: max 5 restarts per 5 minutes
+respawn_delay=15
+respawn_max=5
+respawn_period=300
+output_log="/var/log/rescueclaw.log"
+error_log="/var/log/rescueclaw.log"
+extra_started_commands="reload"
+
+export HOME={} RUST_LOG=info
+
+depend() {{
+	need net
+}}
+
+start_pre() {{
+	checkpath --file --owner "$command_user" /var/log/rescueclaw.log
+}}
+
+reload() {{
+	ebegin "Reloading $RC_SVCNAME config"
+	supervise-daemon "$RC_SVCNAME" --signal HUP
+	eend $?
+}}
+"#,
+        sh_quote(&spec.binary.display().to_string()),
+        dq_quote(&args),
+        sh_quote(&spec.user),
+        sh_quote(&spec.home.display().to_string()),
+    )
+}
+
+fn render_runit(spec: &ServiceSpec) -> String {
+    let exec = std::iter::once(spec.binary.display().to_string())
+        .chain(spec.args())
+        .map(|a| sh_quote(&a))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        r#"#!/bin/sh
+# Restarted by runsv when it exits; reload the config with `sv hup rescueclaw`
+exec 2>&1
+export HOME={} RUST_LOG=info
+exec chpst -u {} {}
+"#,
+        sh_quote(&spec.home.display().to_string()),
+        sh_quote(&spec.user),
+        exec
+    )
+}
+
+/// Write the service definition and start it
+pub fn install(manager: Manager, spec: &ServiceSpec) -> Result<()> {
+    let path = manager.unit_path(&spec.home);
+    println!(
+        "  Installing {:?} service at {}...",
+        manager,
+        path.display()
+    );
+
+    write_file(manager.privileged(), &path, &manager.render(spec))?;
+    if manager != Manager::Systemd && manager != Manager::SystemdUser {
+        // OpenRC and runit services are scripts
+        run(
+            manager.privileged(),
+            &to_strings(&["chmod", "755"]),
+            Some(&path),
+        )?;
+    }
+
+    let mut started = true;
+    for cmd in manager.enable_commands() {
+        started &= run(manager.privileged(), &cmd, None)?;
+    }
+    if started {
+        println!("  ✓ Service installed and started");
+        println!("  View logs: {}", manager.logs_hint());
+    } else {
+        println!("  ⚠ Service installed but failed to start");
+        println!("  Check: rescueclaw service status");
+    }
+    if manager == Manager::SystemdUser {
+        println!(
+            "  Tip: `loginctl enable-linger {}` keeps it running while you're logged out",
+            spec.user
+        );
+    }
+    Ok(())
+}
+
+/// Stop the service and remove its definition; `None` if it isn't installed
+pub fn uninstall(home: &Path) -> Result<Option<Manager>> {
+    let Some(manager) = Manager::installed(home) else {
+        return Ok(None);
+    };
+    let privileged = manager.privileged();
+    for cmd in manager.disable_commands() {
+        // Best effort: the service may already be stopped
+        run(privileged, &cmd, None)?;
+    }
+    let path = manager.unit_path(home);
+    let remove = match manager {
+        Manager::Runit => path.parent().unwrap_or(&path).to_path_buf(),
+        _ => path,
+    };
+    if !run(privileged, &to_strings(&["rm", "-rf"]), Some(&remove))? || remove.exists() {
+        anyhow::bail!(
+            "Stopped the {:?} service but could not remove {}",
+            manager,
+            remove.display()
+        );
+    }
+    match manager {
+        Manager::Systemd => {
+            run(true, &to_strings(&["systemctl", "daemon-reload"]), None)?;
+        }
+        Manager::SystemdUser => {
+            run(
+                false,
+                &to_strings(&["systemctl", "--user", "daemon-reload"]),
+                None,
+            )?;
+        }
+        _ => {}
+    }
+    Ok(Some(manager))
+}
+
+/// Show the service manager's view of the service
+pub fn status(home: &Path) -> Result<()> {
+    let Some(manager) = Manager::installed(home) else {
+        println!("Service: not installed (run `rescueclaw service install`)");
+        return Ok(());
+    };
+    println!(
+        "Service: {:?} ({})",
+        manager,
+        manager.unit_path(home).display()
+    );
+    run(false, &manager.status_command(), None)?;
+    Ok(())
+}
+
+/// Write `content` to `path`, through `sudo tee` when we may not write there
+fn write_file(privileged: bool, path: &Path, content: &str) -> Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("/"));
+    if !privileged || is_root() {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(path, content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        return Ok(());
+    }
+
+    run(true, &to_strings(&["mkdir", "-p"]), Some(dir))?;
+    let mut child = Command::new("sudo")
+        .arg("tee")
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        use std::io::Write;
+        stdin.write_all(content.as_bytes())?;
+    }
+    if !child.wait()?.success() {
+        anyhow::bail!("Failed to write {}", path.display());
+    }
+    Ok(())
+}
+
+/// Run a command (with sudo if it needs root and we aren't); false if it failed
+fn run(privileged: bool, cmd: &[String], path: Option<&Path>) -> Result<bool> {
+    let mut command = if privileged && !is_root() {
+        let mut c = Command::new("sudo");
+        c.args(cmd);
+        c
+    } else {
+        let mut c = Command::new(&cmd[0]);
+        c.args(&cmd[1..]);
+        c
+    };
+    if let Some(path) = path {
+        command.arg(path);
+    }
+    let status = command
+        .status()
+        .with_context(|| format!("Failed to run {}", cmd[0]))?;
+    Ok(status.success())
+}
+
+pub(crate) fn is_root() -> bool {
+    // /proc/self belongs to the effective user
+    std::fs::metadata("/proc/self").is_ok_and(|m| m.uid() == 0)
+}
+
+/// Home directory of `user` from /etc/passwd
+fn home_of(user: &str) -> Option<PathBuf> {
+    let passwd = std::fs::read_to_string("/etc/passwd").ok()?;
+    passwd.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        (fields.len() > 5 && fields[0] == user).then(|| PathBuf::from(fields[5]))
+    })
+}
+
+/// Where runsvdir looks for enabled services (differs between distros)
+fn runit_service_dir() -> PathBuf {
+    [
+        "/var/service",
+        "/etc/runit/runsvdir/default",
+        "/etc/service",
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .find(|d| d.is_dir())
+    .unwrap_or_else(|| PathBuf::from("/var/service"))
+}
+
+fn to_strings(args: &[&str]) -> Vec<String> {
+    args.iter().map(|a| a.to_string()).collect()
+}
+
+fn systemd_quote(s: &str) -> String {
+    if s.chars()
+        .any(|c| c.is_whitespace() || c == '"' || c == '\\')
+    {
+        format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// For OpenRC's `command_args`, which is expanded with `eval`
+fn dq_quote(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        if matches!(c, '"' | '$' | '`' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+    out
+}
+
+fn sh_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> ServiceSpec {
+        ServiceSpec {
+            binary: PathBuf::from("/usr/local/bin/rescueclaw"),
+            user: "opc".to_string(),
+            home: PathBuf::from("/srv/opc"),
+            config_file: Some(PathBuf::from("/etc/rescue claw/rescueclaw.toml")),
+            writable: vec![
+                PathBuf::from("/var/rescueclaw/backups"),
+                PathBuf::from("/srv/opc/clawd"),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_systemd_units_are_sandboxed() {
+        let unit = Manager::Systemd.render(&spec());
+        assert!(unit.contains("User=opc\n"));
+        assert!(unit.contains("Environment=HOME=/srv/opc\n"));
+        assert!(unit.contains(
+            "ExecStart=/usr/local/bin/rescueclaw --config \"/etc/rescue claw/rescueclaw.toml\" start\n"
+        ));
+        assert!(unit.contains("ProtectSystem=strict\n"));
+        assert!(unit.contains("ReadWritePaths=-/var/rescueclaw/backups -/srv/opc/clawd\n"));
+        assert!(unit.contains("WantedBy=multi-user.target\n"));
+
+        let user = Manager::SystemdUser.render(&spec());
+        assert!(!user.contains("User="));
+        assert!(!user.contains("HOME="));
+        assert!(user.contains("ProtectSystem=strict\n"));
+        assert!(user.contains("WantedBy=default.target\n"));
+        assert_eq!(
+            Manager::SystemdUser.unit_path(Path::new("/srv/opc")),
+            PathBuf::from("/srv/opc/.config/systemd/user/rescueclaw.service")
+        );
+    }
+
+    #[test]
+    fn test_openrc_and_runit_scripts() {
+        let openrc = Manager::Openrc.render(&spec());
+        assert!(openrc.starts_with("#!/sbin/openrc-run\n"));
+        assert!(openrc.contains("command='/usr/local/bin/rescueclaw'\n"));
+        assert!(openrc
+            .contains("command_args=\"'--config' '/etc/rescue claw/rescueclaw.toml' 'start'\"\n"));
+        assert!(openrc.contains("command_user='opc'\n"));
+        assert!(openrc.contains("export HOME='/srv/opc'"));
+
+        let runit = Manager::Runit.render(&spec());
+        assert!(runit.starts_with("#!/bin/sh\n"));
+        assert!(runit.contains(
+            "exec chpst -u 'opc' '/usr/local/bin/rescueclaw' '--config' '/etc/rescue claw/rescueclaw.toml' 'start'\n"
+        ));
+    }
+}
@@ -0,0 +1,290 @@
+use anyhow::{Context, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+
+/// Options for `rescueclaw uninstall`
+#[derive(Debug, Default, clap::Args)]
+pub struct UninstallOptions {
+    /// Also delete the backups, rescueclaw's state and its config files
+    #[arg(long)]
+    pub purge: bool,
+    /// First write all snapshots and the config into this .tar.gz
+    #[arg(long, value_name = "FILE")]
+    pub export: Option<PathBuf>,
+    /// Don't ask before purging
+    #[arg(long, short, requires = "purge")]
+    pub yes: bool,
+    /// Purge backup.path even if it is /, a home directory, or holds the
+    /// agent's workspace or config
+    #[arg(long, requires = "purge")]
+    pub force: bool,
+}
+
+/// Something `--purge` deletes
+struct Target {
+    path: PathBuf,
+    what: String,
+}
+
+/// Remove the service, and with `--purge` everything rescueclaw wrote.
+/// `config_files` are the config files in use, `home` the service owner's.
+/// Returns what was removed, one line per item.
+pub fn uninstall(
+    cfg: &Config,
+    config_files: &[PathBuf],
+    home: &Path,
+    opts: &UninstallOptions,
+) -> Result<Vec<String>> {
+    let mut removed = vec![];
+
+    // Export first: nothing is touched if it fails
+    if let Some(archive) = &opts.export {
+        let archive = std::path::absolute(archive)?;
+        export(cfg, config_files, &archive)
+            .with_context(|| format!("Export to {} failed — nothing removed", archive.display()))?;
+        println!("  ✓ Exported backups and config to {}", archive.display());
+    }
+
+    if opts.purge && !opts.force {
+        if let Some(reason) = unsafe_backup_path(cfg, home) {
+            anyhow::bail!(
+                "Refusing to purge backup.path {}: {}. Nothing removed (--force overrides this).",
+                cfg.backup.path.display(),
+                reason
+            );
+        }
+    }
+
+    let targets = if opts.purge {
+        purge_targets(
+            cfg,
+            config_files,
+            Path::new(crate::health::CHECKPOINT_REQUEST_PATH),
+        )
+    } else {
+        vec![]
+    };
+    if !targets.is_empty() && !opts.yes {
+        println!("  --purge will delete:");
+        for target in &targets {
+            println!("    {} ({})", target.path.display(), target.what);
+        }
+        if opts.export.is_none() {
+            println!("  Nothing is exported (use --export <file> to keep a copy).");
+        }
+        if !crate::config::prompt_yn("Delete these? This cannot be undone [n]: ", false)? {
+            anyhow::bail!("Aborted — nothing removed");
+        }
+    }
+
+    let mut failed = vec![];
+    match super::uninstall(home) {
+        Ok(Some(manager)) => removed.push(format!("{:?} service", manager)),
+        Ok(None) => {}
+        Err(e) => failed.push(format!("{:#}", e)),
+    }
+
+    failed.extend(purge(targets, config_files, &mut removed));
+    if !failed.is_empty() {
+        anyhow::bail!(
+            "Removed {} item(s), but could not remove:\n  {}\n(try again with sudo)",
+            removed.len(),
+            failed.join("\n  ")
+        );
+    }
+    Ok(removed)
+}
+
+/// Delete the targets, adding them to `removed`; returns the failures
+fn purge(targets: Vec<Target>, config_files: &[PathBuf], removed: &mut Vec<String>) -> Vec<String> {
+    let mut failed = vec![];
+    for target in targets {
+        let result = if target.path.is_dir() {
+            fs::remove_dir_all(&target.path)
+        } else {
+            fs::remove_file(&target.path)
+        };
+        match result {
+            Ok(()) => removed.push(format!("{} ({})", target.path.display(), target.what)),
+            Err(e) => failed.push(format!("{}: {}", target.path.display(), e)),
+        }
+    }
+    // Directories left empty by the config files
+    for file in config_files {
+        if let Some(dir) = file.parent().filter(|d| !d.as_os_str().is_empty()) {
+            if fs::remove_dir(dir).is_ok() {
+                removed.push(format!("{} (empty config directory)", dir.display()));
+            }
+        }
+    }
+    failed
+}
+
+/// Why deleting `backup.path` wholesale would destroy more than backups
+fn unsafe_backup_path(cfg: &Config, home: &Path) -> Option<String> {
+    let canonical = |p: &Path| fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+    let backups = canonical(&cfg.backup.path);
+    if backups.parent().is_none() {
+        return Some("it is the root directory".to_string());
+    }
+    let homes = [Some(home.to_path_buf()), dirs::home_dir()];
+    if homes.iter().flatten().any(|h| canonical(h) == backups) {
+        return Some("it is a home directory".to_string());
+    }
+    [
+        ("workspace", &cfg.openclaw.workspace),
+        ("configPath", &cfg.openclaw.config_path),
+    ]
+    .into_iter()
+    .filter(|(_, p)| !p.as_os_str().is_empty())
+    .find(|(_, p)| canonical(p).starts_with(&backups))
+    .map(|(key, p)| format!("it contains openclaw.{} {}", key, p.display()))
+}
+
+/// The backup directory, the checkpoint request file (`state`) and the
+/// config files that exist
+fn purge_targets(cfg: &Config, config_files: &[PathBuf], state: &Path) -> Vec<Target> {
+    let mut targets = vec![];
+    if cfg.backup.path.is_dir() {
+        let snapshots = crate::backup::list_snapshots(cfg)
+            .map(|s| s.len())
+            .unwrap_or(0);
+        targets.push(Target {
+            path: cfg.backup.path.clone(),
+            what: format!(
+                "backups: {} snapshot(s), incidents, change journal",
+                snapshots
+            ),
+        });
+    }
+    if state.is_file() && !state.starts_with(&cfg.backup.path) {
+        targets.push(Target {
+            path: state.to_path_buf(),
+            what: "pending checkpoint request".to_string(),
+        });
+    }
+    for file in config_files.iter().filter(|f| f.is_file()) {
+        targets.push(Target {
+            path: file.clone(),
+            what: "config".to_string(),
+        });
+    }
+    targets
+}
+
+/// One .tar.gz with the backup directory under `backups/` and each config
+/// file under `config/<its absolute path>`
+fn export(cfg: &Config, config_files: &[PathBuf], archive: &Path) -> Result<()> {
+    // It would end up archiving itself, and deleted by --purge
+    if archive.starts_with(&cfg.backup.path) {
+        anyhow::bail!(
+            "Export to a file outside the backup directory {}",
+            cfg.backup.path.display()
+        );
+    }
+    if let Some(dir) = archive.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let enc = GzEncoder::new(fs::File::create(archive)?, Compression::default());
+    let mut tar = tar::Builder::new(enc);
+    if cfg.backup.path.is_dir() {
+        tar.append_dir_all("backups", &cfg.backup.path)?;
+    }
+    for file in config_files.iter().filter(|f| f.is_file()) {
+        let file = std::path::absolute(file)?;
+        let name = Path::new("config").join(file.strip_prefix("/").unwrap_or(&file));
+        tar.append_path_with_name(&file, name)?;
+    }
+    tar.into_inner()?.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_then_purge() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut cfg = Config::default();
+        cfg.backup.path = dir.path().join("backups");
+        fs::create_dir_all(cfg.backup.path.join("incidents")).unwrap();
+        fs::write(
+            cfg.backup.path.join("backup-20260301-120000.tar.gz"),
+            "snap",
+        )
+        .unwrap();
+        let config_file = dir.path().join("conf/rescueclaw.json");
+        fs::create_dir_all(config_file.parent().unwrap()).unwrap();
+        fs::write(&config_file, "{}").unwrap();
+        let state = dir.path().join("checkpoint-request.json");
+        fs::write(&state, "{}").unwrap();
+        let files = [config_file.clone()];
+
+        assert!(export(&cfg, &files, &cfg.backup.path.join("export.tar.gz")).is_err());
+        let archive = dir.path().join("export.tar.gz");
+        export(&cfg, &files, &archive).unwrap();
+
+        let targets = purge_targets(&cfg, &files, &state);
+        assert_eq!(targets.len(), 3);
+        assert!(targets[0].what.contains("1 snapshot(s)"));
+        let mut removed = vec![];
+        assert!(purge(targets, &files, &mut removed).is_empty());
+        assert_eq!(removed.len(), 4, "{:?}", removed);
+        assert!(!cfg.backup.path.exists());
+        assert!(!state.exists());
+        assert!(!config_file.parent().unwrap().exists());
+        assert!(archive.exists());
+
+        let mut names: Vec<String> = tar::Archive::new(flate2::read::GzDecoder::new(
+            fs::File::open(&archive).unwrap(),
+        ))
+        .entries()
+        .unwrap()
+        .map(|e| e.unwrap().path().unwrap().display().to_string())
+        .collect();
+        names.sort();
+        assert!(names.contains(&"backups/backup-20260301-120000.tar.gz".to_string()));
+        let config_name = Path::new("config")
+            .join(config_file.strip_prefix("/").unwrap())
+            .display()
+            .to_string();
+        assert!(names.contains(&config_name), "{:?}", names);
+    }
+
+    #[test]
+    fn test_refuses_to_purge_more_than_backups() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let home = dir.path().join("home");
+        fs::create_dir_all(home.join("clawd")).unwrap();
+        let mut cfg = Config::default();
+        cfg.openclaw.workspace = home.join("clawd");
+        cfg.openclaw.config_path = home.join(".openclaw");
+
+        cfg.backup.path = "/".into();
+        assert!(unsafe_backup_path(&cfg, &home).unwrap().contains("root"));
+        // Spelled differently, still the home directory
+        cfg.backup.path = home.join("clawd/..");
+        assert!(unsafe_backup_path(&cfg, &home).unwrap().contains("home"));
+        cfg.backup.path = dir.path().to_path_buf();
+        assert!(unsafe_backup_path(&cfg, &home)
+            .unwrap()
+            .contains("openclaw.workspace"));
+        cfg.backup.path = home.join("backups");
+        assert_eq!(unsafe_backup_path(&cfg, &home), None);
+
+        cfg.backup.path = home.clone();
+        let opts = UninstallOptions {
+            purge: true,
+            yes: true,
+            ..Default::default()
+        };
+        let err = uninstall(&cfg, &[], &home, &opts).unwrap_err().to_string();
+        assert!(err.starts_with("Refusing to purge"), "{}", err);
+        assert!(home.join("clawd").exists());
+    }
+}
@@ -1,7 +1,57 @@
+pub mod pairing;
+
 use anyhow::Result;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use teloxide::prelude::*;
+use teloxide::types::InputFile;
+
+use crate::config::{Config, LiveConfig};
+use crate::redact::Redactor;
+
+/// Largest document we will send (Telegram's bot API allows 50MB; we stay well below)
+const MAX_DOCUMENT_BYTES: usize = 10 * 1024 * 1024;
+
+/// Default and maximum number of gateway.log lines for /gatewaylog
+const GATEWAY_LOG_LINES: usize = 200;
+const GATEWAY_LOG_MAX_LINES: usize = 2000;
+
+/// A reply to a Telegram command
+enum Reply {
+    Text(String),
+    Document {
+        file_name: String,
+        content: Vec<u8>,
+        caption: String,
+    },
+}
+
+impl Reply {
+    /// Mask secrets in everything that is about to leave the box
+    fn redacted(self, redactor: &Redactor) -> Self {
+        match self {
+            Reply::Text(text) => Reply::Text(redactor.redact(&text)),
+            Reply::Document {
+                file_name,
+                content,
+                caption,
+            } => Reply::Document {
+                file_name,
+                content: match String::from_utf8(content) {
+                    Ok(text) => redactor.redact(&text).into_bytes(),
+                    Err(e) => e.into_bytes(),
+                },
+                caption: redactor.redact(&caption),
+            },
+        }
+    }
+}
 
-use crate::config::Config;
+impl From<String> for Reply {
+    fn from(text: String) -> Self {
+        Reply::Text(text)
+    }
+}
 
 /// Validate the Telegram bot token before starting the listener
 pub async fn validate_token(token: &str) -> Result<String> {
@@ -21,8 +71,107 @@ pub async fn validate_token(token: &str) -> Result<String> {
     Ok(bot_name)
 }
 
+/// Someone who messaged the bot
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sender {
+    pub id: i64,
+    pub username: Option<String>,
+    pub name: String,
+}
+
+impl std::fmt::Display for Sender {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.username {
+            Some(username) => write!(f, "{} (@{}, {})", self.name, username, self.id),
+            None => write!(f, "{} ({})", self.name, self.id),
+        }
+    }
+}
+
+/// Wait up to `wait` for people to message the bot and return who did. Reads
+/// the bot's update queue, so it fails while the daemon is polling the same bot.
+pub async fn poll_senders(token: &str, wait: std::time::Duration) -> Result<Vec<Sender>> {
+    let client = reqwest::Client::new();
+    let url = format!("https://api.telegram.org/bot{}/getUpdates", token);
+    let deadline = tokio::time::Instant::now() + wait;
+    let mut offset: Option<i64> = None;
+    let mut senders = Vec::new();
+
+    while senders.is_empty() {
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        let timeout = remaining.as_secs().clamp(1, 30);
+        let resp = client
+            .get(format!(
+                "{}?timeout={}&offset={}",
+                url,
+                timeout,
+                offset.unwrap_or(0)
+            ))
+            .timeout(std::time::Duration::from_secs(timeout + 10))
+            .send()
+            .await?;
+        if resp.status() == reqwest::StatusCode::CONFLICT {
+            anyhow::bail!(
+                "Another process is reading this bot's messages (is the rescueclaw daemon running?) — stop it, or pair from Telegram with /pair"
+            );
+        }
+        if !resp.status().is_success() {
+            anyhow::bail!("Telegram getUpdates failed: {}", resp.status());
+        }
+        let json: serde_json::Value = resp.json().await?;
+        let (found, last) = senders_from_updates(&json);
+        offset = last.map(|id| id + 1).or(offset);
+        for sender in found {
+            if !senders.iter().any(|s: &Sender| s.id == sender.id) {
+                senders.push(sender);
+            }
+        }
+    }
+
+    // Acknowledge what we read so the daemon doesn't answer it later
+    if let Some(offset) = offset {
+        let _ = client
+            .get(format!("{}?timeout=0&offset={}", url, offset))
+            .send()
+            .await;
+    }
+    Ok(senders)
+}
+
+/// Senders of the messages in a getUpdates response, and the last update ID
+fn senders_from_updates(json: &serde_json::Value) -> (Vec<Sender>, Option<i64>) {
+    let mut senders = Vec::new();
+    let mut last = None;
+    for update in json["result"].as_array().into_iter().flatten() {
+        last = update["update_id"].as_i64().max(last);
+        let from = &update["message"]["from"];
+        let Some(id) = from["id"].as_i64() else {
+            continue;
+        };
+        if from["is_bot"].as_bool() == Some(true) {
+            continue;
+        }
+        let name = [from["first_name"].as_str(), from["last_name"].as_str()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        senders.push(Sender {
+            id,
+            username: from["username"].as_str().map(String::from),
+            name,
+        });
+    }
+    (senders, last)
+}
+
 /// Start the Telegram bot listener — validates token first, returns error instead of panicking
-pub async fn listen(cfg: &Config) -> Result<()> {
+/// `config_file` is the `--config` override, where `/pair` adds new users.
+pub async fn listen(live: &LiveConfig, config_file: Option<PathBuf>) -> Result<()> {
+    let cfg = live.current();
     if cfg.telegram.token.is_empty() {
         tracing::warn!("No Telegram token configured — running without Telegram control");
         // Sleep forever so tokio::select doesn't exit
@@ -41,7 +190,7 @@ pub async fn listen(cfg: &Config) -> Result<()> {
                 "Telegram bot token invalid: {} — running without Telegram control",
                 e
             );
-            tracing::error!("Fix the token in ~/.config/rescueclaw/rescueclaw.json and restart");
+            tracing::error!("Fix telegram.token (or $RESCUECLAW_TELEGRAM_TOKEN) and restart");
             // Sleep forever — health checks and backups still run
             loop {
                 tokio::time::sleep(tokio::time::Duration::from_secs(3600)).await;
@@ -50,25 +199,56 @@ pub async fn listen(cfg: &Config) -> Result<()> {
     }
 
     let bot = Bot::new(&cfg.telegram.token);
-    let allowed_users = cfg.telegram.allowed_users.clone();
-    let cfg_clone = cfg.clone();
+    let live = live.clone();
+    let pairing = Arc::new(Mutex::new(pairing::Pairing::default()));
 
     teloxide::repl(bot, move |bot: Bot, msg: Message| {
-        let allowed = allowed_users.clone();
-        let cfg = cfg_clone.clone();
+        // Per message, so reloaded allowed users and settings apply at once
+        let cfg = live.current();
+        let pairing = pairing.clone();
+        let config_file = config_file.clone();
 
         async move {
             let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+            let text = msg.text().unwrap_or("");
+
+            // Before the auth check: a new user pairs with a code
+            if command_name(text) == "/pair" {
+                let name = msg.from.as_ref().map(|u| u.full_name()).unwrap_or_default();
+                let reply = cmd_pair(&cfg, &pairing, config_file.as_deref(), user_id, text);
+                bot.send_message(msg.chat.id, reply.text).await?;
+                if let Some(admin) = reply.notify_admin {
+                    let note = format!("👤 Paired {} ({}) with your code", name, user_id);
+                    if let Err(e) = bot.send_message(ChatId(admin), note).await {
+                        tracing::warn!("Failed to tell admin {} about pairing: {}", admin, e);
+                    }
+                }
+                return Ok(());
+            }
 
             // Auth check
+            let allowed = &cfg.telegram.allowed_users;
             if !allowed.is_empty() && !allowed.contains(&user_id) {
                 bot.send_message(msg.chat.id, "⛔ Unauthorized").await?;
                 return Ok(());
             }
 
-            let text = msg.text().unwrap_or("");
-            let response = handle_command(text, &cfg).await;
-            bot.send_message(msg.chat.id, response).await?;
+            let reply = handle_command(text, &cfg).await;
+            match reply.redacted(&Redactor::for_config(&cfg)) {
+                Reply::Text(response) => {
+                    bot.send_message(msg.chat.id, response).await?;
+                }
+                Reply::Document {
+                    file_name,
+                    content,
+                    caption,
+                } => {
+                    let file = InputFile::memory(content).file_name(file_name);
+                    bot.send_document(msg.chat.id, file)
+                        .caption(caption)
+                        .await?;
+                }
+            }
 
             Ok(())
         }
@@ -78,27 +258,137 @@ pub async fn listen(cfg: &Config) -> Result<()> {
     Ok(())
 }
 
+/// Push an unsolicited message to every allowed user (private chats share the user's ID)
+pub async fn notify(cfg: &Config, text: &str) {
+    if cfg.telegram.token.is_empty() || cfg.telegram.allowed_users.is_empty() {
+        tracing::info!("Telegram notifications not configured: {}", text);
+        return;
+    }
+
+    let bot = Bot::new(&cfg.telegram.token);
+    let text = Redactor::for_config(cfg).redact(text);
+    for user in &cfg.telegram.allowed_users {
+        if let Err(e) = bot.send_message(ChatId(*user), text.clone()).await {
+            tracing::warn!("Failed to notify Telegram user {}: {}", user, e);
+        }
+    }
+}
+
+struct PairReply {
+    text: String,
+    /// Admin whose code was just used
+    notify_admin: Option<i64>,
+}
+
+/// `/pair` from an admin issues a code; `/pair <code>` from anyone else
+/// redeems it and adds them to `telegram.allowedUsers`
+fn cmd_pair(
+    cfg: &Config,
+    pairing: &Mutex<pairing::Pairing>,
+    config_file: Option<&std::path::Path>,
+    user_id: i64,
+    text: &str,
+) -> PairReply {
+    let reply = |text: String| PairReply {
+        text,
+        notify_admin: None,
+    };
+    let telegram = &cfg.telegram;
+    let allowed = telegram.allowed_users.contains(&user_id);
+    let mut pairing = pairing.lock().unwrap_or_else(|e| e.into_inner());
+
+    let Some(code) = text.split_whitespace().nth(1) else {
+        let admin = allowed && (telegram.admins.is_empty() || telegram.admins.contains(&user_id));
+        if !admin {
+            return reply("⛔ Only admins can create pairing codes".to_string());
+        }
+        let code = pairing.issue(user_id, std::time::Instant::now());
+        tracing::info!("Pairing code issued by {}", user_id);
+        return reply(format!(
+            "🔑 Pairing code: {}\n\nValid for {} minutes, once. The new user sends this to the bot:\n/pair {}",
+            code,
+            pairing::CODE_TTL.as_secs() / 60,
+            code
+        ));
+    };
+
+    if allowed {
+        return reply(
+            "You're already allowed to use this bot. Send /pair without a code to invite someone."
+                .to_string(),
+        );
+    }
+    let admin = match pairing.redeem(code, std::time::Instant::now()) {
+        Ok(admin) => admin,
+        Err(e) => {
+            tracing::warn!("Pairing attempt by {} failed: {}", user_id, e);
+            return reply(format!("❌ Pairing failed: {}", e));
+        }
+    };
+    match crate::config::users::add_users(config_file, &[user_id]) {
+        Ok(update) => {
+            tracing::info!(
+                "Paired Telegram user {} (code from {}), config: {}",
+                user_id,
+                admin,
+                update
+                    .file
+                    .map(|f| f.display().to_string())
+                    .unwrap_or_else(|| "unchanged".to_string())
+            );
+            PairReply {
+                text: "✅ Paired! You can now control RescueClaw — try /help".to_string(),
+                notify_admin: Some(admin),
+            }
+        }
+        Err(e) => {
+            tracing::error!("Pairing {} failed: {:#}", user_id, e);
+            reply(format!("❌ Pairing failed: {:#}", e))
+        }
+    }
+}
+
 /// Route Telegram commands to handlers
-async fn handle_command(text: &str, cfg: &Config) -> String {
+/// The command word, lowercased and without the `@botname` Telegram adds
+/// in groups
+fn command_name(text: &str) -> String {
+    let word = text.split_whitespace().next().unwrap_or_default();
+    let word = word.split_once('@').map_or(word, |(cmd, _)| cmd);
+    word.to_lowercase()
+}
+
+async fn handle_command(text: &str, cfg: &Config) -> Reply {
     let parts: Vec<&str> = text.split_whitespace().collect();
-    let cmd = parts.first().map(|s| s.to_lowercase()).unwrap_or_default();
+    let cmd = command_name(text);
 
     match cmd.as_str() {
-        "/start" | "/help" => help_text(),
-        "/status" => cmd_status(cfg).await,
+        "/start" | "/help" => help_text().into(),
+        "/status" => cmd_status(cfg).await.into(),
         "/rescue" => {
             let id = parts.get(1).copied();
             if id == Some("list") {
-                cmd_list(cfg)
+                cmd_list(cfg).into()
             } else {
-                cmd_rescue(cfg, id).await
+                cmd_rescue(cfg, id).await.into()
             }
         }
-        "/backup" => cmd_backup(cfg),
-        "/logs" => cmd_logs(cfg),
-        "/rollback" => cmd_rescue(cfg, None).await, // rollback = restore latest
-        "/health" => cmd_status(cfg).await,
-        _ => "Unknown command. Try /help".to_string(),
+        "/backup" => cmd_backup(cfg).into(),
+        "/logs" => cmd_logs(cfg).into(),
+        "/rollback" => cmd_rescue(cfg, None).await.into(), // rollback = restore latest
+        "/health" => cmd_status(cfg).await.into(),
+        "/report" => cmd_report(cfg, parts.get(1).copied()),
+        "/get" => match (parts.get(1), parts.get(2)) {
+            (Some(id), Some(path)) => cmd_get(cfg, id, path),
+            _ => "Usage: /get <snapshot> <path>".to_string().into(),
+        },
+        "/diff" => match parts.get(1) {
+            Some(id) => cmd_diff(cfg, id),
+            None => "Usage: /diff <snapshot>".to_string().into(),
+        },
+        "/gatewaylog" => cmd_gateway_log(cfg, parts.get(1).copied()),
+        "/analyze" => cmd_analyze(cfg, parts.get(1) == Some(&"offline")).await,
+        "/insights" => cmd_insights(cfg, parts.get(1).copied()).into(),
+        _ => "Unknown command. Try /help".to_string().into(),
     }
 }
 
@@ -111,7 +401,14 @@ fn help_text() -> String {
      /backup — Take a snapshot now\n\
      /logs — Recent incidents\n\
      /rollback — Undo last change\n\
-     /health — Detailed health report"
+     /health — Detailed health report\n\
+     /analyze [offline] — Run incident analysis now\n\
+     /insights [days] — Incident patterns (default 30 days)\n\
+     /report [id] — Latest (or specific) incident report\n\
+     /get <id> <path> — Download a file from a backup\n\
+     /diff <id> — Diff a backup against current files\n\
+     /gatewaylog [lines] — Tail of gateway.log\n\
+     /pair — Pairing code for a new user (admins)"
         .to_string()
 }
 
@@ -166,8 +463,22 @@ async fn cmd_rescue(cfg: &Config, id: Option<&str>) -> String {
         label
     );
 
+    let category = crate::analysis::classify_current(cfg).map(|c| c.slug());
     match crate::restore::restore(cfg, id).await {
-        Ok(_) => format!("✅ Agent restored and online!\n\nRestored from: {}", label),
+        Ok(outcome) => {
+            crate::incidents::record_restore(
+                cfg,
+                category,
+                "manual (telegram)",
+                "manual-restore",
+                &outcome,
+                None,
+            );
+            format!(
+                "✅ Agent restored and online!\n\nRestored from: {}",
+                outcome.snapshot_id
+            )
+        }
         Err(e) => format!(
             "❌ Restore failed: {}\n\nYou may need to SSH in and fix manually.",
             e
@@ -176,14 +487,23 @@ async fn cmd_rescue(cfg: &Config, id: Option<&str>) -> String {
 }
 
 fn cmd_logs(cfg: &Config) -> String {
-    match crate::health::recent_incidents(cfg, 5) {
-        Ok(logs) if logs.is_empty() => "✅ No incidents recorded.".to_string(),
-        Ok(logs) => {
+    match crate::incidents::IncidentStore::open(cfg).recent(5) {
+        Ok(incidents) if incidents.is_empty() => "✅ No incidents recorded.".to_string(),
+        Ok(incidents) => {
             let mut out = "📋 Recent incidents:\n\n".to_string();
-            for log in logs {
+            for i in incidents {
                 out.push_str(&format!(
-                    "• {} — {} ({})\n",
-                    log.timestamp, log.cause, log.recovery
+                    "• {} — {} via {}, {} ({})\n",
+                    i.started_at
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M"),
+                    i.category,
+                    i.trigger,
+                    crate::incidents::format_duration(i.duration()),
+                    i.recovery
+                        .as_deref()
+                        .filter(|_| i.ended_at.is_some())
+                        .unwrap_or("open")
                 ));
             }
             out
@@ -191,3 +511,139 @@ fn cmd_logs(cfg: &Config) -> String {
         Err(e) => format!("❌ Error reading logs: {}", e),
     }
 }
+
+fn cmd_insights(cfg: &Config, days: Option<&str>) -> String {
+    let days = match days.map(str::parse::<i64>) {
+        None => 30,
+        Some(Ok(d)) if (1..=crate::incidents::MAX_INSIGHTS_DAYS).contains(&d) => d,
+        Some(_) => {
+            return format!(
+                "Usage: /insights [days] (1-{})",
+                crate::incidents::MAX_INSIGHTS_DAYS
+            )
+        }
+    };
+    match crate::incidents::insights(cfg, days) {
+        Ok(insights) => insights.to_string(),
+        Err(e) => format!("❌ Error reading incidents: {}", e),
+    }
+}
+
+async fn cmd_analyze(cfg: &Config, offline: bool) -> Reply {
+    let incident = crate::analysis::on_demand_incident("Telegram");
+    match crate::analysis::run_analysis(cfg, &incident, None, offline).await {
+        Ok(report) => text_document(
+            format!("incident-report-{}.md", report.id),
+            &report.report,
+            format!(
+                "📊 Analysis {}\n\n{}",
+                report.id,
+                crate::analysis::summarize(&report.analysis)
+            ),
+        ),
+        Err(e) => format!("❌ Analysis failed: {}", e).into(),
+    }
+}
+
+fn cmd_report(cfg: &Config, id: Option<&str>) -> Reply {
+    let id = match id {
+        Some(id) => id.to_string(),
+        None => match crate::analysis::list_incident_reports(cfg) {
+            Ok(ids) if ids.is_empty() => return "✅ No incident reports yet.".to_string().into(),
+            Ok(mut ids) => ids.remove(0),
+            Err(e) => return format!("❌ Error listing reports: {}", e).into(),
+        },
+    };
+
+    let path = crate::analysis::incident_report_path(cfg, &id);
+    match std::fs::read_to_string(&path) {
+        Ok(report) => text_document(
+            format!("incident-report-{}.md", id),
+            &report,
+            format!("🚨 Incident report {}", id),
+        ),
+        Err(_) => format!("❌ No incident report '{}'", id).into(),
+    }
+}
+
+fn cmd_get(cfg: &Config, id: &str, path: &str) -> Reply {
+    let result = crate::backup::find_snapshot(cfg, Some(id))
+        .and_then(|snap| crate::backup::read_snapshot_file(&snap, path, MAX_DOCUMENT_BYTES as u64));
+
+    match result {
+        Ok(content) => match String::from_utf8(content) {
+            Ok(text) => {
+                let file_name = path.rsplit('/').next().unwrap_or(path).to_string();
+                text_document(file_name, &text, format!("📄 {} from {}", path, id))
+            }
+            Err(_) => format!("❌ '{}' is not a text file", path).into(),
+        },
+        Err(e) => format!("❌ {}", e).into(),
+    }
+}
+
+fn cmd_diff(cfg: &Config, id: &str) -> Reply {
+    let result = crate::backup::find_snapshot(cfg, Some(id))
+        .and_then(|snap| crate::backup::diff_snapshot(cfg, &snap));
+
+    match result {
+        Ok(diff) if diff.is_empty() => format!("✅ No changes since backup {}", id).into(),
+        Ok(diff) => text_document(
+            format!("diff-{}.patch", id),
+            &diff,
+            format!("🔀 Changes since backup {}", id),
+        ),
+        Err(e) => format!("❌ {}", e).into(),
+    }
+}
+
+fn cmd_gateway_log(cfg: &Config, lines: Option<&str>) -> Reply {
+    let lines = match lines.map(str::parse::<usize>) {
+        None => GATEWAY_LOG_LINES,
+        Some(Ok(n)) => n.clamp(1, GATEWAY_LOG_MAX_LINES),
+        Some(Err(_)) => return "Usage: /gatewaylog [lines]".to_string().into(),
+    };
+
+    let path = cfg.openclaw.config_path.join("gateway.log");
+    match crate::analysis::read_last_n_lines(&path, lines) {
+        Ok(tail) => text_document(
+            "gateway.log".to_string(),
+            &tail,
+            format!("📜 Last {} lines of gateway.log", lines),
+        ),
+        Err(e) => format!("❌ Could not read {}: {}", path.display(), e).into(),
+    }
+}
+
+/// Wrap text as a document, enforcing the size limit
+fn text_document(file_name: String, text: &str, caption: String) -> Reply {
+    let content = text.as_bytes().to_vec();
+    if content.len() > MAX_DOCUMENT_BYTES {
+        return format!(
+            "❌ {} is too large to send ({}, limit {})",
+            file_name,
+            crate::backup::human_size(content.len() as u64),
+            crate::backup::human_size(MAX_DOCUMENT_BYTES as u64)
+        )
+        .into();
+    }
+
+    Reply::Document {
+        file_name,
+        content,
+        caption,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_name_strips_bot_suffix() {
+        assert_eq!(command_name("/pair@RescueClawBot 123456"), "/pair");
+        assert_eq!(command_name("/Status@RescueClawBot"), "/status");
+        assert_eq!(command_name("  /get 20260101-000000 SOUL.md"), "/get");
+        assert_eq!(command_name(""), "");
+    }
+}
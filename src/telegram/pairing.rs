@@ -0,0 +1,122 @@
+use std::io::Read;
+use std::time::{Duration, Instant};
+
+/// How long a pairing code stays valid
+pub const CODE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Wrong guesses before the code is thrown away
+const MAX_ATTEMPTS: u32 = 5;
+
+/// One-time codes an admin hands to a new user, who sends `/pair <code>` to
+/// the bot. Only one code is live at a time; issuing a new one replaces it.
+#[derive(Debug, Default)]
+pub struct Pairing {
+    active: Option<PairingCode>,
+}
+
+#[derive(Debug)]
+struct PairingCode {
+    code: String,
+    issued_by: i64,
+    expires: Instant,
+    attempts: u32,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RedeemError {
+    /// No code issued, expired, or used up
+    NoCode,
+    Wrong,
+}
+
+impl std::fmt::Display for RedeemError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RedeemError::NoCode => write!(f, "no pairing code is active — ask an admin for one"),
+            RedeemError::Wrong => write!(f, "wrong pairing code"),
+        }
+    }
+}
+
+impl Pairing {
+    /// New code from `admin`, valid for [`CODE_TTL`]
+    pub fn issue(&mut self, admin: i64, now: Instant) -> String {
+        let code = random_code();
+        self.active = Some(PairingCode {
+            code: code.clone(),
+            issued_by: admin,
+            expires: now + CODE_TTL,
+            attempts: 0,
+        });
+        code
+    }
+
+    /// Use up the code; returns the admin who issued it
+    pub fn redeem(&mut self, code: &str, now: Instant) -> Result<i64, RedeemError> {
+        let active = match &mut self.active {
+            Some(active) if now < active.expires => active,
+            _ => {
+                self.active = None;
+                return Err(RedeemError::NoCode);
+            }
+        };
+        if active.code != code.trim() {
+            active.attempts += 1;
+            if active.attempts >= MAX_ATTEMPTS {
+                tracing::warn!(
+                    "Pairing code discarded after {} wrong attempts",
+                    MAX_ATTEMPTS
+                );
+                self.active = None;
+            }
+            return Err(RedeemError::Wrong);
+        }
+        let issued_by = active.issued_by;
+        self.active = None;
+        Ok(issued_by)
+    }
+}
+
+/// Six random digits
+fn random_code() -> String {
+    let mut bytes = [0u8; 4];
+    let n = match std::fs::File::open("/dev/urandom").and_then(|mut f| f.read_exact(&mut bytes)) {
+        Ok(()) => u32::from_le_bytes(bytes),
+        // Not reached on Linux; still better than a fixed code
+        Err(_) => std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or_default(),
+    };
+    format!("{:06}", n % 1_000_000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_are_single_use_and_expire() {
+        let now = Instant::now();
+        let mut pairing = Pairing::default();
+        assert_eq!(pairing.redeem("123456", now), Err(RedeemError::NoCode));
+
+        let code = pairing.issue(7, now);
+        assert_eq!(code.len(), 6);
+        assert_eq!(pairing.redeem(&code, now), Ok(7));
+        assert_eq!(pairing.redeem(&code, now), Err(RedeemError::NoCode));
+
+        let code = pairing.issue(7, now);
+        assert_eq!(
+            pairing.redeem(&code, now + CODE_TTL),
+            Err(RedeemError::NoCode)
+        );
+
+        let code = pairing.issue(7, now);
+        let wrong = if code == "000000" { "000001" } else { "000000" };
+        for _ in 0..MAX_ATTEMPTS {
+            assert_eq!(pairing.redeem(wrong, now), Err(RedeemError::Wrong));
+        }
+        assert_eq!(pairing.redeem(&code, now), Err(RedeemError::NoCode));
+    }
+}
@@ -0,0 +1,320 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::Path;
+
+use super::{Rule, Scope, Severity, ValidationIssue, ValidationTarget};
+
+/// A rules file: `{ "rules": [ ... ] }`
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    rules: Vec<RuleSpec>,
+}
+
+/// One user rule as written in the rules file
+#[derive(Debug, Deserialize)]
+struct RuleSpec {
+    id: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default = "default_severity")]
+    severity: Severity,
+    /// Message to report instead of the generated one
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(flatten)]
+    assertion: AssertionSpec,
+}
+
+fn default_severity() -> Severity {
+    Severity::Error
+}
+
+/// What a user rule asserts. Paths are `config/...` or `workspace/...`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum AssertionSpec {
+    /// A JSON pointer into openclaw.json: must exist, or equal a value
+    #[serde(rename_all = "camelCase")]
+    Pointer {
+        pointer: String,
+        #[serde(default)]
+        equals: Option<Value>,
+        #[serde(default = "default_true")]
+        exists: bool,
+    },
+    /// A file must exist
+    FileExists { path: String },
+    /// A regex must not match a file's content (or must, with `mustMatch`)
+    #[serde(rename_all = "camelCase")]
+    Regex {
+        path: String,
+        pattern: String,
+        #[serde(default)]
+        must_match: bool,
+    },
+}
+
+fn default_true() -> bool {
+    true
+}
+
+enum Assertion {
+    Pointer {
+        pointer: String,
+        equals: Option<Value>,
+        exists: bool,
+    },
+    FileExists {
+        path: String,
+    },
+    Regex {
+        path: String,
+        re: Regex,
+        must_match: bool,
+    },
+}
+
+/// A rule loaded from the user's rules file
+pub struct UserRule {
+    id: String,
+    description: String,
+    severity: Severity,
+    message: Option<String>,
+    assertion: Assertion,
+}
+
+/// Load user rules from a JSON rules file
+pub fn load(path: &Path) -> Result<Vec<Box<dyn Rule>>> {
+    let content = std::fs::read_to_string(path)?;
+    parse(&content)
+}
+
+fn parse(content: &str) -> Result<Vec<Box<dyn Rule>>> {
+    let file: RulesFile = serde_json::from_str(content)?;
+    file.rules
+        .into_iter()
+        .map(|spec| UserRule::compile(spec).map(|r| Box::new(r) as Box<dyn Rule>))
+        .collect()
+}
+
+impl UserRule {
+    fn compile(spec: RuleSpec) -> Result<Self> {
+        let assertion = match spec.assertion {
+            AssertionSpec::Pointer {
+                pointer,
+                equals,
+                exists,
+            } => {
+                if !pointer.is_empty() && !pointer.starts_with('/') {
+                    anyhow::bail!(
+                        "rule '{}': pointer must start with '/' (got '{}')",
+                        spec.id,
+                        pointer
+                    );
+                }
+                if equals.is_some() && !exists {
+                    anyhow::bail!(
+                        "rule '{}': a pointer cannot both equal a value and be absent",
+                        spec.id
+                    );
+                }
+                Assertion::Pointer {
+                    pointer,
+                    equals,
+                    exists,
+                }
+            }
+            AssertionSpec::FileExists { path } => Assertion::FileExists { path },
+            AssertionSpec::Regex {
+                path,
+                pattern,
+                must_match,
+            } => Assertion::Regex {
+                path,
+                re: Regex::new(&pattern)
+                    .with_context(|| format!("rule '{}': invalid pattern", spec.id))?,
+                must_match,
+            },
+        };
+
+        let description = spec.description.unwrap_or_else(|| match &assertion {
+            Assertion::Pointer { pointer, .. } => format!("openclaw.json {}", pointer),
+            Assertion::FileExists { path } => format!("{} exists", path),
+            Assertion::Regex { path, re, .. } => format!("{} vs /{}/", path, re.as_str()),
+        });
+
+        Ok(Self {
+            id: spec.id,
+            description,
+            severity: spec.severity,
+            message: spec.message,
+            assertion,
+        })
+    }
+
+    fn issue(&self, generated: String) -> ValidationIssue {
+        ValidationIssue {
+            rule: String::new(),
+            severity: self.severity.clone(),
+            message: self.message.clone().unwrap_or(generated),
+        }
+    }
+
+    /// Describe a violation, or `None` when the assertion holds
+    fn violation(&self, target: &ValidationTarget) -> Option<String> {
+        match &self.assertion {
+            Assertion::Pointer {
+                pointer,
+                equals,
+                exists,
+            } => {
+                // An unparseable config is reported by `config-parse`
+                let config = target.config()?;
+                let value = config.raw.pointer(pointer);
+                match (value, equals) {
+                    (None, _) if *exists => Some(format!("{} is not set", pointer)),
+                    (Some(_), None) if !*exists => Some(format!("{} must not be set", pointer)),
+                    (Some(actual), Some(expected)) if actual != expected => {
+                        Some(format!("{} is {}, expected {}", pointer, actual, expected))
+                    }
+                    _ => None,
+                }
+            }
+            Assertion::FileExists { path } => {
+                (!target.resolve(path).exists()).then(|| format!("{} does not exist", path))
+            }
+            Assertion::Regex {
+                path,
+                re,
+                must_match,
+            } => {
+                let content = match std::fs::read_to_string(target.resolve(path)) {
+                    Ok(c) => c,
+                    Err(_) if *must_match => return Some(format!("{} is missing", path)),
+                    Err(_) => return None,
+                };
+                match (re.find(&content), must_match) {
+                    (Some(m), false) => {
+                        let line = content[..m.start()].matches('\n').count() + 1;
+                        Some(format!("{}:{} matches /{}/", path, line, re.as_str()))
+                    }
+                    (None, true) => Some(format!("{} does not match /{}/", path, re.as_str())),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+impl Rule for UserRule {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn scope(&self) -> Scope {
+        match &self.assertion {
+            Assertion::Pointer { .. } => Scope::Config,
+            Assertion::FileExists { path } | Assertion::Regex { path, .. } => {
+                if path.starts_with("config/") {
+                    Scope::Config
+                } else {
+                    Scope::Workspace
+                }
+            }
+        }
+    }
+
+    fn check(&self, target: &ValidationTarget) -> Vec<ValidationIssue> {
+        self.violation(target)
+            .map(|v| self.issue(v))
+            .into_iter()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = r#"{
+        "rules": [
+            { "id": "port-7744", "type": "pointer", "pointer": "/gateway/port", "equals": 7744 },
+            { "id": "no-debug", "type": "pointer", "pointer": "/debug", "exists": false, "severity": "warning" },
+            { "id": "has-user", "type": "fileExists", "path": "workspace/USER.md", "message": "USER.md is required" },
+            { "id": "no-http", "type": "regex", "path": "config/openclaw.json", "pattern": "\"baseUrl\":\\s*\"http://" }
+        ]
+    }"#;
+
+    fn target(config: &str) -> (tempfile::TempDir, ValidationTarget) {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(temp.path().join("config")).unwrap();
+        std::fs::create_dir_all(temp.path().join("workspace")).unwrap();
+        std::fs::write(temp.path().join("config/openclaw.json"), config).unwrap();
+        let target =
+            ValidationTarget::new(temp.path().join("config"), temp.path().join("workspace"));
+        (temp, target)
+    }
+
+    fn failures(rules: &[Box<dyn Rule>], target: &ValidationTarget) -> Vec<(String, String)> {
+        rules
+            .iter()
+            .flat_map(|r| {
+                r.check(target)
+                    .into_iter()
+                    .map(|i| (r.id().to_string(), i.message))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_user_rules_pass() {
+        let rules = parse(RULES).unwrap();
+        let (temp, target) = target(r#"{"gateway": {"port": 7744}}"#);
+        std::fs::write(temp.path().join("workspace/USER.md"), "me").unwrap();
+        assert!(failures(&rules, &target).is_empty());
+    }
+
+    #[test]
+    fn test_user_rules_fail() {
+        let rules = parse(RULES).unwrap();
+        let (_temp, target) = target(
+            r#"{"gateway": {"port": 8080}, "debug": true, "models": {"providers": {"x": {"baseUrl": "http://x"}}}}"#,
+        );
+        let found = failures(&rules, &target);
+        assert_eq!(found.len(), 4);
+        assert_eq!(found[0].1, "/gateway/port is 8080, expected 7744");
+        assert_eq!(found[1].1, "/debug must not be set");
+        assert_eq!(found[2].1, "USER.md is required");
+        assert!(found[3].1.starts_with("config/openclaw.json:1 matches"));
+        assert_eq!(rules[1].scope(), Scope::Config);
+        assert_eq!(rules[2].scope(), Scope::Workspace);
+    }
+
+    #[test]
+    fn test_invalid_rules_rejected() {
+        assert!(
+            parse(r#"{"rules": [{"id": "x", "type": "regex", "path": "a", "pattern": "("}]}"#)
+                .is_err()
+        );
+        assert!(
+            parse(r#"{"rules": [{"id": "x", "type": "pointer", "pointer": "gateway"}]}"#).is_err()
+        );
+        assert!(parse(r#"{"rules": [{"id": "x", "type": "nope"}]}"#).is_err());
+        let err = parse(
+            r#"{"rules": [{"id": "x", "type": "pointer", "pointer": "/a", "equals": 1, "exists": false}]}"#,
+        )
+        .err()
+        .unwrap();
+        assert!(
+            format!("{:#}", err).contains("cannot both equal"),
+            "{:#}",
+            err
+        );
+    }
+}
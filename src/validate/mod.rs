@@ -1,15 +1,34 @@
-use anyhow::Result;
-use serde_json::Value;
-use std::path::Path;
+pub mod custom;
+pub mod rules;
 
-#[derive(Debug, Clone, PartialEq)]
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, OpenClawFile, RuleLevel, ValidationConfig};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Severity {
     Error,
     Warning,
 }
 
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ValidationIssue {
+    /// ID of the rule that raised it
+    pub rule: String,
     pub severity: Severity,
     pub message: String,
 }
@@ -17,6 +36,7 @@ pub struct ValidationIssue {
 impl ValidationIssue {
     fn error(msg: impl Into<String>) -> Self {
         Self {
+            rule: String::new(),
             severity: Severity::Error,
             message: msg.into(),
         }
@@ -24,221 +44,208 @@ impl ValidationIssue {
 
     fn warning(msg: impl Into<String>) -> Self {
         Self {
+            rule: String::new(),
             severity: Severity::Warning,
             message: msg.into(),
         }
     }
 }
 
-/// Validate OpenClaw config file
-pub fn validate_openclaw_config(config_path: &Path) -> Result<Vec<ValidationIssue>> {
-    let mut issues = Vec::new();
-
-    // Find the actual config file (openclaw.json or clawdbot.json)
-    let config_file = if config_path.join("openclaw.json").exists() {
-        config_path.join("openclaw.json")
-    } else if config_path.join("clawdbot.json").exists() {
-        config_path.join("clawdbot.json")
-    } else if config_path.is_file() && config_path.exists() {
-        config_path.to_path_buf()
-    } else {
-        issues.push(ValidationIssue::error("OpenClaw config file not found"));
-        return Ok(issues);
-    };
-
-    // Parse config
-    let content = match std::fs::read_to_string(&config_file) {
-        Ok(c) => c,
-        Err(e) => {
-            issues.push(ValidationIssue::error(format!(
-                "Failed to read config: {}",
-                e
-            )));
-            return Ok(issues);
-        }
-    };
+/// Which half of an OpenClaw install a rule looks at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scope {
+    /// The OpenClaw config directory (openclaw.json, auth profiles)
+    Config,
+    /// The agent workspace (SOUL.md, memory/, ...)
+    Workspace,
+}
 
-    let config: Value = match serde_json::from_str(&content) {
-        Ok(c) => c,
-        Err(e) => {
-            issues.push(ValidationIssue::error(format!("Invalid JSON: {}", e)));
-            return Ok(issues);
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Scope::Config => write!(f, "config"),
+            Scope::Workspace => write!(f, "workspace"),
         }
-    };
-
-    // Check for primary model (agents.defaults.model.primary or agents.defaults.model as string)
-    let has_model = config
-        .pointer("/agents/defaults/model/primary")
-        .and_then(|v| v.as_str())
-        .is_some_and(|s| !s.is_empty())
-        || config
-            .pointer("/agents/defaults/model")
-            .and_then(|v| v.as_str())
-            .is_some_and(|s| !s.is_empty());
-
-    if !has_model {
-        issues.push(ValidationIssue::warning(
-            "No primary model configured (agents.defaults.model.primary)",
-        ));
-    }
-
-    // Check model providers (models.providers)
-    if let Some(providers) = config.pointer("/models/providers") {
-        if let Some(providers_obj) = providers.as_object() {
-            if providers_obj.is_empty() {
-                issues.push(ValidationIssue::warning("No model providers configured"));
-            } else {
-                // Validate each provider has a valid baseUrl
-                for (name, provider) in providers_obj {
-                    if let Some(base_url) = provider.get("baseUrl").and_then(|v| v.as_str()) {
-                        if base_url.is_empty() {
-                            issues.push(ValidationIssue::error(format!(
-                                "Provider '{}' has empty baseUrl",
-                                name
-                            )));
-                        } else if !base_url.starts_with("https://")
-                            && !base_url.starts_with("http://")
-                        {
-                            issues.push(ValidationIssue::error(format!(
-                                "Provider '{}' has invalid baseUrl: {}",
-                                name, base_url
-                            )));
-                        }
-                    }
-
-                    // Check inline apiKey if present (some providers use it, others use auth profiles)
-                    if let Some(api_key) = provider.get("apiKey").and_then(|v| v.as_str()) {
-                        if api_key.is_empty() {
-                            issues.push(ValidationIssue::warning(format!(
-                                "Provider '{}' has empty apiKey",
-                                name
-                            )));
-                        } else if is_placeholder_key(api_key) {
-                            issues.push(ValidationIssue::warning(format!(
-                                "Provider '{}' appears to have placeholder apiKey",
-                                name
-                            )));
-                        }
-                    }
-                    // Note: Anthropic uses auth profiles (auth.profiles), not inline apiKey — that's fine
-
-                    // Check models array exists and is non-empty
-                    if let Some(models) = provider.get("models") {
-                        if let Some(arr) = models.as_array() {
-                            if arr.is_empty() {
-                                issues.push(ValidationIssue::warning(format!(
-                                    "Provider '{}' has empty models list",
-                                    name
-                                )));
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    } else {
-        issues.push(ValidationIssue::warning(
-            "No model providers section (models.providers) — using built-in defaults",
-        ));
     }
+}
 
-    // Check auth profiles exist (OpenClaw uses auth.profiles for API keys)
-    if let Some(auth) = config.get("auth") {
-        if let Some(profiles) = auth.get("profiles").and_then(|v| v.as_object()) {
-            if profiles.is_empty() {
-                issues.push(ValidationIssue::warning("No auth profiles configured"));
-            }
+/// A validation rule. Rules report issues at their default severity; the
+/// [`Validator`] applies per-rule overrides from config.
+pub trait Rule: Send + Sync {
+    /// Stable ID, used in config overrides and output
+    fn id(&self) -> &str;
+    fn description(&self) -> &str;
+    fn scope(&self) -> Scope;
+    fn check(&self, target: &ValidationTarget) -> Vec<ValidationIssue>;
+}
+
+/// State of the OpenClaw config file in a target
+enum ConfigState {
+    Missing,
+    Unreadable(String),
+    Invalid(String),
+    Parsed(Box<OpenClawFile>),
+}
+
+/// An OpenClaw install to validate: live directories or an extracted snapshot
+pub struct ValidationTarget {
+    pub config_dir: PathBuf,
+    pub workspace: PathBuf,
+    config: ConfigState,
+}
+
+impl ValidationTarget {
+    pub fn new(config_dir: impl Into<PathBuf>, workspace: impl Into<PathBuf>) -> Self {
+        let config_dir = config_dir.into();
+
+        // Find the actual config file (openclaw.json or clawdbot.json)
+        let config = match OpenClawFile::find(&config_dir) {
+            None => ConfigState::Missing,
+            Some(file) => match std::fs::read_to_string(&file) {
+                Err(e) => ConfigState::Unreadable(e.to_string()),
+                Ok(content) => match OpenClawFile::parse(&file, &content) {
+                    Ok(parsed) => ConfigState::Parsed(Box::new(parsed)),
+                    Err(e) => ConfigState::Invalid(e.to_string()),
+                },
+            },
+        };
+
+        Self {
+            config_dir,
+            workspace: workspace.into(),
+            config,
         }
     }
 
-    // Check gateway config
-    if config.get("gateway").is_none() {
-        issues.push(ValidationIssue::warning("Missing gateway configuration"));
-    } else if let Some(gateway) = config.get("gateway") {
-        if let Some(port) = gateway.get("port") {
-            if let Some(port_num) = port.as_u64() {
-                if port_num == 0 || port_num > 65535 {
-                    issues.push(ValidationIssue::error(format!(
-                        "Invalid gateway port: {}",
-                        port_num
-                    )));
-                }
-            }
+    /// The live install from RescueClaw's config
+    pub fn live(cfg: &Config) -> Self {
+        Self::new(&cfg.openclaw.config_path, &cfg.openclaw.workspace)
+    }
+
+    /// The parsed OpenClaw config, if it exists and is valid JSON
+    pub fn config(&self) -> Option<&OpenClawFile> {
+        match &self.config {
+            ConfigState::Parsed(c) => Some(c),
+            _ => None,
         }
     }
 
-    Ok(issues)
+    /// Resolve a rule path: `config/...` and `workspace/...` (as in snapshot
+    /// archives) map to the target's directories; bare paths are workspace-relative
+    pub fn resolve(&self, path: &str) -> PathBuf {
+        if let Some(rest) = path.strip_prefix("config/") {
+            self.config_dir.join(rest)
+        } else if let Some(rest) = path.strip_prefix("workspace/") {
+            self.workspace.join(rest)
+        } else {
+            self.workspace.join(path)
+        }
+    }
 }
 
-/// Validate OpenClaw workspace
-pub fn validate_workspace(workspace_path: &Path) -> Result<Vec<ValidationIssue>> {
-    let mut issues = Vec::new();
+/// Runs the built-in and user rules with config overrides applied
+pub struct Validator {
+    rules: Vec<Box<dyn Rule>>,
+    overrides: BTreeMap<String, RuleLevel>,
+}
 
-    if !workspace_path.exists() {
-        issues.push(ValidationIssue::error("Workspace path does not exist"));
-        return Ok(issues);
+impl Validator {
+    /// Built-in rules at their default severities
+    pub fn builtin() -> Self {
+        Self {
+            rules: rules::builtin(),
+            overrides: BTreeMap::new(),
+        }
     }
 
-    if !workspace_path.is_dir() {
-        issues.push(ValidationIssue::error("Workspace path is not a directory"));
-        return Ok(issues);
+    /// Build from the `validation` config section; fails on an unreadable
+    /// rules file, an invalid user rule, or an override for an unknown rule
+    pub fn new(validation: &ValidationConfig) -> Result<Self> {
+        let mut rules = rules::builtin();
+        if let Some(path) = &validation.rules_file {
+            rules.extend(
+                custom::load(path)
+                    .with_context(|| format!("loading rules file {}", path.display()))?,
+            );
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for rule in &rules {
+            if !seen.insert(rule.id()) {
+                anyhow::bail!("Duplicate validation rule ID '{}'", rule.id());
+            }
+        }
+        for id in validation.rules.keys() {
+            if !seen.contains(id.as_str()) {
+                anyhow::bail!("Unknown validation rule '{}' in validation.rules", id);
+            }
+        }
+
+        Ok(Self {
+            rules,
+            overrides: validation.rules.clone(),
+        })
     }
 
-    // Check for critical files
-    if !workspace_path.join("SOUL.md").exists() {
-        issues.push(ValidationIssue::error(
-            "Missing SOUL.md - agent identity file",
-        ));
+    /// Validator for a loaded config. Problems with user rules are logged and
+    /// the built-in rules are used, so validation itself never fails to run.
+    pub fn for_config(cfg: &Config) -> Self {
+        Self::new(&cfg.validation).unwrap_or_else(|e| {
+            tracing::warn!("{:#} — using built-in validation rules", e);
+            Self::builtin()
+        })
     }
 
-    if !workspace_path.join("AGENTS.md").exists() {
-        issues.push(ValidationIssue::warning("Missing AGENTS.md"));
+    pub fn rules(&self) -> impl Iterator<Item = &dyn Rule> {
+        self.rules.iter().map(|r| r.as_ref())
     }
 
-    // Check memory directory
-    if !workspace_path.join("memory").exists() {
-        issues.push(ValidationIssue::warning("Missing memory/ directory"));
-    } else if !workspace_path.join("memory").is_dir() {
-        issues.push(ValidationIssue::error(
-            "memory exists but is not a directory",
-        ));
+    /// Configured level of a rule, if overridden
+    pub fn level(&self, id: &str) -> Option<RuleLevel> {
+        self.overrides.get(id).copied()
     }
 
-    // Warn if workspace seems empty
-    if let Ok(entries) = std::fs::read_dir(workspace_path) {
-        let count = entries.count();
-        if count < 3 {
-            issues.push(ValidationIssue::warning(format!(
-                "Workspace seems sparse (only {} items)",
-                count
-            )));
+    /// Run every rule (or only those in `scope`) against a target
+    pub fn check(&self, target: &ValidationTarget, scope: Option<Scope>) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        for rule in &self.rules {
+            if scope.is_some_and(|s| s != rule.scope()) {
+                continue;
+            }
+            let level = self.level(rule.id());
+            if level == Some(RuleLevel::Off) {
+                continue;
+            }
+            for mut issue in rule.check(target) {
+                issue.rule = rule.id().to_string();
+                match level {
+                    Some(RuleLevel::Error) => issue.severity = Severity::Error,
+                    Some(RuleLevel::Warning) => issue.severity = Severity::Warning,
+                    _ => {}
+                }
+                issues.push(issue);
+            }
         }
+        issues
     }
 
-    Ok(issues)
-}
-
-/// Check if an API key looks like a placeholder
-fn is_placeholder_key(key: &str) -> bool {
-    let key_lower = key.to_lowercase();
-    key_lower.contains("your_key")
-        || key_lower.contains("your-key")
-        || key_lower.contains("placeholder")
-        || key_lower.contains("xxx")
-        || key_lower.contains("replace")
-        || key == "sk-"
+    /// Config-scoped rules against an OpenClaw config directory
+    pub fn check_config(&self, config_dir: &Path) -> Vec<ValidationIssue> {
+        self.check(&ValidationTarget::new(config_dir, ""), Some(Scope::Config))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_placeholder_detection() {
-        assert!(is_placeholder_key("YOUR_KEY_HERE"));
-        assert!(is_placeholder_key("sk-xxx"));
-        assert!(is_placeholder_key("placeholder-key"));
-        assert!(!is_placeholder_key("sk-1234567890abcdef"));
+    fn write_install(dir: &Path, config: &str) -> ValidationTarget {
+        std::fs::create_dir_all(dir.join("config")).unwrap();
+        std::fs::create_dir_all(dir.join("workspace/memory")).unwrap();
+        std::fs::write(dir.join("config/openclaw.json"), config).unwrap();
+        std::fs::write(dir.join("workspace/SOUL.md"), "# Soul").unwrap();
+        std::fs::write(dir.join("workspace/AGENTS.md"), "# Agents").unwrap();
+        ValidationTarget::new(dir.join("config"), dir.join("workspace"))
     }
 
     #[test]
@@ -250,4 +257,58 @@ mod tests {
         let warn = ValidationIssue::warning("test warning");
         assert_eq!(warn.severity, Severity::Warning);
     }
+
+    #[test]
+    fn test_overrides_disable_and_promote() {
+        let temp = tempfile::tempdir().unwrap();
+        // No gateway section (warning) and a bad baseUrl (error)
+        let target = write_install(
+            temp.path(),
+            r#"{"defaultModel": "gpt-4", "providers": {"openai": {"baseUrl": "ftp://x", "apiKey": "sk-1234567890abcdef"}}}"#,
+        );
+
+        let issues = Validator::builtin().check(&target, None);
+        assert!(issues
+            .iter()
+            .any(|i| i.rule == "gateway-section" && i.severity == Severity::Warning));
+        assert!(issues.iter().any(|i| i.rule == "provider-base-url"));
+
+        let validator = Validator::new(&ValidationConfig {
+            rules: BTreeMap::from([
+                ("gateway-section".to_string(), RuleLevel::Error),
+                ("provider-base-url".to_string(), RuleLevel::Off),
+            ]),
+            rules_file: None,
+        })
+        .unwrap();
+        let issues = validator.check(&target, None);
+        assert!(issues
+            .iter()
+            .any(|i| i.rule == "gateway-section" && i.severity == Severity::Error));
+        assert!(!issues.iter().any(|i| i.rule == "provider-base-url"));
+    }
+
+    #[test]
+    fn test_unknown_override_rejected() {
+        let result = Validator::new(&ValidationConfig {
+            rules: BTreeMap::from([("no-such-rule".to_string(), RuleLevel::Off)]),
+            rules_file: None,
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_scope_filtering() {
+        let temp = tempfile::tempdir().unwrap();
+        let target = write_install(temp.path(), "{ not json");
+        let config_issues = Validator::builtin().check(&target, Some(Scope::Config));
+        assert_eq!(config_issues.len(), 1);
+        assert_eq!(config_issues[0].rule, "config-parse");
+        assert!(config_issues[0].message.starts_with("Invalid JSON"));
+
+        let workspace_issues = Validator::builtin().check(&target, Some(Scope::Workspace));
+        assert!(workspace_issues
+            .iter()
+            .all(|i| i.severity != Severity::Error));
+    }
 }
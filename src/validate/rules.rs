@@ -0,0 +1,420 @@
+use std::path::Path;
+
+use super::{ConfigState, Rule, Scope, ValidationIssue, ValidationTarget};
+use crate::config::OpenClawFile;
+
+/// How a built-in rule inspects its target
+#[derive(Clone, Copy)]
+enum Check {
+    /// The whole target (for rules that handle missing inputs themselves)
+    Target(fn(&ValidationTarget) -> Vec<ValidationIssue>),
+    /// A parsed OpenClaw config; skipped when it is missing or invalid
+    Config(fn(&OpenClawFile) -> Vec<ValidationIssue>),
+    /// An existing workspace directory; skipped when there is none
+    Workspace(fn(&Path) -> Vec<ValidationIssue>),
+}
+
+#[derive(Clone, Copy)]
+struct BuiltinRule {
+    id: &'static str,
+    description: &'static str,
+    scope: Scope,
+    check: Check,
+}
+
+impl Rule for BuiltinRule {
+    fn id(&self) -> &str {
+        self.id
+    }
+
+    fn description(&self) -> &str {
+        self.description
+    }
+
+    fn scope(&self) -> Scope {
+        self.scope
+    }
+
+    fn check(&self, target: &ValidationTarget) -> Vec<ValidationIssue> {
+        match self.check {
+            Check::Target(f) => f(target),
+            Check::Config(f) => target.config().map(f).unwrap_or_default(),
+            Check::Workspace(f) if target.workspace.is_dir() => f(&target.workspace),
+            Check::Workspace(_) => Vec::new(),
+        }
+    }
+}
+
+/// Built-in rule library, in reporting order
+const BUILTIN: &[BuiltinRule] = &[
+    BuiltinRule {
+        id: "config-parse",
+        description: "OpenClaw config exists and is valid JSON",
+        scope: Scope::Config,
+        check: Check::Target(config_parse),
+    },
+    BuiltinRule {
+        id: "primary-model",
+        description: "A primary model is configured",
+        scope: Scope::Config,
+        check: Check::Config(primary_model),
+    },
+    BuiltinRule {
+        id: "primary-provider-key",
+        description: "The primary model's provider has credentials",
+        scope: Scope::Config,
+        check: Check::Config(primary_provider_key),
+    },
+    BuiltinRule {
+        id: "providers",
+        description: "Model providers are configured",
+        scope: Scope::Config,
+        check: Check::Config(providers),
+    },
+    BuiltinRule {
+        id: "provider-base-url",
+        description: "Provider baseUrls are http(s) URLs",
+        scope: Scope::Config,
+        check: Check::Config(provider_base_url),
+    },
+    BuiltinRule {
+        id: "provider-api-key",
+        description: "Inline provider apiKeys are not empty or placeholders",
+        scope: Scope::Config,
+        check: Check::Config(provider_api_key),
+    },
+    BuiltinRule {
+        id: "provider-models",
+        description: "Provider model lists are not empty",
+        scope: Scope::Config,
+        check: Check::Config(provider_models),
+    },
+    BuiltinRule {
+        id: "auth-profiles",
+        description: "auth.profiles is not empty",
+        scope: Scope::Config,
+        check: Check::Config(auth_profiles),
+    },
+    BuiltinRule {
+        id: "gateway-section",
+        description: "A gateway section is present",
+        scope: Scope::Config,
+        check: Check::Config(gateway_section),
+    },
+    BuiltinRule {
+        id: "gateway-port",
+        description: "gateway.port is a valid port",
+        scope: Scope::Config,
+        check: Check::Config(gateway_port),
+    },
+    BuiltinRule {
+        id: "workspace-dir",
+        description: "The workspace exists and is a directory",
+        scope: Scope::Workspace,
+        check: Check::Target(workspace_dir),
+    },
+    BuiltinRule {
+        id: "soul-md",
+        description: "SOUL.md (agent identity) is present",
+        scope: Scope::Workspace,
+        check: Check::Workspace(soul_md),
+    },
+    BuiltinRule {
+        id: "agents-md",
+        description: "AGENTS.md is present",
+        scope: Scope::Workspace,
+        check: Check::Workspace(agents_md),
+    },
+    BuiltinRule {
+        id: "memory-dir",
+        description: "memory/ is a directory",
+        scope: Scope::Workspace,
+        check: Check::Workspace(memory_dir),
+    },
+    BuiltinRule {
+        id: "workspace-sparse",
+        description: "The workspace is not nearly empty",
+        scope: Scope::Workspace,
+        check: Check::Workspace(workspace_sparse),
+    },
+];
+
+pub(super) fn builtin() -> Vec<Box<dyn Rule>> {
+    BUILTIN
+        .iter()
+        .map(|r| Box::new(*r) as Box<dyn Rule>)
+        .collect()
+}
+
+// ─── Config rules ──────────────────────────────────────────────────
+
+fn config_parse(target: &ValidationTarget) -> Vec<ValidationIssue> {
+    match &target.config {
+        ConfigState::Missing => vec![ValidationIssue::error("OpenClaw config file not found")],
+        ConfigState::Unreadable(e) => {
+            vec![ValidationIssue::error(format!(
+                "Failed to read config: {}",
+                e
+            ))]
+        }
+        ConfigState::Invalid(e) => vec![ValidationIssue::error(format!("Invalid JSON: {}", e))],
+        ConfigState::Parsed(_) => Vec::new(),
+    }
+}
+
+// Check for primary model (agents.defaults.model.primary, or legacy defaultModel)
+fn primary_model(config: &OpenClawFile) -> Vec<ValidationIssue> {
+    match config.primary_model {
+        None => vec![ValidationIssue::warning(
+            "No primary model configured (agents.defaults.model.primary)",
+        )],
+        Some(_) => Vec::new(),
+    }
+}
+
+// The primary model's provider needs credentials from somewhere
+fn primary_provider_key(config: &OpenClawFile) -> Vec<ValidationIssue> {
+    let Some(provider) = config
+        .primary_model
+        .as_ref()
+        .and_then(|m| m.provider.as_ref())
+    else {
+        return Vec::new();
+    };
+    let is_local = config
+        .providers
+        .get(provider)
+        .and_then(|p| p.base_url.as_deref())
+        .is_some_and(|u| u.contains("127.0.0.1") || u.contains("localhost"));
+    if !is_local && config.api_key_for(provider).is_none() {
+        vec![ValidationIssue::warning(format!(
+            "No API key or auth profile found for primary model provider '{}'",
+            provider
+        ))]
+    } else {
+        Vec::new()
+    }
+}
+
+// Check model providers (models.providers, or legacy providers)
+fn providers(config: &OpenClawFile) -> Vec<ValidationIssue> {
+    let has_providers_section =
+        config.raw.pointer("/models/providers").is_some() || config.raw.get("providers").is_some();
+    if !has_providers_section {
+        vec![ValidationIssue::warning(
+            "No model providers section (models.providers) — using built-in defaults",
+        )]
+    } else if config.providers.is_empty() {
+        vec![ValidationIssue::warning("No model providers configured")]
+    } else {
+        Vec::new()
+    }
+}
+
+fn provider_base_url(config: &OpenClawFile) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    for (name, provider) in &config.providers {
+        if let Some(base_url) = &provider.base_url {
+            if base_url.is_empty() {
+                issues.push(ValidationIssue::error(format!(
+                    "Provider '{}' has empty baseUrl",
+                    name
+                )));
+            } else if !base_url.starts_with("https://") && !base_url.starts_with("http://") {
+                issues.push(ValidationIssue::error(format!(
+                    "Provider '{}' has invalid baseUrl: {}",
+                    name, base_url
+                )));
+            }
+        }
+    }
+    issues
+}
+
+// Check inline apiKey if present (some providers use it, others use auth profiles)
+fn provider_api_key(config: &OpenClawFile) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    for (name, provider) in &config.providers {
+        if let Some(api_key) = &provider.api_key {
+            if api_key.is_empty() {
+                issues.push(ValidationIssue::warning(format!(
+                    "Provider '{}' has empty apiKey",
+                    name
+                )));
+            } else if is_placeholder_key(api_key) {
+                issues.push(ValidationIssue::warning(format!(
+                    "Provider '{}' appears to have placeholder apiKey",
+                    name
+                )));
+            }
+        }
+    }
+    issues
+}
+
+fn provider_models(config: &OpenClawFile) -> Vec<ValidationIssue> {
+    config
+        .providers
+        .iter()
+        .filter(|(_, p)| p.models.as_ref().is_some_and(|m| m.is_empty()))
+        .map(|(name, _)| {
+            ValidationIssue::warning(format!("Provider '{}' has empty models list", name))
+        })
+        .collect()
+}
+
+// Check auth profiles exist (OpenClaw uses auth.profiles for API keys)
+fn auth_profiles(config: &OpenClawFile) -> Vec<ValidationIssue> {
+    if config
+        .raw
+        .pointer("/auth/profiles")
+        .and_then(|v| v.as_object())
+        .is_some_and(|p| p.is_empty())
+    {
+        vec![ValidationIssue::warning("No auth profiles configured")]
+    } else {
+        Vec::new()
+    }
+}
+
+fn gateway_section(config: &OpenClawFile) -> Vec<ValidationIssue> {
+    if config.raw.get("gateway").is_none() {
+        vec![ValidationIssue::warning("Missing gateway configuration")]
+    } else {
+        Vec::new()
+    }
+}
+
+fn gateway_port(config: &OpenClawFile) -> Vec<ValidationIssue> {
+    match config.gateway_port {
+        Some(port) if port == 0 || port > 65535 => vec![ValidationIssue::error(format!(
+            "Invalid gateway port: {}",
+            port
+        ))],
+        _ => Vec::new(),
+    }
+}
+
+// ─── Workspace rules ───────────────────────────────────────────────
+
+fn workspace_dir(target: &ValidationTarget) -> Vec<ValidationIssue> {
+    if !target.workspace.exists() {
+        vec![ValidationIssue::error("Workspace path does not exist")]
+    } else if !target.workspace.is_dir() {
+        vec![ValidationIssue::error("Workspace path is not a directory")]
+    } else {
+        Vec::new()
+    }
+}
+
+fn soul_md(workspace: &Path) -> Vec<ValidationIssue> {
+    if workspace.join("SOUL.md").exists() {
+        Vec::new()
+    } else {
+        vec![ValidationIssue::error(
+            "Missing SOUL.md - agent identity file",
+        )]
+    }
+}
+
+fn agents_md(workspace: &Path) -> Vec<ValidationIssue> {
+    if workspace.join("AGENTS.md").exists() {
+        Vec::new()
+    } else {
+        vec![ValidationIssue::warning("Missing AGENTS.md")]
+    }
+}
+
+fn memory_dir(workspace: &Path) -> Vec<ValidationIssue> {
+    let memory = workspace.join("memory");
+    if !memory.exists() {
+        vec![ValidationIssue::warning("Missing memory/ directory")]
+    } else if !memory.is_dir() {
+        vec![ValidationIssue::error(
+            "memory exists but is not a directory",
+        )]
+    } else {
+        Vec::new()
+    }
+}
+
+// Warn if workspace seems empty
+fn workspace_sparse(workspace: &Path) -> Vec<ValidationIssue> {
+    match std::fs::read_dir(workspace).map(|entries| entries.count()) {
+        Ok(count) if count < 3 => vec![ValidationIssue::warning(format!(
+            "Workspace seems sparse (only {} items)",
+            count
+        ))],
+        _ => Vec::new(),
+    }
+}
+
+/// Check if an API key looks like a placeholder
+fn is_placeholder_key(key: &str) -> bool {
+    let key_lower = key.to_lowercase();
+    key_lower.contains("your_key")
+        || key_lower.contains("your-key")
+        || key_lower.contains("placeholder")
+        || key_lower.contains("xxx")
+        || key_lower.contains("replace")
+        || key == "sk-"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_all(config: &OpenClawFile) -> Vec<ValidationIssue> {
+        BUILTIN
+            .iter()
+            .filter_map(|r| match r.check {
+                Check::Config(f) => Some(f(config)),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    #[test]
+    fn test_placeholder_detection() {
+        assert!(is_placeholder_key("YOUR_KEY_HERE"));
+        assert!(is_placeholder_key("sk-xxx"));
+        assert!(is_placeholder_key("placeholder-key"));
+        assert!(!is_placeholder_key("sk-1234567890abcdef"));
+    }
+
+    #[test]
+    fn test_config_rules_layouts() {
+        let legacy = OpenClawFile::from_value(
+            serde_json::json!({
+                "defaultModel": "gpt-4",
+                "providers": { "openai": { "apiKey": "sk-1234567890abcdef" } },
+                "gateway": { "port": 7744 }
+            }),
+            None,
+        );
+        assert!(check_all(&legacy).is_empty());
+
+        let modern = OpenClawFile::from_value(
+            serde_json::json!({
+                "agents": { "defaults": { "model": { "primary": "zz-missing/model-x" } } },
+                "models": { "providers": { "openrouter": { "baseUrl": "ftp://bad", "models": [] } } },
+                "gateway": { "port": 70000 }
+            }),
+            None,
+        );
+        let messages: Vec<String> = check_all(&modern).into_iter().map(|i| i.message).collect();
+        assert!(messages.iter().any(|m| m.contains("'zz-missing'")));
+        assert!(messages.iter().any(|m| m.contains("invalid baseUrl")));
+        assert!(messages.iter().any(|m| m.contains("empty models list")));
+        assert!(messages.iter().any(|m| m.contains("Invalid gateway port")));
+    }
+
+    #[test]
+    fn test_rule_ids_unique() {
+        let mut ids: Vec<&str> = BUILTIN.iter().map(|r| r.id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), BUILTIN.len());
+    }
+}
@@ -6,25 +6,44 @@ use tempfile::tempdir;
 fn create_test_config(temp_path: PathBuf) -> config::Config {
     config::Config {
         backup: config::BackupConfig {
-            interval: "1h".to_string(),
+            interval: "1h".parse().unwrap(),
             max_snapshots: 10,
             path: temp_path.join("backups"),
             include_sessions: false,
+            mode: config::BackupMode::Interval,
+            debounce: "2m".parse().unwrap(),
+            min_interval: "10m".parse().unwrap(),
+            max_interval: None,
+            max_wait: "15m".parse().unwrap(),
+            skip_unchanged: Some(true),
+            schedules: vec![],
+            quiet_hours: vec![],
         },
         health: config::HealthConfig {
-            check_interval: "5m".to_string(),
+            check_interval: "5m".parse().unwrap(),
             unhealthy_threshold: 3,
             auto_restore: false,
-            auto_restore_cooldown: Some("1h".to_string()),
+            auto_restore_cooldown: Some("1h".parse().unwrap()),
+            analyze_on_restore: false,
+            quiet_hours: vec![],
+            escalation: vec!["restart 1m".into(), "alert".into()],
         },
         telegram: config::TelegramConfig {
             token: "test_token".to_string(),
             allowed_users: vec![123456789],
+            admins: vec![],
         },
         openclaw: config::OpenClawConfig {
             workspace: temp_path.join("workspace"),
             config_path: temp_path.join("config"),
+            gateway: config::GatewayConfig::default(),
+            container_mode: None,
         },
+        redaction: config::RedactionConfig::default(),
+        validation: config::ValidationConfig::default(),
+        guard: config::GuardConfig::default(),
+        journal: config::JournalConfig::default(),
+        timezone: None,
     }
 }
 
@@ -161,3 +180,91 @@ async fn test_backup_restore_roundtrip() {
 
     assert!(found_soul, "SOUL.md should be in backup");
 }
+
+#[test]
+fn test_read_snapshot_file() {
+    let temp = tempdir().unwrap();
+    let cfg = create_test_config(temp.path().to_path_buf());
+
+    setup_test_workspace(&cfg.openclaw.workspace);
+    setup_test_config_dir(&cfg.openclaw.config_path);
+
+    let snapshot = backup::take_snapshot(&cfg).unwrap();
+
+    // Bare paths resolve against workspace/ then config/
+    let soul = backup::read_snapshot_file(&snapshot, "SOUL.md", 1024).unwrap();
+    assert_eq!(soul, b"# Test Agent Soul\n");
+    let oc = backup::read_snapshot_file(&snapshot, "config/openclaw.json", 1024).unwrap();
+    assert!(String::from_utf8(oc).unwrap().contains("gateway"));
+
+    assert!(backup::read_snapshot_file(&snapshot, "missing.md", 1024).is_err());
+    assert!(backup::read_snapshot_file(&snapshot, "../etc/passwd", 1024).is_err());
+
+    // Refused from the header, before reading
+    let err = backup::read_snapshot_file(&snapshot, "SOUL.md", 10).unwrap_err();
+    assert!(err.to_string().contains("too large"), "{}", err);
+}
+
+#[test]
+fn test_diff_snapshot() {
+    let temp = tempdir().unwrap();
+    let cfg = create_test_config(temp.path().to_path_buf());
+
+    setup_test_workspace(&cfg.openclaw.workspace);
+    setup_test_config_dir(&cfg.openclaw.config_path);
+
+    let snapshot = backup::take_snapshot(&cfg).unwrap();
+    assert!(backup::diff_snapshot(&cfg, &snapshot).unwrap().is_empty());
+
+    fs::write(cfg.openclaw.workspace.join("SOUL.md"), "# MODIFIED SOUL\n").unwrap();
+    fs::write(cfg.openclaw.workspace.join("memory/new.md"), "new\n").unwrap();
+
+    let diff = backup::diff_snapshot(&cfg, &snapshot).unwrap();
+    assert!(diff.contains("--- a/workspace/SOUL.md"));
+    assert!(diff.contains("-# Test Agent Soul"));
+    assert!(diff.contains("+# MODIFIED SOUL"));
+    assert!(diff.contains("+++ b/workspace/memory/new.md"));
+}
+
+#[test]
+fn test_scheduler_skips_unchanged_content() {
+    let temp = tempdir().unwrap();
+    let cfg = create_test_config(temp.path().to_path_buf());
+    setup_test_workspace(&cfg.openclaw.workspace);
+    setup_test_config_dir(&cfg.openclaw.config_path);
+
+    let first = backup::take_snapshot(&cfg).unwrap();
+    assert_eq!(
+        backup::snapshot_content_hash(&first),
+        Some(backup::content_hash(&cfg, &Default::default()))
+    );
+
+    // Picks up the hash of the latest snapshot, so nothing to do yet
+    let mut scheduler = backup::Scheduler::new(&cfg).unwrap();
+    assert!(scheduler.run("Scheduled").is_none());
+
+    fs::write(cfg.openclaw.workspace.join("SOUL.md"), "# Changed\n").unwrap();
+    assert!(scheduler.run("Change-triggered").is_some());
+    assert!(scheduler.run("Scheduled").is_none());
+
+    // Snapshots taken elsewhere count as the previous one
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    fs::write(cfg.openclaw.workspace.join("SOUL.md"), "# Manual\n").unwrap();
+    backup::take_snapshot(&cfg).unwrap();
+    assert!(scheduler.run("Scheduled").is_none());
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    fs::write(cfg.openclaw.workspace.join("SOUL.md"), "# Changed\n").unwrap();
+    assert!(scheduler.run("Scheduled").is_some());
+}
+
+#[test]
+fn test_skip_unchanged_defaults_by_mode() {
+    let temp = tempdir().unwrap();
+    let mut cfg = create_test_config(temp.path().to_path_buf());
+    cfg.backup.skip_unchanged = None;
+    assert!(!cfg.backup.skip_unchanged());
+    cfg.backup.mode = config::BackupMode::Changes;
+    assert!(cfg.backup.skip_unchanged());
+    cfg.backup.skip_unchanged = Some(false);
+    assert!(!cfg.backup.skip_unchanged());
+}
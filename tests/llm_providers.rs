@@ -0,0 +1,214 @@
+use rescueclaw::analysis::provider::{ApiKind, LlmProvider};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// A request as seen by the mock server
+#[derive(Debug, Clone)]
+struct Recorded {
+    request_line: String,
+    headers: Vec<(String, String)>,
+    body: serde_json::Value,
+}
+
+impl Recorded {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Minimal HTTP/1.1 server: answers each connection with the next scripted
+/// `(status, body)` and records what it received
+async fn mock_server(responses: Vec<(u16, &'static str)>) -> (String, Arc<Mutex<Vec<Recorded>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+    let log = recorded.clone();
+
+    tokio::spawn(async move {
+        for (status, body) in responses {
+            let (mut stream, _) = listener.accept().await.unwrap();
+
+            let mut buf = Vec::new();
+            let mut chunk = [0u8; 4096];
+            let header_end = loop {
+                let n = stream.read(&mut chunk).await.unwrap();
+                buf.extend_from_slice(&chunk[..n]);
+                if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break pos + 4;
+                }
+            };
+
+            let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+            let mut lines = head.lines();
+            let request_line = lines.next().unwrap_or_default().to_string();
+            let headers: Vec<(String, String)> = lines
+                .filter_map(|l| l.split_once(':'))
+                .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+                .collect();
+            let content_length: usize = headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+                .and_then(|(_, v)| v.parse().ok())
+                .unwrap_or(0);
+            while buf.len() < header_end + content_length {
+                let n = stream.read(&mut chunk).await.unwrap();
+                buf.extend_from_slice(&chunk[..n]);
+            }
+            let body_json = serde_json::from_slice(&buf[header_end..header_end + content_length])
+                .unwrap_or(serde_json::Value::Null);
+
+            log.lock().unwrap().push(Recorded {
+                request_line,
+                headers,
+                body: body_json,
+            });
+
+            let response = format!(
+                "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.shutdown().await.ok();
+        }
+    });
+
+    (url, recorded)
+}
+
+fn fast(mut provider: LlmProvider) -> LlmProvider {
+    provider.timeout = Duration::from_secs(5);
+    provider.retry_backoff = Duration::from_millis(10);
+    provider
+}
+
+#[tokio::test]
+async fn test_openai_compatible_request() {
+    let (url, recorded) = mock_server(vec![(
+        200,
+        r#"{"choices":[{"message":{"role":"assistant","content":"Root cause: bad config"}}]}"#,
+    )])
+    .await;
+
+    let provider = fast(LlmProvider::new(
+        "openai",
+        ApiKind::OpenAi,
+        format!("{}/v1", url),
+        Some("sk-test".to_string()),
+        "gpt-4o-mini",
+    ));
+    let reply = provider.complete("why?").await.unwrap();
+    assert_eq!(reply, "Root cause: bad config");
+
+    let req = recorded.lock().unwrap()[0].clone();
+    assert!(req.request_line.starts_with("POST /v1/chat/completions"));
+    assert_eq!(req.header("authorization"), Some("Bearer sk-test"));
+    assert_eq!(req.body["model"], "gpt-4o-mini");
+    assert_eq!(req.body["messages"][0]["content"], "why?");
+}
+
+#[tokio::test]
+async fn test_anthropic_messages_request() {
+    let (url, recorded) = mock_server(vec![(
+        200,
+        r#"{"content":[{"type":"text","text":"Root cause: "},{"type":"text","text":"port in use"}]}"#,
+    )])
+    .await;
+
+    let provider = fast(LlmProvider::new(
+        "anthropic",
+        ApiKind::Anthropic,
+        format!("{}/v1", url),
+        Some("sk-ant-test".to_string()),
+        "claude-3-5-haiku-20241022",
+    ));
+    let reply = provider.complete("why?").await.unwrap();
+    assert_eq!(reply, "Root cause: port in use");
+
+    let req = recorded.lock().unwrap()[0].clone();
+    assert!(req.request_line.starts_with("POST /v1/messages"));
+    assert_eq!(req.header("x-api-key"), Some("sk-ant-test"));
+    assert_eq!(req.header("anthropic-version"), Some("2023-06-01"));
+    assert!(req.header("authorization").is_none());
+    assert!(req.body["max_tokens"].as_u64().is_some());
+}
+
+#[tokio::test]
+async fn test_ollama_chat_request() {
+    let (url, recorded) = mock_server(vec![(
+        200,
+        r#"{"message":{"role":"assistant","content":"local says hi"},"done":true}"#,
+    )])
+    .await;
+
+    let provider = fast(LlmProvider::new(
+        "ollama",
+        ApiKind::Ollama,
+        url,
+        None,
+        "llama3.2",
+    ));
+    assert_eq!(provider.complete("hi").await.unwrap(), "local says hi");
+
+    let req = recorded.lock().unwrap()[0].clone();
+    assert!(req.request_line.starts_with("POST /api/chat"));
+    assert_eq!(req.body["stream"], false);
+}
+
+#[tokio::test]
+async fn test_retries_server_errors_then_succeeds() {
+    let (url, recorded) = mock_server(vec![
+        (503, r#"{"error":"overloaded"}"#),
+        (429, r#"{"error":"rate limited"}"#),
+        (200, r#"{"choices":[{"message":{"content":"ok"}}]}"#),
+    ])
+    .await;
+
+    let provider = fast(LlmProvider::new(
+        "openai",
+        ApiKind::OpenAi,
+        url,
+        Some("sk-test".to_string()),
+        "gpt-4o-mini",
+    ));
+    assert_eq!(provider.complete("x").await.unwrap(), "ok");
+    assert_eq!(recorded.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_client_errors_are_not_retried() {
+    let (url, recorded) = mock_server(vec![(401, r#"{"error":"invalid x-api-key"}"#)]).await;
+
+    let provider = fast(LlmProvider::new(
+        "anthropic",
+        ApiKind::Anthropic,
+        url,
+        Some("bad".to_string()),
+        "claude-3-5-haiku-20241022",
+    ));
+    let err = provider.complete("x").await.unwrap_err().to_string();
+    assert!(err.contains("401"));
+    assert_eq!(recorded.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_gives_up_after_max_retries() {
+    let (url, recorded) = mock_server(vec![(500, "{}"), (500, "{}"), (500, "{}")]).await;
+
+    let mut provider = fast(LlmProvider::new(
+        "openai",
+        ApiKind::OpenAi,
+        url,
+        Some("sk-test".to_string()),
+        "gpt-4o-mini",
+    ));
+    provider.max_retries = 2;
+    assert!(provider.complete("x").await.is_err());
+    assert_eq!(recorded.lock().unwrap().len(), 3);
+}